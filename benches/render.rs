@@ -0,0 +1,81 @@
+//! Time to draw a frame of the table, which depends on the size of the terminal rather
+//! than on the number of rows since only a page of them is formatted, and time to sort
+//! and hide a column, which format the page again. Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use polars::prelude::*;
+use ratatui::{backend::TestBackend, Terminal};
+use tabiew::{
+    app::{StatusBar, Tabs, Tabular},
+    ui::render,
+};
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const DRAWS: u32 = 200;
+const CHANGES: u32 = 10;
+
+/// Table of `height` rows with values of the same widths whatever the height, so that
+/// every size draws the same amount of text.
+fn data_frame(height: usize) -> DataFrame {
+    let keys = (0..height).map(|idx| (idx * 7919 % 100_000) as u64);
+    df! {
+        "key" => keys.clone().collect::<Vec<_>>(),
+        "value" => keys.clone().map(|key| key as f64 / 8.0).collect::<Vec<_>>(),
+        "name" => keys.map(|key| format!("name {:05}", key)).collect::<Vec<_>>(),
+    }
+    .unwrap()
+}
+
+/// Mean time of a draw while paging down from the top of the table, so that new pages
+/// are formatted along the way. The draws stay clear of the end of the smallest table.
+fn draw_time(height: usize) -> Duration {
+    let mut tabs = Tabs::new(vec![Tabular::new("bench", "df", data_frame(height))]);
+    let mut status_bar = StatusBar::default();
+    let mut terminal = Terminal::new(TestBackend::new(160, 48)).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..DRAWS {
+        terminal
+            .draw(|frame| render(&mut tabs, &mut status_bar, frame))
+            .unwrap();
+        tabs.selected_mut().page_down();
+    }
+    start.elapsed() / DRAWS
+}
+
+/// Mean time of sorting the table by a column, then of hiding a column and showing it
+/// again.
+fn change_times(height: usize) -> (Duration, Duration) {
+    let mut tabular = Tabular::new("bench", "df", data_frame(height));
+
+    let start = Instant::now();
+    for idx in 0..CHANGES {
+        tabular.sort_selected(idx % 2 == 0).unwrap();
+    }
+    let sort = start.elapsed() / CHANGES;
+
+    let start = Instant::now();
+    for _ in 0..CHANGES {
+        tabular.hide_selected().unwrap();
+        tabular.unhide_all();
+    }
+    (sort, start.elapsed() / CHANGES)
+}
+
+fn main() {
+    println!(
+        "{:>10}  {:>12}  {:>12}  {:>12}",
+        "rows", "draw", "sort", "hide"
+    );
+    for height in SIZES {
+        let (sort, hide) = change_times(height);
+        println!(
+            "{:>10}  {:>12.1?}  {:>12.1?}  {:>12.1?}",
+            height,
+            draw_time(height),
+            sort,
+            hide
+        );
+    }
+}
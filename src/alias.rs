@@ -0,0 +1,147 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crate::{app::AppResult, command::ExecutionTable, utils::config_dir};
+
+/// User defined shorthands for commands and queries, read from lines like
+/// `top = SELECT * FROM df LIMIT $1`. An expansion not starting with `:` is a query.
+#[derive(Debug, Default)]
+pub struct Aliases(HashMap<String, String>);
+
+impl Aliases {
+    /// Loads the aliases from `aliases` in the config directory, if it exists.
+    pub fn load(exec_tbl: &ExecutionTable) -> AppResult<Self> {
+        match config_dir() {
+            Some(dir) => Self::from_file(&dir.join("aliases"), exec_tbl),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path, exec_tbl: &ExecutionTable) -> AppResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content, exec_tbl)
+                .map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Parses one alias per line, skipping empty lines and `#` comments. An alias
+    /// must not shadow a built-in command.
+    pub fn parse(content: &str, exec_tbl: &ExecutionTable) -> Result<Self, String> {
+        let mut aliases = HashMap::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, expansion) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected <name> = <expansion>", idx + 1))?;
+            let name = name.trim().trim_start_matches(':');
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(format!("line {}: invalid alias name '{}'", idx + 1, name));
+            }
+            if exec_tbl.contains_key(format!(":{}", name).as_str()) {
+                return Err(format!(
+                    "line {}: alias '{}' shadows the built-in command :{}",
+                    idx + 1,
+                    name,
+                    name
+                ));
+            }
+            aliases.insert(name.to_owned(), expansion.trim().to_owned());
+        }
+        Ok(Self(aliases))
+    }
+
+    /// Names of the aliases, with the `:` prefix of commands.
+    pub fn names(&self) -> impl Iterator<Item = String> + '_ {
+        self.0.keys().map(|name| format!(":{}", name))
+    }
+
+    /// Expands `command` if it starts with an alias, replacing `$1` to `$9` with its
+    /// arguments. Arguments are appended when the expansion has no placeholders.
+    pub fn expand(&self, command: &str) -> Result<Option<String>, String> {
+        let mut words = command.split_whitespace();
+        let Some(expansion) = words
+            .next()
+            .and_then(|name| name.strip_prefix(':'))
+            .and_then(|name| self.0.get(name))
+        else {
+            return Ok(None);
+        };
+        let args = words.collect::<Vec<_>>();
+
+        let mut expanded = String::with_capacity(expansion.len());
+        let mut placeholders = false;
+        let mut chars = expansion.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek().and_then(|next| next.to_digit(10))) {
+                ('$', Some(digit)) if digit > 0 => {
+                    chars.next();
+                    placeholders = true;
+                    let arg = args.get(digit as usize - 1).ok_or_else(|| {
+                        format!("alias {} expects at least {} argument(s)", command, digit)
+                    })?;
+                    expanded.push_str(arg);
+                }
+                _ => expanded.push(c),
+            }
+        }
+        if !placeholders && !args.is_empty() {
+            expanded.push(' ');
+            expanded.push_str(&args.join(" "));
+        }
+
+        if expanded.starts_with(':') {
+            Ok(Some(expanded))
+        } else {
+            Ok(Some(format!(":Q {}", expanded)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandList;
+
+    fn aliases(content: &str) -> Result<Aliases, String> {
+        Aliases::parse(content, &CommandList::default().into_exec())
+    }
+
+    #[test]
+    fn test_alias_expansion() {
+        let aliases = aliases(
+            "# shorthands\n\
+             head = SELECT * FROM df LIMIT $1\n\
+             \n\
+             :top = :goto\n\
+             between = SELECT * FROM df WHERE id > $1 AND id < $2 -- costs $ 0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            aliases.expand(":head 20").unwrap().as_deref(),
+            Some(":Q SELECT * FROM df LIMIT 20")
+        );
+        assert_eq!(
+            aliases.expand(":top 5").unwrap().as_deref(),
+            Some(":goto 5")
+        );
+        assert_eq!(
+            aliases.expand(":between 1 9").unwrap().as_deref(),
+            Some(":Q SELECT * FROM df WHERE id > 1 AND id < 9 -- costs $ 0")
+        );
+        assert!(aliases.expand(":between 1").is_err());
+        assert_eq!(aliases.expand(":goto 3").unwrap(), None);
+        assert_eq!(aliases.expand("/head").unwrap(), None);
+    }
+
+    #[test]
+    fn test_alias_validation() {
+        assert!(aliases("q = :quit").unwrap_err().contains("shadows"));
+        assert!(aliases("goto = :goup 1").unwrap_err().contains("shadows"));
+        assert!(aliases("no equals sign").is_err());
+        assert!(aliases("two words = :q").is_err());
+    }
+}
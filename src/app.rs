@@ -1,12 +1,50 @@
-use std::error;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    error,
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{KeyCode, KeyEvent};
-use polars::frame::DataFrame;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use polars::{
+    frame::DataFrame,
+    lazy::frame::LazyFrame,
+    prelude::{
+        col, AnyValue, BooleanChunked, DataType, Expr, IdxCa, IdxSize, IntoLazy, Series,
+        SortMultipleOptions,
+    },
+};
+use polars_sql::{sql_expr, SQLContext};
 use rand::Rng;
+use ratatui::{style::Color, Frame};
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
+    alias::Aliases,
+    args::AppTheme,
+    command::{CommandList, ExecutionTable},
     command_pallete::CommandPalleteState,
-    utils::{data_frame_widths, Scroll, TableValues},
+    completion::complete,
+    event::Event,
+    filter::{self, ColumnFilter},
+    follow::{Follow, Growth},
+    handler::{execute_command, handle_key_events, handle_mouse_events},
+    history::History,
+    keymap::{Action, Help, Keymap},
+    palette::Palette,
+    pending::PendingQuery,
+    reader::{LazySource, Origin, SourceFile, Table, Truncation},
+    search::{Search, SearchOptions},
+    state::Place,
+    stats::{
+        ColumnStats, Histogram, SchemaOverview, ValueCounts, HISTOGRAM_BUCKETS, VALUE_COUNTS_TOP,
+    },
+    ui,
+    utils::{
+        any_value_into_string, cast_series, data_frame_widths, parse_value, set_cell, wrap_text,
+        NumberFormat, Scroll, TableValues,
+    },
 };
 
 /// Application result type.
@@ -15,86 +53,1901 @@ pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 /// Application.
 #[derive(Debug)]
 pub struct Tabular {
+    pub name: String,
+    pub table_name: String,
+    /// Other name of the table in the SQL context, derived from the file name.
+    pub stem_name: Option<String>,
     pub offset: usize,
     pub select: usize,
+    /// Row where the selected range starts, the range extending to the selected row.
+    pub range_start: Option<usize>,
+    /// Rows bookmarked with `m`, by their row in the source, or in the result of the
+    /// query, so that they follow the sort and filters.
+    pub bookmarks: BTreeSet<usize>,
+    /// Whether the bookmarks were dropped as the rows were replaced, see
+    /// [`Tabular::take_dropped_bookmarks`].
+    dropped_bookmarks: bool,
+    pub select_column: usize,
+    pub column_offset: usize,
+    pub frozen: usize,
     pub rendered_rows: u16,
+    pub rendered_columns: Range<usize>,
+    pub cell_areas: CellAreas,
     pub widths: Vec<usize>,
+    /// Render widths set with `+` and `-`, by column name.
+    pub column_widths: HashMap<String, usize>,
+    pub fit_content: bool,
+    /// Widest the columns get unless they are resized, their values being cut with `…`,
+    /// without a limit if zero.
+    pub max_width: usize,
+    /// Columns whose values wrap over several lines rather than being cut, by name.
+    pub wrapped: HashSet<String>,
+    /// Whether the values of all the columns wrap, with `--wrap`.
+    pub wrap_all: bool,
     pub headers: Vec<String>,
+    pub dtypes: Vec<String>,
+    /// Whether the columns hold numbers, which are right-aligned.
+    pub numeric: Vec<bool>,
+    /// Display of the numbers, set with [`Tabular::set_number_format`].
+    pub number_format: NumberFormat,
+    /// Whether the booleans are shown as symbols and the values of categorical columns
+    /// colored, set with [`Tabular::set_symbols`].
+    pub symbols: bool,
+    /// Symbols of the values of each column, all plain without [`Tabular::symbols`].
+    pub column_symbols: Vec<ColumnSymbols>,
+    pub show_types: bool,
+    pub highlight_nulls: bool,
+    /// Rules coloring the rows they match, set with `:highlight`, the latest winning.
+    pub highlights: Vec<Highlight>,
+    pub line_numbers: bool,
+    /// Whether every other row is shaded, in the themes with a background.
+    pub stripes: bool,
+    /// Number of the first row in the line numbers, 0 or 1.
+    pub first_line_number: usize,
+    pub last_query: Option<String>,
+    /// Query the data frame is the result of, `None` while it holds the source or the
+    /// output of a command.
+    pub view_query: Option<String>,
+    /// Time the query of the view took and the rows of its result.
+    pub query_timing: Option<(Duration, usize)>,
+    /// Query running in the background, whose result replaces the data frame.
+    pub pending: Option<PendingQuery>,
+    /// Rendered values of a page of rows around the viewport.
     pub table_values: TableValues,
+    /// Rows formatted into [`Tabular::table_values`] at a time.
+    pub page_size: usize,
     pub detailed_view: Option<Scroll>,
+    /// Index of the first column listed in the record view, when it is shown.
+    pub record_view: Option<usize>,
+    pub cell_popup: Option<Scroll>,
+    /// Popup with the full description of the view, see [`Tabular::provenance`].
+    pub view_popup: Option<Scroll>,
+    pub stats: Option<ColumnStats>,
+    /// Counts of the values of a column, when they are shown.
+    pub value_counts: Option<ValueCounts>,
+    /// Histogram of a numeric column, when it is shown.
+    pub histogram: Option<Histogram>,
+    /// Overview of the columns, when it is shown.
+    pub schema: Option<SchemaOverview>,
+    pub search: Option<Search>,
+    pub sort: Option<Sort>,
+    /// Whether the columns were last sorted in descending order, by name, which the
+    /// `sort` action starts from.
+    pub sort_directions: BTreeMap<String, bool>,
+    pub filters: Vec<ColumnFilter>,
+    pub hidden: HashSet<String>,
+    /// Display order of the columns moved with [`Tabular::move_selected`], by name. The
+    /// columns not listed follow in the order of the data frame.
+    pub column_order: Vec<String>,
+    /// Whether exports, copies and SQL queries see the columns in the order of the data
+    /// frame rather than in the display order.
+    pub keep_column_order: bool,
+    pub data_frame: DataFrame,
+    /// Data frame the table was created with, before any query.
+    source: DataFrame,
+    /// Scan of the source with `--lazy`, of which `data_frame` holds a window of rows
+    /// starting at `window_start` while `windowed`.
+    lazy: Option<LazySource>,
+    windowed: bool,
+    window_start: usize,
+    /// Data frame before the interactive sort and filters.
+    original: Option<DataFrame>,
+    /// Rows of the original shown in the sorted or filtered data frame, in order.
+    view_rows: Option<IdxCa>,
+    /// File the source was read from, which `:write` writes the edits to.
+    pub file: Option<SourceFile>,
+    /// Where the table was read from, to read it again with `:reload`.
+    pub origin: Option<Origin>,
+    /// Query the table opened with `:as` is the result of, which a saved session runs
+    /// again, see [`Tabular::defining_query`].
+    pub derivation: Option<String>,
+    /// Whether the source has edits that are not written yet.
+    pub modified: bool,
+    /// File read again as it grows, with `--follow`.
+    pub follow: Option<Follow>,
+    /// How the rows of the source were cut at load time, shown in the status bar.
+    pub truncation: Option<Truncation>,
+    /// Columns loaded with `--columns`, which the source keeps when it is read again,
+    /// all of them if empty.
+    pub projection: Vec<String>,
+    /// Columns of the projection missing from the source read again, see
+    /// [`Tabular::take_dropped_columns`].
+    dropped_columns: Vec<String>,
+    /// Absolute path of the file the table was read from, under which its place is
+    /// saved when leaving.
+    pub source_path: Option<PathBuf>,
+    /// Changes reverting the last changes, the latest last.
+    undo: Vec<Change>,
+    /// Changes making the undone changes again, the latest undone last.
+    redo: Vec<Change>,
+    /// Whether the data frame holds the result of a query or a command rather than
+    /// the source, which only is editable.
+    queried: bool,
+    last_click: Option<(Instant, usize, usize)>,
+}
+
+/// Changes that can be undone.
+const UNDO_DEPTH: usize = 32;
+
+/// Column numbering the rows of the original while the view is computed.
+const VIEW_ROW: &str = "__tabiew_view_row";
+
+/// Column of the value of a highlight rule while it is evaluated.
+const HIGHLIGHT_ROW: &str = "__tabiew_highlight_row";
+
+/// Rows formatted at a time by default, see [`Tabular::page_size`].
+pub const PAGE_SIZE: usize = 1000;
+
+/// Widest a column gets by default unless it is resized, see [`Tabular::max_width`].
+pub const MAX_WIDTH: usize = 40;
+
+/// Widest a wrapped column gets unless it is resized, its values wrapping to it.
+const WRAP_WIDTH: usize = 40;
+
+/// Maximum time between the two clicks of a double-click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Bookmarks of the rows left once the sorted `rows` are removed.
+fn removed_bookmarks(bookmarks: &BTreeSet<usize>, rows: &[usize]) -> BTreeSet<usize> {
+    bookmarks
+        .iter()
+        .filter(|row| rows.binary_search(row).is_err())
+        .map(|row| row - rows.partition_point(|removed| removed < row))
+        .collect()
+}
+
+/// Bookmarks of the rows moved by inserting rows at the sorted positions `rows`.
+fn inserted_bookmarks(bookmarks: &BTreeSet<usize>, rows: &[usize]) -> BTreeSet<usize> {
+    bookmarks
+        .iter()
+        .map(|row| {
+            rows.iter()
+                .fold(*row, |row, inserted| row + (*inserted <= row) as usize)
+        })
+        .collect()
 }
 
-impl Tabular {
-    /// Constructs a new instance of [`App`].
-    pub fn new(data_frame: DataFrame) -> Self {
-        Self {
-            offset: 0,
-            select: 0,
-            rendered_rows: 0,
-            widths: data_frame_widths(&data_frame),
-            headers: data_frame.get_column_names().into_iter().map(ToOwned::to_owned).collect(),
-            table_values: TableValues::from_dataframe(data_frame),
-            detailed_view: None,
-        }
+/// Value of the boolean expression `predicate` for each row of `data_frame`.
+fn highlighted_rows(data_frame: &DataFrame, predicate: &Expr) -> AppResult<BooleanChunked> {
+    let rows = data_frame
+        .clone()
+        .lazy()
+        .with_column(predicate.clone().alias(HIGHLIGHT_ROW))
+        .select([col(HIGHLIGHT_ROW)])
+        .collect()?;
+    Ok(rows.column(HIGHLIGHT_ROW)?.bool()?.clone())
+}
+
+/// Symbols of the values of `series`, categories counted only if `count` is set.
+fn column_symbols(series: &Series, count: bool) -> ColumnSymbols {
+    match series.dtype() {
+        DataType::Boolean => ColumnSymbols::Boolean,
+        // values repeating at least once on average, as in a column of labels
+        DataType::String
+            if count
+                && series
+                    .n_unique()
+                    .is_ok_and(|unique| unique <= CATEGORY_LIMIT && unique * 2 <= series.len()) =>
+        {
+            ColumnSymbols::Category
+        }
+        _ => ColumnSymbols::Plain,
+    }
+}
+
+/// Terminal coordinates of the last drawn table, to find the cell under the mouse.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellAreas {
+    /// Column indices and the x-ranges they were drawn in.
+    pub columns: Vec<(usize, Range<u16>)>,
+    /// Y-range of the header.
+    pub header: Range<u16>,
+    /// Y-range of the data rows, the first of which shows the row at the offset.
+    pub rows: Range<u16>,
+    /// Lines of each drawn row, when some are taller than one line.
+    pub row_heights: Vec<u16>,
+}
+
+impl CellAreas {
+    /// Column drawn at `x`.
+    pub fn column_at(&self, x: u16) -> Option<usize> {
+        self.columns
+            .iter()
+            .find(|(_, xs)| xs.contains(&x))
+            .map(|(col, _)| *col)
+    }
+
+    /// Position of `y` among the data rows.
+    pub fn row_at(&self, y: u16) -> Option<usize> {
+        if !self.rows.contains(&y) {
+            return None;
+        }
+        let line = y - self.rows.start;
+        if self.row_heights.is_empty() {
+            return Some(line as usize);
+        }
+        let mut end = 0;
+        self.row_heights.iter().position(|height| {
+            end += height;
+            line < end
+        })
+    }
+}
+
+/// Interactive sort of a [`Tabular`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// How the values of a column are shown with `--symbols`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnSymbols {
+    Plain,
+    /// Booleans, shown as the symbols of the theme.
+    Boolean,
+    /// Strings with few distinct values, each colored after its text.
+    Category,
+}
+
+/// Most distinct values of a string column colored as categories.
+pub const CATEGORY_LIMIT: usize = 12;
+
+/// Rule of `:highlight` coloring the rows for which a SQL expression is true.
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub expr: String,
+    pub color: Color,
+    predicate: Expr,
+    /// Value of the expression for the rows of the data frame, empty if it does not
+    /// apply to it, as after a query dropping the columns it uses.
+    rows: BooleanChunked,
+}
+
+/// Change of a [`Tabular`] that undo reverts and redo makes again. Only what is needed
+/// to revert a change is kept rather than a copy of the data frame: the column before
+/// an edit, the deleted rows, or the sort and filters.
+#[derive(Debug, Clone)]
+enum Change {
+    /// Replaces the column of the same name in the source.
+    SetColumn(Series),
+    /// Inserts rows into the source, which end up at the indices, in ascending order.
+    InsertRows(Vec<usize>, DataFrame),
+    /// Removes the rows at the indices, in ascending order, from the source.
+    RemoveRows(Vec<usize>),
+    /// Sorts and filters the rows again.
+    View(Option<Sort>, Vec<ColumnFilter>),
+}
+
+/// Pushes `change` onto `stack`, forgetting the oldest change past [`UNDO_DEPTH`].
+fn push_change(stack: &mut Vec<Change>, change: Change) {
+    if stack.len() == UNDO_DEPTH {
+        stack.remove(0);
+    }
+    stack.push(change);
+}
+
+impl Tabular {
+    /// Constructs a new instance of [`App`], displayed as `name` and registered as
+    /// `table_name` in the SQL context.
+    pub fn new(name: impl ToString, table_name: impl ToString, data_frame: DataFrame) -> Self {
+        let mut tabular = Self {
+            name: name.to_string(),
+            table_name: table_name.to_string(),
+            stem_name: None,
+            offset: 0,
+            select: 0,
+            range_start: None,
+            bookmarks: BTreeSet::new(),
+            dropped_bookmarks: false,
+            select_column: 0,
+            column_offset: 0,
+            frozen: 0,
+            rendered_rows: 0,
+            rendered_columns: 0..0,
+            cell_areas: CellAreas::default(),
+            widths: Vec::new(),
+            column_widths: HashMap::new(),
+            fit_content: false,
+            max_width: MAX_WIDTH,
+            wrapped: HashSet::new(),
+            wrap_all: false,
+            headers: Vec::new(),
+            dtypes: Vec::new(),
+            numeric: Vec::new(),
+            number_format: NumberFormat::default(),
+            symbols: false,
+            column_symbols: Vec::new(),
+            show_types: false,
+            highlight_nulls: false,
+            highlights: Vec::new(),
+            line_numbers: false,
+            stripes: true,
+            first_line_number: 1,
+            last_query: None,
+            view_query: None,
+            query_timing: None,
+            pending: None,
+            table_values: TableValues::from_dataframe(DataFrame::empty()),
+            page_size: PAGE_SIZE,
+            detailed_view: None,
+            record_view: None,
+            cell_popup: None,
+            view_popup: None,
+            stats: None,
+            value_counts: None,
+            histogram: None,
+            schema: None,
+            search: None,
+            sort: None,
+            sort_directions: BTreeMap::new(),
+            filters: Vec::new(),
+            hidden: HashSet::new(),
+            column_order: Vec::new(),
+            keep_column_order: false,
+            source: data_frame.clone(),
+            data_frame,
+            lazy: None,
+            windowed: false,
+            window_start: 0,
+            original: None,
+            view_rows: None,
+            file: None,
+            origin: None,
+            derivation: None,
+            modified: false,
+            follow: None,
+            truncation: None,
+            projection: Vec::new(),
+            dropped_columns: Vec::new(),
+            source_path: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            queried: false,
+            last_click: None,
+        };
+        tabular.refresh_values();
+        tabular
+    }
+
+    /// Constructs a table of a lazy source, of which `data_frame` holds the first rows.
+    pub fn new_lazy(
+        name: impl ToString,
+        table_name: impl ToString,
+        data_frame: DataFrame,
+        lazy: LazySource,
+    ) -> Self {
+        let mut tabular = Self::new(name, table_name, data_frame);
+        tabular.lazy = Some(lazy);
+        tabular.windowed = true;
+        tabular.refresh_values();
+        tabular
+    }
+
+    /// Formats the page of rows around the viewport and the selected row when they are
+    /// out of the loaded one, so that drawing does not depend on the number of rows.
+    /// With `--lazy` the page is first collected from the lazy source.
+    pub fn load_page(&mut self) -> AppResult<()> {
+        let height = self.table_values.height();
+        let needed = self.viewport_rows(height);
+        let loaded = self.table_values.window();
+        if loaded.start <= needed.start && needed.end <= loaded.end {
+            return Ok(());
+        }
+        let page = self.page_around(needed, height);
+        let Some(lazy) = self.lazy.as_ref().filter(|_| self.windowed) else {
+            let view = self
+                .visible_data_frame()
+                .slice(page.start as i64, page.len());
+            let widths = data_frame_widths(&view, self.number_format);
+            self.widen(widths);
+            self.table_values
+                .replace_window(view, page.start, height, self.number_format);
+            return Ok(());
+        };
+        self.data_frame = lazy
+            .frame
+            .clone()
+            .slice(page.start as i64, page.len() as IdxSize)
+            .with_streaming(true)
+            .collect()?;
+        // the streaming engine returns several chunks, which the row iterators reject
+        self.data_frame.as_single_chunk();
+        self.window_start = page.start;
+        // the search stays for the rows of the next windows
+        let widths = std::mem::take(&mut self.widths);
+        let search = self.search.take();
+        self.refresh_values();
+        self.search = search;
+        self.widen(widths);
+        Ok(())
+    }
+
+    /// Widens the columns to `widths` where they are wider, so that the columns only
+    /// widen while scrolling rather than changing with every page.
+    fn widen(&mut self, widths: Vec<usize>) {
+        if widths.len() == self.widths.len() {
+            for (width, new) in self.widths.iter_mut().zip(widths) {
+                *width = (*width).max(new);
+            }
+        }
+    }
+
+    /// Rows of the viewport and the selected row in a table of `height` rows.
+    fn viewport_rows(&self, height: usize) -> Range<usize> {
+        self.offset.min(self.select).min(height)
+            ..(self.offset + self.rendered_rows as usize)
+                .max(self.select + 1)
+                .min(height)
+    }
+
+    /// Page of [`Tabular::page_size`] rows centered on `rows` in a table of `height`
+    /// rows, or `rows` if they are more.
+    fn page_around(&self, rows: Range<usize>, height: usize) -> Range<usize> {
+        let len = self.page_size.max(rows.len());
+        let start = rows
+            .start
+            .saturating_sub((len - rows.len()) / 2)
+            .min(height.saturating_sub(len));
+        start..(start + len).min(height)
+    }
+
+    /// Fails for the operations needing all the rows while only a window of a lazy
+    /// source is loaded.
+    pub fn require_loaded(&self, what: &str) -> AppResult<()> {
+        if self.windowed {
+            Err(format!(
+                "{} needs all the rows, which --lazy does not load; query them first, e.g. :Q SELECT * FROM {}",
+                what, self.table_name
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Handles the tick event of the terminal, which only comes every `--tick-rate`
+    /// milliseconds while [`Tabular::needs_tick`] or [`StatusBar::needs_tick`] is true.
+    /// Reads the rows appended to the followed file, registering the source again in
+    /// `sql`, and shows the result of the query running in the background once it is
+    /// done, or fails with its error.
+    pub fn tick(&mut self, sql: &mut SQLContext) -> AppResult<()> {
+        let follow = self.follow.as_mut().filter(|follow| !follow.paused);
+        if let Some(growth) = follow.map(Follow::poll).transpose()?.flatten() {
+            match growth {
+                Growth::Appended(rows) => self.append_rows(&rows)?,
+                Growth::Reloaded(rows) => self.reload_source(rows)?,
+            }
+            self.register(sql);
+        }
+        let Some(result) = self.pending.as_mut().and_then(PendingQuery::poll) else {
+            return Ok(());
+        };
+        let (query, elapsed) = self
+            .pending
+            .take()
+            .map(|pending| (pending.query().to_owned(), pending.elapsed()))
+            .unwrap_or_default();
+        self.show_result(query, elapsed, result?);
+        Ok(())
+    }
+
+    /// Whether there is tick driven work, like an animation or a file to follow, pending.
+    pub fn needs_tick(&self) -> bool {
+        self.pending.is_some() || self.follow.as_ref().is_some_and(|follow| !follow.paused)
+    }
+
+    /// Collects `frame`, the plan of `query`, in the background, cancelling the query
+    /// already running if any.
+    pub fn start_query(&mut self, query: String, frame: LazyFrame) -> AppResult<()> {
+        self.cancel_query();
+        self.pending = Some(PendingQuery::start(query, frame)?);
+        Ok(())
+    }
+
+    /// Cancels the query running in the background, false if there is none.
+    pub fn cancel_query(&mut self) -> bool {
+        self.pending.take().map(PendingQuery::cancel).is_some()
+    }
+
+    /// Blocks until the query running in the background is done and shows its result.
+    pub fn wait_query(&mut self) -> AppResult<()> {
+        if let Some(pending) = self.pending.take() {
+            let data_frame = pending.wait()?;
+            self.show_result(pending.query().to_owned(), pending.elapsed(), data_frame);
+        }
+        Ok(())
+    }
+
+    /// Shows the result of `query`, which took `elapsed`, and remembers the query for
+    /// `:save-query`.
+    fn show_result(&mut self, query: String, elapsed: Duration, data_frame: DataFrame) {
+        let rows = data_frame.height();
+        self.set_data_frame(data_frame);
+        self.query_timing = Some((elapsed, rows));
+        self.view_query = Some(query.clone());
+        self.last_query = Some(query);
+    }
+
+    /// What the data frame shows: `(full)` for the source, else the query it is the
+    /// result of.
+    pub fn provenance(&self) -> &str {
+        match &self.view_query {
+            _ if !self.queried => "(full)",
+            Some(query) => query,
+            None => "(command output)",
+        }
+    }
+
+    /// Query of the SQL context giving the rows shown: the query of the view, or all the
+    /// rows of the source. `None` for the output of a command, and for the filtered or
+    /// sorted rows and the edits not written, which the query does not give.
+    pub fn defining_query(&self) -> Option<String> {
+        if !self.filters.is_empty() || self.sort.is_some() || self.modified {
+            return None;
+        }
+        match &self.view_query {
+            _ if !self.queried => Some(format!("SELECT * FROM {}", self.table_name)),
+            Some(query) => Some(query.clone()),
+            None => None,
+        }
+    }
+
+    pub fn select_up(&mut self, len: usize) {
+        self.select(self.select.saturating_sub(len))
+    }
+
+    pub fn select_down(&mut self, len: usize) {
+        self.select(self.select + len);
+    }
+
+    /// Scrolls a screen up, keeping the selected row at the same place on the screen.
+    pub fn page_up(&mut self) {
+        let len = self.rendered_rows as usize;
+        self.offset = self.offset.saturating_sub(len);
+        self.select_up(len);
+    }
+
+    /// Scrolls a screen down, keeping the selected row at the same place on the screen.
+    pub fn page_down(&mut self) {
+        let len = self.rendered_rows as usize;
+        self.offset = (self.offset + len).min(self.table_values.height().saturating_sub(len));
+        self.select_down(len);
+    }
+
+    pub fn select_first(&mut self) {
+        self.select(usize::MIN)
+    }
+
+    pub fn select_last(&mut self) {
+        self.select(usize::MAX);
+    }
+
+    /// Whether the table has no rows, for which the keys moving the selection do nothing.
+    pub fn is_empty(&self) -> bool {
+        self.table_values.height() == 0
+    }
+
+    pub fn select_random(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        self.select(rng.gen_range(0..self.table_values.height()))
+    }
+
+    pub fn select(&mut self, select: usize) {
+        self.select = select.min(self.table_values.height().saturating_sub(1))
+    }
+
+    pub fn select_left(&mut self) {
+        self.select_column = self.select_column.saturating_sub(1);
+    }
+
+    pub fn select_right(&mut self) {
+        self.select_column = (self.select_column + 1).min(self.headers.len().saturating_sub(1));
+    }
+
+    /// Selects a column whose name starts with `name`, ignoring the case: the one named
+    /// `name` if there is one, and when a match is selected already, the next match, so
+    /// that repeating it goes through them. Returns the position of the selected match
+    /// and the number of them.
+    pub fn select_column_named(&mut self, name: &str) -> AppResult<(usize, usize)> {
+        let pattern = name.to_lowercase();
+        let matches = self
+            .headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| header.to_lowercase().starts_with(&pattern))
+            .map(|(col, _)| col)
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            let hidden = self
+                .hidden
+                .iter()
+                .any(|column| column.to_lowercase().starts_with(&pattern));
+            return Err(match hidden {
+                true => format!("'{}' is hidden, U shows the hidden columns", name),
+                false => format!("no column named '{}'", name),
+            }
+            .into());
+        }
+        let position = match matches.iter().position(|col| *col == self.select_column) {
+            Some(position) => (position + 1) % matches.len(),
+            None => matches
+                .iter()
+                .position(|col| self.headers[*col].to_lowercase() == pattern)
+                .unwrap_or(0),
+        };
+        self.select_column = matches[position];
+        Ok((position + 1, matches.len()))
+    }
+
+    /// Colors the rows for which the SQL expression `expr` is true with `color`, over
+    /// the colors of earlier rules.
+    pub fn highlight(&mut self, expr: &str, color: Color) -> AppResult<()> {
+        let predicate = sql_expr(expr)?;
+        let rows = highlighted_rows(&self.data_frame, &predicate)?;
+        self.highlights.push(Highlight {
+            expr: expr.to_owned(),
+            color,
+            predicate,
+            rows,
+        });
+        Ok(())
+    }
+
+    /// Color of the latest highlight rule matching the row, if any.
+    pub fn row_color(&self, row: usize) -> Option<Color> {
+        let row = row.checked_sub(self.window_start)?;
+        self.highlights
+            .iter()
+            .rev()
+            .find(|highlight| highlight.rows.get(row).unwrap_or(false))
+            .map(|highlight| highlight.color)
+    }
+
+    /// Bookmarks the selected row, or removes its bookmark.
+    pub fn toggle_bookmark(&mut self) {
+        if self.select >= self.table_values.height() {
+            return;
+        }
+        let row = self.source_row(self.select);
+        if !self.bookmarks.remove(&row) {
+            self.bookmarks.insert(row);
+        }
+    }
+
+    /// Whether `row` of the data frame is bookmarked.
+    pub fn is_bookmarked(&self, row: usize) -> bool {
+        !self.bookmarks.is_empty()
+            && row < self.table_values.height()
+            && self.bookmarks.contains(&self.source_row(row))
+    }
+
+    /// Selects the next bookmarked row after the selected one, or the previous one
+    /// before it, wrapping around. Returns false if no bookmarked row is shown.
+    pub fn select_bookmark(&mut self, forward: bool) -> bool {
+        let height = self.table_values.height();
+        let rows = match &self.view_rows {
+            Some(_) => (0..height)
+                .filter(|row| self.is_bookmarked(*row))
+                .collect::<Vec<_>>(),
+            None => self.bookmarks.range(..height).copied().collect(),
+        };
+        let row = match forward {
+            true => rows.iter().find(|row| **row > self.select).or(rows.first()),
+            false => rows
+                .iter()
+                .rev()
+                .find(|row| **row < self.select)
+                .or(rows.last()),
+        };
+        match row.copied() {
+            Some(row) => {
+                self.select(row);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the bookmarks were dropped since the last call, as the result of a
+    /// query replaced the rows.
+    pub fn take_dropped_bookmarks(&mut self) -> bool {
+        std::mem::take(&mut self.dropped_bookmarks)
+    }
+
+    /// Columns of the projection dropped since the last call, as the source read again
+    /// no longer has them.
+    pub fn take_dropped_columns(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.dropped_columns)
+    }
+
+    fn drop_bookmarks(&mut self) {
+        self.dropped_bookmarks |= !self.bookmarks.is_empty();
+        self.bookmarks.clear();
+    }
+
+    /// Starts a range of rows at the selected row, or drops the selected range.
+    pub fn toggle_range(&mut self) {
+        self.range_start = match self.range_start {
+            Some(_) => None,
+            None => Some(self.select),
+        };
+    }
+
+    /// Starts a range of rows at the selected row unless a range is selected already.
+    pub fn start_range(&mut self) {
+        self.range_start.get_or_insert(self.select);
+    }
+
+    /// Rows of the selected range, in order.
+    pub fn selected_range(&self) -> Option<Range<usize>> {
+        self.range_start
+            .map(|start| start.min(self.select)..start.max(self.select) + 1)
+    }
+
+    /// Data frame as displayed, only the rows of the selected range if there is one.
+    pub fn selected_data_frame(&self) -> DataFrame {
+        let data_frame = self.output_data_frame();
+        match self.selected_range() {
+            Some(range) => data_frame.slice(range.start as i64, range.len()),
+            None => data_frame,
+        }
+    }
+
+    /// Sorts the rows by the selected column, placing nulls last. Sorting again by the
+    /// same column in the same direction flips the direction.
+    pub fn sort_selected(&mut self, descending: bool) -> AppResult<()> {
+        self.require_loaded("sorting")?;
+        let Some(column) = self.headers.get(self.select_column).cloned() else {
+            return Ok(());
+        };
+        let descending = match &self.sort {
+            Some(sort) if sort.column == column && sort.descending == descending => !descending,
+            _ => descending,
+        };
+        self.sort_directions.insert(column.clone(), descending);
+        self.record(Change::View(
+            Some(Sort { column, descending }),
+            self.filters.clone(),
+        ))
+    }
+
+    /// Sorts by the selected column in the order it was last sorted in, or if it never
+    /// was, newest first for dates and ascending for other columns. Sorting by the same
+    /// column again flips the order.
+    pub fn sort_selected_again(&mut self) -> AppResult<()> {
+        let Some(column) = self.headers.get(self.select_column) else {
+            return Ok(());
+        };
+        let descending = match (&self.sort, self.sort_directions.get(column)) {
+            (Some(sort), _) if sort.column == *column => sort.descending,
+            (_, Some(descending)) => *descending,
+            _ => matches!(
+                self.data_frame.column(column)?.dtype(),
+                DataType::Date | DataType::Datetime(_, _)
+            ),
+        };
+        self.sort_selected(descending)
+    }
+
+    /// Restores the order the rows had before sorting.
+    pub fn reset_sort(&mut self) {
+        if self.sort.is_some() {
+            // the filters alone were applied before, they do not fail
+            let _ = self.record(Change::View(None, self.filters.clone()));
+        }
+    }
+
+    /// Filters the rows on the selected column with `predicate`, in addition to the
+    /// current filters.
+    pub fn filter_selected(&mut self, predicate: &str) -> AppResult<()> {
+        self.require_loaded("filtering")?;
+        let Some(column) = self.headers.get(self.select_column) else {
+            return Ok(());
+        };
+        let dtype = self.data_frame.column(column)?.dtype().clone();
+        let filter = ColumnFilter::new(column, &dtype, predicate)?;
+        let mut filters = self.filters.clone();
+        filters.push(filter);
+        self.record(Change::View(self.sort.clone(), filters))?;
+        self.select = 0;
+        self.offset = 0;
+        Ok(())
+    }
+
+    /// Removes the column filters, keeping the sort.
+    pub fn clear_filters(&mut self) {
+        if !self.filters.is_empty() {
+            let _ = self.record(Change::View(self.sort.clone(), Vec::new()));
+        }
+    }
+
+    /// Recomputes the data frame from the original with the filters and the sort.
+    fn apply_view(&mut self) -> AppResult<()> {
+        let original = self.original.as_ref().unwrap_or(&self.data_frame);
+        if self.filters.is_empty() && self.sort.is_none() {
+            if let Some(original) = self.original.take() {
+                self.data_frame = original;
+            }
+            self.view_rows = None;
+            self.refresh_values();
+            return Ok(());
+        }
+        let mut view = original.clone().lazy().with_row_index(VIEW_ROW, None);
+        if let Some(expr) = filter::combine(&self.filters, &original.schema())? {
+            view = view.filter(expr);
+        }
+        if let Some(sort) = &self.sort {
+            view = view.sort(
+                [sort.column.as_str()],
+                SortMultipleOptions::default()
+                    .with_order_descending(sort.descending)
+                    .with_nulls_last(true)
+                    .with_maintain_order(true),
+            );
+        }
+        let mut view = view.collect()?;
+        self.view_rows = Some(view.drop_in_place(VIEW_ROW)?.idx()?.clone());
+        if self.original.is_none() {
+            self.original = Some(std::mem::replace(&mut self.data_frame, view));
+        } else {
+            self.data_frame = view;
+        }
+        self.refresh_values();
+        Ok(())
+    }
+
+    /// Hides the selected column from the view, it is still part of the data frame.
+    pub fn hide_selected(&mut self) -> AppResult<()> {
+        if self.headers.len() <= 1 {
+            return Err("cannot hide the last visible column".into());
+        }
+        if let Some(column) = self.headers.get(self.select_column) {
+            self.hidden.insert(column.clone());
+            self.refresh_values();
+        }
+        Ok(())
+    }
+
+    /// Shows all the hidden columns, keeping the selected one.
+    pub fn unhide_all(&mut self) {
+        let selected = self.headers.get(self.select_column).cloned();
+        self.hidden.clear();
+        self.refresh_values();
+        if let Some(idx) = selected.and_then(|col| self.headers.iter().position(|h| *h == col)) {
+            self.select_column = idx;
+        }
+    }
+
+    /// Moves the selected column before the previous visible one, or after the next with
+    /// `right`, in the display order. The column stays selected.
+    pub fn move_selected(&mut self, right: bool) {
+        let target = match right {
+            true => self.select_column + 1,
+            false => match self.select_column.checked_sub(1) {
+                Some(target) => target,
+                None => return,
+            },
+        };
+        let (Some(column), Some(neighbour)) = (
+            self.headers.get(self.select_column),
+            self.headers.get(target),
+        ) else {
+            return;
+        };
+        let mut order = self
+            .ordered(self.data_frame.get_column_names())
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        let from = order.iter().position(|name| name == column);
+        let to = order.iter().position(|name| name == neighbour);
+        if let (Some(from), Some(to)) = (from, to) {
+            order.swap(from, to);
+        }
+        self.column_order = order;
+        self.refresh_values();
+        self.select_column = target;
+    }
+
+    /// `columns` of a data frame in the display order.
+    fn ordered<'a>(&self, columns: Vec<&'a str>) -> Vec<&'a str> {
+        let mut ordered = self
+            .column_order
+            .iter()
+            .filter_map(|name| columns.iter().find(|column| *column == name).copied())
+            .collect::<Vec<_>>();
+        ordered.extend(
+            columns
+                .iter()
+                .filter(|column| !self.column_order.iter().any(|name| name == *column)),
+        );
+        ordered
+    }
+
+    /// Place in the table to restore the next time its file is opened, `None` while it
+    /// shows the result of a query.
+    pub fn place(&self) -> Option<Place> {
+        (!self.queried).then(|| Place {
+            select: self.select,
+            offset: self.offset,
+            select_column: self.select_column,
+            column_offset: self.column_offset,
+            sort: self.sort.clone(),
+            directions: self
+                .sort_directions
+                .iter()
+                .map(|(column, descending)| Sort {
+                    column: column.clone(),
+                    descending: *descending,
+                })
+                .collect(),
+            hidden: self.hidden.iter().cloned().collect(),
+        })
+    }
+
+    /// Restores a place saved with [`Tabular::place`], skipping the columns that are
+    /// gone and keeping the rows in the table. The sort is not a change to undo.
+    pub fn restore(&mut self, place: &Place) {
+        let columns = self.data_frame.get_column_names();
+        self.hidden = place
+            .hidden
+            .iter()
+            .filter(|column| columns.contains(&column.as_str()))
+            .cloned()
+            .collect();
+        if self.hidden.len() >= columns.len() {
+            self.hidden.clear();
+        }
+        self.refresh_values();
+        self.sort_directions = place
+            .directions
+            .iter()
+            .map(|sort| (sort.column.clone(), sort.descending))
+            .collect();
+        let sort = place
+            .sort
+            .clone()
+            .filter(|sort| !self.windowed && self.data_frame.column(&sort.column).is_ok());
+        if sort.is_some() {
+            // the filters are empty, the sort alone does not fail on a known column
+            let _ = self.apply(Change::View(sort, Vec::new()), "restoring");
+        }
+        self.select_column = place
+            .select_column
+            .min(self.headers.len().saturating_sub(1));
+        self.column_offset = place.column_offset.min(self.select_column);
+        self.select(place.select);
+        self.offset = place.offset.min(self.select);
+    }
+
+    /// The data frame as displayed, without its hidden columns and in the display order.
+    pub fn visible_data_frame(&self) -> DataFrame {
+        if self.hidden.is_empty() && self.column_order.is_empty() {
+            self.data_frame.clone()
+        } else {
+            self.data_frame
+                .select(
+                    self.ordered(self.data_frame.get_column_names())
+                        .into_iter()
+                        .filter(|col| !self.hidden.contains(*col)),
+                )
+                .expect("visible columns are part of the data frame")
+        }
+    }
+
+    /// The visible columns of the data frame, in the display order unless
+    /// [`Tabular::keep_column_order`] is set, for exports and copies.
+    fn output_data_frame(&self) -> DataFrame {
+        let data_frame = self.visible_data_frame();
+        if !self.keep_column_order || self.column_order.is_empty() {
+            return data_frame;
+        }
+        data_frame
+            .select(
+                self.data_frame
+                    .get_column_names()
+                    .into_iter()
+                    .filter(|col| !self.hidden.contains(*col)),
+            )
+            .expect("visible columns are part of the data frame")
+    }
+
+    /// Rebuilds the rendered values from the data frame without its hidden columns.
+    fn refresh_values(&mut self) {
+        let view = self.visible_data_frame();
+        self.dtypes = view.dtypes().iter().map(ToString::to_string).collect();
+        self.numeric = view.dtypes().iter().map(DataType::is_numeric).collect();
+        self.column_symbols = view
+            .get_columns()
+            .iter()
+            .map(|series| match self.symbols {
+                // the distinct values of a window are not those of the table
+                true => column_symbols(series, !self.windowed),
+                false => ColumnSymbols::Plain,
+            })
+            .collect();
+        self.headers = view
+            .get_column_names()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect();
+        self.select_column = self.select_column.min(self.headers.len().saturating_sub(1));
+        self.search = None;
+        for highlight in &mut self.highlights {
+            highlight.rows =
+                highlighted_rows(&self.data_frame, &highlight.predicate).unwrap_or_default();
+        }
+        // the widths fit the formatted page, and widen as other pages are loaded, so that
+        // they do not cost formatting all the rows
+        match self.lazy.as_ref().filter(|_| self.windowed) {
+            Some(lazy) => {
+                self.widths = data_frame_widths(&view, self.number_format);
+                self.table_values.replace_window(
+                    view,
+                    self.window_start,
+                    lazy.height,
+                    self.number_format,
+                )
+            }
+            None => {
+                let height = view.height();
+                let page = self.page_around(self.viewport_rows(height), height);
+                let view = view.slice(page.start as i64, page.len());
+                self.widths = data_frame_widths(&view, self.number_format);
+                self.table_values
+                    .replace_window(view, page.start, height, self.number_format);
+            }
+        }
+    }
+
+    /// Shows the booleans as symbols and colors the values of categorical columns, or
+    /// not, from now on.
+    pub fn set_symbols(&mut self, symbols: bool) {
+        if self.symbols != symbols {
+            self.symbols = symbols;
+            self.refresh_values();
+        }
+    }
+
+    /// Formats the numbers with `format` from now on.
+    pub fn set_number_format(&mut self, format: NumberFormat) {
+        if self.number_format != format {
+            self.number_format = format;
+            self.refresh_values();
+        }
+    }
+
+    pub fn adjust_offset(&mut self) {
+        if !self.wraps() {
+            self.offset = self.offset.clamp(
+                self.select
+                    .saturating_sub(self.rendered_rows.saturating_sub(1).into()),
+                self.select,
+            );
+            return;
+        }
+        // the rows above the selected one that fit with it, from the offset
+        let widths = self.render_widths();
+        let mut start = self.select;
+        let mut lines = self.row_height(self.select, &widths);
+        while start > self.offset {
+            let height = self.row_height(start - 1, &widths);
+            if lines + height > self.rendered_rows {
+                break;
+            }
+            lines += height;
+            start -= 1;
+        }
+        self.offset = start;
+    }
+
+    /// Wraps the values of the selected column over several lines, or cuts them again.
+    pub fn toggle_wrap(&mut self) {
+        let Some(column) = self.headers.get(self.select_column) else {
+            return;
+        };
+        if self.wrap_all {
+            // the other columns keep wrapping
+            self.wrap_all = false;
+            self.wrapped = self.headers.iter().cloned().collect();
+        }
+        if !self.wrapped.remove(column) {
+            self.wrapped.insert(column.clone());
+        }
+    }
+
+    /// Whether the values of some columns wrap, making rows taller than one line.
+    pub fn wraps(&self) -> bool {
+        self.wrap_all || !self.wrapped.is_empty()
+    }
+
+    pub fn is_wrapped(&self, col: usize) -> bool {
+        self.wrap_all
+            || self
+                .headers
+                .get(col)
+                .is_some_and(|header| self.wrapped.contains(header))
+    }
+
+    /// Lines of `row`, as many as its longest wrapped value takes at the render `widths`
+    /// and at most the rendered rows.
+    pub fn row_height(&self, row: usize, widths: &[usize]) -> u16 {
+        if !self.wraps() {
+            return 1;
+        }
+        let lines = widths
+            .iter()
+            .enumerate()
+            .filter(|(col, _)| self.is_wrapped(*col))
+            .filter_map(|(col, width)| Some(wrap_text(self.table_values.get(row, col)?, *width)))
+            .map(|lines| lines.len())
+            .max()
+            .unwrap_or(1);
+        lines.clamp(1, self.rendered_rows.max(1).into()) as u16
+    }
+
+    /// Lines of the rows drawn from the offset, as many as fit in the rendered rows.
+    pub fn row_heights(&self, widths: &[usize]) -> Vec<u16> {
+        let mut heights = Vec::new();
+        let mut lines = 0;
+        for row in self.offset..self.table_values.height() {
+            let height = self.row_height(row, widths);
+            if lines + height > self.rendered_rows {
+                break;
+            }
+            heights.push(height);
+            lines += height;
+        }
+        heights
+    }
+
+    /// Searches the table, or only the selected column with [`SearchOptions::column`],
+    /// for `query` and selects the first match at or after the selected row. Returns
+    /// false if nothing matches.
+    pub fn search(&mut self, query: &str, options: SearchOptions) -> AppResult<bool> {
+        self.require_loaded("searching")?;
+        let column = options.column.then_some(self.select_column);
+        let mut search = Search::new(query, options, &self.visible_data_frame(), 0, column)?;
+        let found = self.select_match(search.seek(self.select));
+        self.search = Some(search);
+        Ok(found)
+    }
+
+    pub fn search_next(&mut self) -> bool {
+        let found = self.search.as_mut().and_then(Search::next_match);
+        self.select_match(found)
+    }
+
+    pub fn search_prev(&mut self) -> bool {
+        let found = self.search.as_mut().and_then(Search::prev_match);
+        self.select_match(found)
+    }
+
+    /// Selects the cell of the match, whose column is scrolled into view when the table
+    /// is drawn.
+    fn select_match(&mut self, found: Option<(usize, usize)>) -> bool {
+        if let Some((row, col)) = found {
+            self.select(row);
+            self.select_column = col;
+        }
+        found.is_some()
+    }
+
+    /// Scrolls the columns after the frozen ones so the selected column fits in `space`.
+    pub fn adjust_column_offset(&mut self, space: usize) {
+        let widths = self.render_widths();
+        let frozen = self.frozen.min(widths.len());
+        self.column_offset = self
+            .column_offset
+            .clamp(frozen, widths.len().saturating_sub(1).max(frozen));
+        if (frozen..widths.len()).contains(&self.select_column) {
+            self.column_offset = self.column_offset.min(self.select_column);
+            while self.column_offset < self.select_column
+                && widths[self.column_offset..=self.select_column]
+                    .iter()
+                    .map(|width| width + 1)
+                    .sum::<usize>()
+                    > space + 1
+            {
+                self.column_offset += 1;
+            }
+        }
+    }
+
+    /// Widths of the columns, fitting the data types if they are shown and at most
+    /// [`Tabular::max_width`]. With [`Tabular::fit_content`] the columns fit the visible
+    /// rows rather than all of them, and the widths set by hand take precedence over all.
+    pub fn render_widths(&self) -> Vec<usize> {
+        let widths = if self.fit_content {
+            let rows = self.offset..(self.offset + self.rendered_rows as usize);
+            (0..self.widths.len())
+                .map(|col| {
+                    rows.clone()
+                        .filter_map(|row| self.table_values.get(row, col))
+                        .map(UnicodeWidthStr::width)
+                        .chain([self.headers[col].width()])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect()
+        } else {
+            self.widths.clone()
+        };
+        widths
+            .into_iter()
+            .map(|width| match self.max_width {
+                0 => width,
+                max_width => width.min(max_width),
+            })
+            .enumerate()
+            .map(|(col, width)| match self.column_widths.get(&self.headers[col]) {
+                Some(width) => *width,
+                None if self.is_wrapped(col) => width.min(WRAP_WIDTH),
+                None if self.show_types => width.max(self.dtypes[col].chars().count()),
+                None => width,
+            })
+            .collect()
+    }
+
+    /// Grows or shrinks the selected column by `delta` characters, down to one.
+    pub fn resize_selected(&mut self, delta: isize) {
+        let Some(width) = self.render_widths().get(self.select_column).copied() else {
+            return;
+        };
+        let header = self.headers[self.select_column].clone();
+        self.column_widths
+            .insert(header, width.saturating_add_signed(delta).max(1));
+    }
+
+    /// Switches between fitting the columns to all the rows and to the visible rows,
+    /// discarding the widths set by hand.
+    pub fn toggle_fit_content(&mut self) {
+        self.fit_content = !self.fit_content;
+        self.column_widths.clear();
+    }
+
+    /// Freezes the columns up to the selected one, or unfreezes them if they already are.
+    pub fn toggle_freeze(&mut self) {
+        if self.frozen == self.select_column + 1 {
+            self.frozen = 0;
+        } else {
+            self.frozen = self.select_column + 1;
+        }
+    }
+
+    pub fn switch_view(&mut self) {
+        if self.detailed_view.is_none() && !self.is_empty() {
+            self.detailed_view = Scroll::default().into();
+            self.record_view = None;
+        } else {
+            self.detailed_view = None;
+        }
+    }
+
+    /// Shows the selected row as a list of `column value` lines, or goes back to the table.
+    pub fn toggle_record_view(&mut self) {
+        if self.record_view.is_none() && !self.is_empty() {
+            self.record_view = Some(0);
+            self.detailed_view = None;
+        } else {
+            self.record_view = None;
+        }
+    }
+
+    /// Opens or closes the popup showing the full value of the selected cell.
+    /// Selects the cell drawn at the terminal coordinates, or only its column when the
+    /// header is clicked. Clicking the same cell twice in a row opens its popup.
+    pub fn click(&mut self, x: u16, y: u16) {
+        let Some(col) = self.cell_areas.column_at(x) else {
+            return;
+        };
+        if self.cell_areas.header.contains(&y) {
+            self.select_column = col;
+            self.last_click = None;
+        } else if let Some(row) = self.cell_areas.row_at(y) {
+            let row = self.offset + row;
+            if row >= self.table_values.height() {
+                return;
+            }
+            self.select(row);
+            self.select_column = col;
+            let now = Instant::now();
+            let double = self.last_click.is_some_and(|(time, last_row, last_col)| {
+                now.duration_since(time) <= DOUBLE_CLICK && (last_row, last_col) == (row, col)
+            });
+            if double {
+                self.last_click = None;
+                self.toggle_cell_popup();
+            } else {
+                self.last_click = Some((now, row, col));
+            }
+        }
+    }
+
+    pub fn toggle_cell_popup(&mut self) {
+        if self.cell_popup.is_none() && !self.headers.is_empty() && !self.is_empty() {
+            self.cell_popup = Scroll::default().into();
+        } else {
+            self.cell_popup = None;
+        }
+    }
+
+    pub fn toggle_view_popup(&mut self) {
+        self.view_popup = match self.view_popup {
+            None => Some(Scroll::default()),
+            Some(_) => None,
+        };
+    }
+
+    /// Opens the statistics panel of the selected column, or closes it.
+    pub fn toggle_stats(&mut self) -> AppResult<()> {
+        self.require_loaded("statistics")?;
+        self.stats = match (&self.stats, self.headers.get(self.select_column)) {
+            (None, Some(column)) => Some(ColumnStats::new(&self.data_frame, column)?),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Shows how many rows hold each value of the selected column, in the sorted and
+    /// filtered rows, or hides the counts.
+    pub fn toggle_value_counts(&mut self) -> AppResult<()> {
+        self.require_loaded("value counts")?;
+        self.value_counts = match (&self.value_counts, self.headers.get(self.select_column)) {
+            (None, Some(column)) => Some(ValueCounts::new(
+                &self.data_frame,
+                column,
+                VALUE_COUNTS_TOP,
+            )?),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Shows the overview of the visible columns with the selected one selected, or
+    /// hides it. The nulls are only counted when all the rows are loaded.
+    pub fn toggle_schema(&mut self) -> AppResult<()> {
+        self.schema = match self.schema {
+            None => {
+                let mut schema =
+                    SchemaOverview::new(&self.data_frame, &self.headers, !self.windowed)?;
+                schema.selected = self.select_column;
+                Some(schema)
+            }
+            Some(_) => None,
+        };
+        Ok(())
+    }
+
+    /// Shows the histogram of the selected column, in the filtered rows, or hides it.
+    pub fn toggle_histogram(&mut self) -> AppResult<()> {
+        self.require_loaded("histograms")?;
+        self.histogram = match (&self.histogram, self.headers.get(self.select_column)) {
+            (None, Some(column)) => {
+                Some(Histogram::new(&self.data_frame, column, HISTOGRAM_BUCKETS)?)
+            }
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Value of the selected cell in the data frame, rather than its text.
+    pub fn selected_value(&self) -> Option<AnyValue<'_>> {
+        let column = self.headers.get(self.select_column)?;
+        let row = self.select.checked_sub(self.window_start)?;
+        self.data_frame.column(column).ok()?.get(row).ok()
+    }
+
+    /// Fails unless the data frame is the source, the only one that is edited.
+    fn require_source(&self, what: &str) -> AppResult<()> {
+        self.require_loaded(what)?;
+        if self.queried {
+            return Err(format!("{} needs the source table, :reset goes back to it", what).into());
+        }
+        Ok(())
+    }
+
+    /// Row of the source shown at `row` of the data frame.
+    fn source_row(&self, row: usize) -> usize {
+        match &self.view_rows {
+            Some(rows) => rows.get(row).expect("a row of the view") as usize,
+            None => row,
+        }
+    }
+
+    /// Position of `row` of the source in the data frame, `None` when it is filtered
+    /// out.
+    fn view_position(&self, row: usize) -> Option<usize> {
+        match &self.view_rows {
+            Some(rows) => rows.into_no_null_iter().position(|idx| idx as usize == row),
+            None => Some(row),
+        }
+    }
+
+    /// Sets the selected cell to `text` converted to the type of its column, an empty
+    /// text being null. The edit is made to the source, through the sort and filters.
+    pub fn edit_selected(&mut self, text: &str) -> AppResult<()> {
+        self.require_source("editing")?;
+        let Some(column) = self.headers.get(self.select_column).cloned() else {
+            return Ok(());
+        };
+        if self.select >= self.data_frame.height() {
+            return Ok(());
+        }
+        let value = parse_value(text, self.source.column(&column)?.dtype())?;
+        let previous = self.source.column(&column)?.clone();
+        let row = self.source_row(self.select);
+        set_cell(&mut self.source, &column, row, &value)?;
+        self.recorded(Change::SetColumn(previous));
+        self.modified = true;
+        match &mut self.original {
+            // the edited row keeps its place in the view
+            Some(original) => {
+                *original = self.source.clone();
+                set_cell(&mut self.data_frame, &column, self.select, &value)?;
+            }
+            None => self.data_frame = self.source.clone(),
+        }
+        self.refresh_values();
+        Ok(())
+    }
+
+    /// Casts `column` of the source to `dtype`, returning the number of values that do
+    /// not convert and become null. With `strict`, such values are an error and the
+    /// column is left as it is.
+    pub fn cast_column(
+        &mut self,
+        column: &str,
+        dtype: &DataType,
+        strict: bool,
+    ) -> AppResult<usize> {
+        self.require_source("casting columns")?;
+        let series = self.source.column(column)?;
+        let cast = cast_series(series, dtype)?;
+        let failed = cast.is_null() & series.is_not_null();
+        let count = failed.sum().unwrap_or_default() as usize;
+        if strict && count > 0 {
+            let first = failed.into_iter().position(|failed| failed == Some(true));
+            let value = first
+                .and_then(|row| series.get(row).ok())
+                .map(any_value_into_string)
+                .unwrap_or_default();
+            return Err(match count {
+                1 => format!("'{}' in {} is not a valid {}", value, column, dtype),
+                count => format!(
+                    "{} values in {} are not valid {}, as '{}'",
+                    count, column, dtype, value
+                ),
+            }
+            .into());
+        }
+        self.record(Change::SetColumn(cast))?;
+        Ok(count)
+    }
+
+    /// Deletes the rows of the selected range, or the selected row, from the source.
+    pub fn delete_selected_rows(&mut self) -> AppResult<()> {
+        self.require_source("deleting rows")?;
+        let range = self
+            .selected_range()
+            .unwrap_or(self.select..self.select + 1);
+        if range.end > self.data_frame.height() {
+            return Ok(());
+        }
+        let start = range.start;
+        let mut rows = range.map(|row| self.source_row(row)).collect::<Vec<_>>();
+        rows.sort_unstable();
+        self.record(Change::RemoveRows(rows))?;
+        // the row after the deleted ones
+        self.select(start);
+        Ok(())
+    }
+
+    /// Appends a row of nulls to the source and selects it.
+    pub fn append_row(&mut self) -> AppResult<()> {
+        self.require_source("adding rows")?;
+        let row = DataFrame::new(
+            self.source
+                .get_columns()
+                .iter()
+                .map(|column| Series::full_null(column.name(), 1, column.dtype()))
+                .collect(),
+        )?;
+        self.record(Change::InsertRows(vec![self.source.height()], row))
+    }
+
+    /// Reverts the last edit, deletion, appended row, sort or filter.
+    pub fn undo(&mut self) -> AppResult<()> {
+        let change = self.undo.last().ok_or("nothing to undo")?.clone();
+        let inverse = self.apply(change, "undoing")?;
+        self.undo.pop();
+        push_change(&mut self.redo, inverse);
+        Ok(())
+    }
+
+    /// Makes the last undone change again.
+    pub fn redo(&mut self) -> AppResult<()> {
+        let change = self.redo.last().ok_or("nothing to redo")?.clone();
+        let inverse = self.apply(change, "redoing")?;
+        self.redo.pop();
+        push_change(&mut self.undo, inverse);
+        Ok(())
+    }
+
+    /// Makes `change`, which can then be undone.
+    fn record(&mut self, change: Change) -> AppResult<()> {
+        let inverse = self.apply(change, "changing the table")?;
+        self.recorded(inverse);
+        Ok(())
+    }
+
+    /// Remembers `inverse`, reverting a change just made, and forgets the undone
+    /// changes, which cannot be made again after it.
+    fn recorded(&mut self, inverse: Change) {
+        push_change(&mut self.undo, inverse);
+        self.redo.clear();
+    }
+
+    /// Makes `change` and returns the change reverting it. The changes of the source
+    /// are shown through the sort and filters.
+    fn apply(&mut self, change: Change, what: &str) -> AppResult<Change> {
+        let inverse = match change {
+            Change::View(sort, filters) => {
+                let sort = std::mem::replace(&mut self.sort, sort);
+                let filters = std::mem::replace(&mut self.filters, filters);
+                if let Err(error) = self.apply_view() {
+                    self.sort = sort;
+                    self.filters = filters;
+                    return Err(error);
+                }
+                self.range_start = None;
+                self.select(self.select);
+                return Ok(Change::View(sort, filters));
+            }
+            Change::SetColumn(column) => {
+                self.require_source(what)?;
+                let previous = self.source.column(column.name())?.clone();
+                self.source.replace(previous.name(), column)?;
+                Change::SetColumn(previous)
+            }
+            Change::InsertRows(rows, values) => {
+                self.require_source(what)?;
+                let mut source = self.source.clear();
+                // rows of the source before the next inserted row
+                let mut taken = 0;
+                for (idx, row) in rows.iter().enumerate() {
+                    let before = row - idx;
+                    source.vstack_mut(&self.source.slice(taken as i64, before - taken))?;
+                    source.vstack_mut(&values.slice(idx as i64, 1))?;
+                    taken = before;
+                }
+                source.vstack_mut(&self.source.slice(taken as i64, usize::MAX))?;
+                source.as_single_chunk();
+                self.source = source;
+                self.bookmarks = inserted_bookmarks(&self.bookmarks, &rows);
+                Change::RemoveRows(rows)
+            }
+            Change::RemoveRows(rows) => {
+                self.require_source(what)?;
+                let mut keep = vec![true; self.source.height()];
+                for row in &rows {
+                    keep[*row] = false;
+                }
+                let values = self.source.take(&IdxCa::from_vec(
+                    "",
+                    rows.iter().map(|row| *row as IdxSize).collect(),
+                ))?;
+                self.source = self
+                    .source
+                    .filter(&keep.into_iter().collect::<BooleanChunked>())?;
+                self.bookmarks = removed_bookmarks(&self.bookmarks, &rows);
+                Change::InsertRows(rows, values)
+            }
+        };
+        self.modified = true;
+        self.range_start = None;
+        self.show_source()?;
+        if let Change::RemoveRows(rows) = &inverse {
+            // the first inserted row
+            if let Some(position) = rows.first().and_then(|row| self.view_position(*row)) {
+                self.select(position);
+            }
+        }
+        Ok(inverse)
+    }
+
+    /// Shows the source again, with the sort and filters.
+    fn show_source(&mut self) -> AppResult<()> {
+        match &mut self.original {
+            Some(original) => {
+                *original = self.source.clone();
+                self.apply_view()?;
+            }
+            None => {
+                self.data_frame = self.source.clone();
+                self.refresh_values();
+            }
+        }
+        self.select(self.select);
+        Ok(())
+    }
+
+    /// Appends `rows`, read from the followed file, to the source. The table stays at the
+    /// bottom if its last row is selected, and the result of a query is left as it is.
+    pub fn append_rows(&mut self, rows: &DataFrame) -> AppResult<()> {
+        let at_bottom = self.select + 1 >= self.data_frame.height();
+        let rows = self.project(rows.clone())?;
+        self.source.vstack_mut(&rows)?;
+        self.source.as_single_chunk();
+        if !self.queried {
+            self.show_source()?;
+            if at_bottom {
+                self.select_last();
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the source by `source`, the rows of the followed file after it was
+    /// truncated or replaced. The changes of the rows read before cannot be undone.
+    pub fn reload_source(&mut self, source: DataFrame) -> AppResult<()> {
+        self.source = self.project(source)?;
+        if !self.queried {
+            self.drop_bookmarks();
+        }
+        for stack in [&mut self.undo, &mut self.redo] {
+            stack.retain(|change| matches!(change, Change::View(..)));
+        }
+        self.range_start = None;
+        if !self.queried {
+            self.show_source()?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the source by `table`, read again with `:reload`, discarding the edits.
+    /// The selection stays where it still is in the table.
+    pub fn reload(&mut self, table: Table) -> AppResult<()> {
+        if self.lazy.is_some() {
+            return Err("tables scanned with --lazy are read again by each query".into());
+        }
+        self.reload_source(table.data_frame)?;
+        self.file = table.file;
+        self.truncation = table.truncation;
+        self.modified = false;
+        Ok(())
+    }
+
+    /// The columns of the projection in `data_frame`, read from the source again. Those
+    /// it no longer has are dropped from the projection, all the columns being kept when
+    /// none is left.
+    fn project(&mut self, data_frame: DataFrame) -> AppResult<DataFrame> {
+        if self.projection.is_empty() {
+            return Ok(data_frame);
+        }
+        let names = data_frame.get_column_names();
+        let (kept, dropped) = std::mem::take(&mut self.projection)
+            .into_iter()
+            .partition::<Vec<_>, _>(|column| names.contains(&column.as_str()));
+        self.projection = kept;
+        self.dropped_columns.extend(dropped);
+        match self.projection.is_empty() {
+            true => Ok(data_frame),
+            false => Ok(data_frame.select(&self.projection)?),
+        }
+    }
+
+    /// Whether the data frame holds the result of a query or a command rather than the
+    /// source.
+    pub fn queried(&self) -> bool {
+        self.queried
+    }
+
+    /// Source with its edits, which is what `:write` writes.
+    pub fn source(&self) -> &DataFrame {
+        &self.source
+    }
+
+    /// Registers the source in the SQL context under the names of the table, again after
+    /// it changes so that queries see the changes.
+    pub fn register(&self, sql: &mut SQLContext) {
+        sql.register(&self.table_name, self.source_frame());
+        if let Some(stem_name) = &self.stem_name {
+            sql.register(stem_name, self.source_frame());
+        }
+    }
+
+    /// Frame of the source, to register it again in the SQL context. Its columns are in
+    /// the display order unless [`Tabular::keep_column_order`] is set.
+    pub fn source_frame(&self) -> LazyFrame {
+        let mut frame = match &self.lazy {
+            Some(lazy) => lazy.frame.clone(),
+            None => self.source.clone().lazy(),
+        };
+        if self.keep_column_order || self.column_order.is_empty() {
+            return frame;
+        }
+        let schema = match &self.lazy {
+            Some(_) => match frame.schema() {
+                Ok(schema) => schema,
+                Err(_) => return frame,
+            },
+            None => self.source.schema().into(),
+        };
+        let columns = self.ordered(schema.iter_names().map(|name| name.as_str()).collect());
+        frame.select(columns.into_iter().map(col).collect::<Vec<_>>())
+    }
+
+    pub fn source_height(&self) -> usize {
+        match &self.lazy {
+            Some(lazy) => lazy.height,
+            None => self.source.height(),
+        }
+    }
+
+    /// Discards the queries, sort, filters and hidden columns, and scrolls back to the
+    /// first cell of the data frame the table was created with. A query running in the
+    /// background is cancelled.
+    pub fn reset(&mut self) {
+        self.cancel_query();
+        self.hidden.clear();
+        self.column_order.clear();
+        self.last_query = None;
+        self.detailed_view = None;
+        self.select_column = 0;
+        self.column_offset = 0;
+        // the bookmarks of the source are still of its rows
+        let bookmarks = (!self.queried).then(|| std::mem::take(&mut self.bookmarks));
+        self.set_data_frame(self.source.clone());
+        self.bookmarks = bookmarks.unwrap_or_default();
+        self.queried = false;
+        if self.lazy.is_some() {
+            self.windowed = true;
+            self.refresh_values();
+        }
+    }
+
+    pub fn set_data_frame(&mut self, data_frame: DataFrame) {
+        self.windowed = false;
+        self.window_start = 0;
+        self.cell_popup = None;
+        self.view_query = None;
+        self.query_timing = None;
+        self.stats = None;
+        self.value_counts = None;
+        self.histogram = None;
+        self.schema = None;
+        self.offset = 0;
+        self.select = 0;
+        self.range_start = None;
+        self.sort = None;
+        self.filters.clear();
+        self.original = None;
+        self.view_rows = None;
+        self.queried = true;
+        self.drop_bookmarks();
+        // the sort and filters were of the data frame replaced
+        for stack in [&mut self.undo, &mut self.redo] {
+            stack.retain(|change| !matches!(change, Change::View(..)));
+        }
+        self.hidden
+            .retain(|col| data_frame.get_column_index(col).is_some());
+        if self.hidden.len() == data_frame.width() {
+            self.hidden.clear();
+        }
+        self.data_frame = data_frame;
+        self.refresh_values();
+    }
+}
+
+/// Tabular views of the open tables, one of which is selected at a time.
+#[derive(Debug)]
+pub struct Tabs {
+    tabulars: Vec<Tabular>,
+    idx: usize,
+}
+
+impl Tabs {
+    pub fn new(tabulars: Vec<Tabular>) -> Self {
+        assert!(!tabulars.is_empty(), "at least one tab is required");
+        Self { tabulars, idx: 0 }
+    }
+
+    pub fn selected(&self) -> &Tabular {
+        &self.tabulars[self.idx]
     }
 
-    /// Handles the tick event of the terminal.
-    pub fn tick(&mut self) {}
+    pub fn iter(&self) -> impl Iterator<Item = &Tabular> {
+        self.tabulars.iter()
+    }
 
-    pub fn select_up(&mut self, len: usize) {
-        self.select(self.select.saturating_sub(len))
+    pub fn selected_mut(&mut self) -> &mut Tabular {
+        &mut self.tabulars[self.idx]
     }
 
-    pub fn select_down(&mut self, len: usize) {
-        self.select(self.select + len);
+    /// Adds a tab after the others and selects it.
+    pub fn push(&mut self, tabular: Tabular) {
+        self.tabulars.push(tabular);
+        self.idx = self.tabulars.len() - 1;
     }
 
-    pub fn select_first(&mut self) {
-        self.select(usize::MIN)
+    /// Index of the selected tab.
+    pub fn index(&self) -> usize {
+        self.idx
     }
 
-    pub fn select_last(&mut self) {
-        self.select(usize::MAX);
+    pub fn len(&self) -> usize {
+        self.tabulars.len()
     }
 
-    pub fn select_random(&mut self) {
-        let mut rng = rand::thread_rng();
-        self.select(rng.gen_range(0..self.table_values.height()))
+    pub fn is_empty(&self) -> bool {
+        self.tabulars.is_empty()
     }
 
-    pub fn select(&mut self, select: usize) {
-        self.select = select.min(self.table_values.height().saturating_sub(1))
+    pub fn select_next(&mut self) {
+        self.idx = (self.idx + 1) % self.tabulars.len();
     }
 
-    pub fn adjust_offset(&mut self) {
-        self.offset = self.offset.clamp(
-            self.select
-                .saturating_sub(self.rendered_rows.saturating_sub(1).into()),
-            self.select,
-        );
+    pub fn select_prev(&mut self) {
+        self.idx = (self.idx + self.tabulars.len() - 1) % self.tabulars.len();
     }
 
-    pub fn switch_view(&mut self) {
-        if self.detailed_view.is_none() {
-            self.detailed_view = Scroll::default().into();
-        } else {
-            self.detailed_view = None;
-        }
+    /// Handles the tick event of the terminal in every tab, so that the queries running
+    /// in the background finish and the followed files are read in the hidden tabs too.
+    pub fn tick(&mut self, sql: &mut SQLContext) -> AppResult<()> {
+        self.tabulars
+            .iter_mut()
+            .try_for_each(|tabular| tabular.tick(sql))
     }
 
-    pub fn set_data_frame(&mut self, data_frame: DataFrame) {
-        self.widths = data_frame_widths(&data_frame);
-        self.offset = 0;
-        self.select = 0;
-        self.headers = data_frame.get_column_names().into_iter().map(ToOwned::to_owned).collect();
-        self.table_values.replace_dataframe(data_frame);
+    pub fn needs_tick(&self) -> bool {
+        self.tabulars.iter().any(Tabular::needs_tick)
     }
 }
 
 #[derive(Debug, Default)]
 pub struct StatusBar {
     pub state: StatusBarState,
-    prompt_history: Vec<String>,
+    /// Selected entry of the history popup, newest first, when it is open.
+    pub history_popup: Option<usize>,
+    /// Candidates of the last ambiguous completion in the prompt.
+    pub completions: Vec<String>,
+    /// Help overlay listing the keys, when it is open.
+    pub help: Option<Help>,
+    /// Command palette, when it is open.
+    pub palette: Option<Palette>,
+    /// Action waiting for its key to be pressed again, as `dd` deletes a row.
+    pub pending_action: Option<Action>,
+    /// How the searches match, kept from one search to the next.
+    pub search_options: SearchOptions,
+    /// Theme the interface is drawn with, switched with `:theme`.
+    pub theme: AppTheme,
+    history: History,
 }
 
 #[derive(Debug, Default)]
@@ -102,10 +1955,79 @@ pub enum StatusBarState {
     #[default]
     Normal,
     Error(String),
+    Info(String),
     Command(CommandPalleteState),
 }
 
+/// What the keys do: move around the table, or edit a command or a search in the
+/// prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Command,
+    Search,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Command => "COMMAND",
+            Mode::Search => "SEARCH",
+        }
+    }
+}
+
 impl StatusBar {
+    pub fn new(history: History) -> Self {
+        Self {
+            history,
+            ..Default::default()
+        }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn show_history(&mut self) {
+        self.history_popup = Some(0);
+    }
+
+    pub fn history_up(&mut self) {
+        if let Some(idx) = &mut self.history_popup {
+            *idx = idx.saturating_sub(1);
+        }
+    }
+
+    pub fn history_down(&mut self) {
+        let len = self.history.entries().len();
+        if let Some(idx) = &mut self.history_popup {
+            *idx = (*idx + 1).min(len.saturating_sub(1));
+        }
+    }
+
+    /// Closes the history popup and opens the prompt with the selected entry.
+    pub fn select_history(&mut self) {
+        if let Some(entry) = self
+            .history_popup
+            .take()
+            .and_then(|idx| self.history.entries().iter().rev().nth(idx))
+        {
+            self.command(entry.clone());
+        }
+    }
+
+    /// Mode of the keys, which follows the prompt: a prompt starting with `/` is a
+    /// search, and the messages are shown in the normal mode.
+    pub fn mode(&self) -> Mode {
+        match &self.state {
+            StatusBarState::Command(prompt) if prompt.command().starts_with('/') => Mode::Search,
+            StatusBarState::Command(_) => Mode::Command,
+            _ => Mode::Normal,
+        }
+    }
+
     pub fn normal(&mut self) {
         self.state = StatusBarState::Normal;
     }
@@ -114,25 +2036,65 @@ impl StatusBar {
         self.state = StatusBarState::Error(msg.to_string());
     }
 
+    pub fn info(&mut self, msg: impl ToString) {
+        self.state = StatusBarState::Info(msg.to_string());
+    }
+
     pub fn command(&mut self, prefix: impl ToString) {
-        let mut history = self.prompt_history.clone();
+        let mut history = self.history.entries().to_vec();
         history.push(prefix.to_string());
         self.state = StatusBarState::Command(history.into());
     }
 
+    /// Completes the token before the cursor in the prompt. A single candidate is
+    /// inserted; several extend the token to their common prefix and are kept in
+    /// [`StatusBar::completions`] to be shown.
+    pub fn complete<'a>(
+        &mut self,
+        commands: impl IntoIterator<Item = &'a str>,
+        tables: impl IntoIterator<Item = &'a str>,
+        columns: impl IntoIterator<Item = &'a str>,
+    ) {
+        if let StatusBarState::Command(prompt) = &mut self.state {
+            let completion = complete(&prompt.before_cursor(), commands, tables, columns);
+            match completion.candidates.as_slice() {
+                [] => self.completions.clear(),
+                [candidate] => {
+                    prompt.replace_before_cursor(completion.start, candidate);
+                    self.completions.clear();
+                }
+                _ => {
+                    let prefix = completion.common_prefix();
+                    if prefix.chars().count() > prompt.cursor().1 - completion.start {
+                        prompt.replace_before_cursor(completion.start, &prefix);
+                    }
+                    self.completions = completion.candidates;
+                }
+            }
+        }
+    }
+
     pub fn commit_prompt(&mut self) -> Option<String> {
         if let StatusBarState::Command(prompt) = &self.state {
             let command = prompt.command();
-            self.prompt_history.push(command.clone());
+            self.history.push(command.clone());
+            self.completions.clear();
             Some(command)
         } else {
             None
         }
     }
 
+    /// Handles the tick event of the terminal, see [`Tabular::tick`].
     pub fn tick(&mut self) {}
 
+    /// Whether there is tick driven work pending, see [`Tabular::needs_tick`].
+    pub fn needs_tick(&self) -> bool {
+        false
+    }
+
     pub fn input(&mut self, input: KeyEvent) {
+        self.completions.clear();
         if let StatusBarState::Command(prompt) = &mut self.state {
             match input.code {
                 KeyCode::Up => {
@@ -141,10 +2103,8 @@ impl StatusBar {
                 KeyCode::Down => {
                     prompt.move_down().move_eol();
                 }
-                KeyCode::Left => {
-                    if prompt.cursor().1 > 1 {
-                        prompt.move_left();
-                    }
+                KeyCode::Left if prompt.cursor().1 > 1 => {
+                    prompt.move_left();
                 }
                 KeyCode::Right => {
                     prompt.move_right();
@@ -181,3 +2141,1094 @@ impl StatusBar {
         }
     }
 }
+
+/// State of the application and the dispatch of the events to it, the terminal aside:
+/// the tabs, the status bar, the SQL context, the commands, the aliases and the key
+/// bindings. The main loop draws it and feeds it the events of the terminal, tests feed
+/// it events of their own with [`App::step`].
+pub struct App {
+    pub tabs: Tabs,
+    pub status_bar: StatusBar,
+    pub sql_context: SQLContext,
+    /// Whether the application keeps running, until it is quit.
+    pub running: bool,
+    pub exec_tbl: ExecutionTable,
+    pub aliases: Aliases,
+    pub keymap: Keymap,
+}
+
+impl App {
+    /// Application showing the tabs, with the built-in commands, no aliases and the
+    /// default key bindings.
+    pub fn new(tabs: Tabs, sql_context: SQLContext) -> Self {
+        Self {
+            tabs,
+            status_bar: StatusBar::default(),
+            sql_context,
+            running: true,
+            exec_tbl: CommandList::default().into_exec(),
+            aliases: Aliases::default(),
+            keymap: Keymap::default(),
+        }
+    }
+
+    /// Handles an event: a tick moves the queries and the followed files along, and the
+    /// keys and the mouse act on the selected tab.
+    pub fn step(&mut self, event: Event) -> AppResult<()> {
+        match event {
+            Event::Tick => {
+                if let Err(error) = self.tabs.tick(&mut self.sql_context) {
+                    self.status_bar.error(error);
+                }
+                self.status_bar.tick();
+            }
+            // releases are reported on Windows only, and do nothing
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Release => (),
+            Event::Key(key_event) => handle_key_events(
+                key_event,
+                &mut self.tabs,
+                &mut self.status_bar,
+                &mut self.sql_context,
+                &mut self.running,
+                &self.exec_tbl,
+                &self.aliases,
+                &self.keymap,
+            )?,
+            Event::Mouse(mouse_event) => {
+                handle_mouse_events(mouse_event, &mut self.tabs, &mut self.status_bar)
+            }
+            Event::Resize(_, _) => (),
+        }
+        if self.tabs.selected_mut().take_dropped_bookmarks() {
+            self.status_bar
+                .info("bookmarks cleared, the rows were replaced");
+        }
+        let dropped = self.tabs.selected_mut().take_dropped_columns();
+        if !dropped.is_empty() {
+            let dropped = dropped.iter().map(|column| format!("'{}'", column));
+            self.status_bar.error(format!(
+                "column(s) {} no longer in the file, no longer shown",
+                dropped.collect::<Vec<_>>().join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Runs a command, or a query prefixed with `:Q`, as if it was entered in the prompt.
+    pub fn execute(&mut self, command: &str) -> AppResult<()> {
+        execute_command(
+            command,
+            &mut self.tabs,
+            &mut self.status_bar,
+            &mut self.sql_context,
+            &mut self.running,
+            &self.exec_tbl,
+            &self.aliases,
+        )
+    }
+
+    /// Whether ticks are needed, for an animation or work in the background.
+    pub fn needs_tick(&self) -> bool {
+        self.tabs.needs_tick() || self.status_bar.needs_tick()
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        ui::render(&mut self.tabs, &mut self.status_bar, frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn prices(tabular: &Tabular) -> Vec<Option<i32>> {
+        prices_of(&tabular.data_frame)
+    }
+
+    fn prices_of(data_frame: &DataFrame) -> Vec<Option<i32>> {
+        data_frame
+            .column("price")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_click() {
+        let df = df! {
+            "name" => ["a", "b", "c"],
+            "price" => [1, 2, 3],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.offset = 1;
+        tabular.cell_areas = CellAreas {
+            columns: vec![(0, 0..4), (1, 5..10)],
+            header: 0..1,
+            rows: 1..5,
+            row_heights: Vec::new(),
+        };
+
+        tabular.click(6, 1);
+        assert_eq!((tabular.select, tabular.select_column), (1, 1));
+        assert!(tabular.cell_popup.is_none());
+        tabular.click(6, 1);
+        assert!(tabular.cell_popup.is_some());
+        tabular.cell_popup = None;
+
+        // the header selects the column only, below the last row nothing happens
+        tabular.click(2, 0);
+        assert_eq!((tabular.select, tabular.select_column), (1, 0));
+        tabular.click(2, 3);
+        tabular.click(4, 2);
+        assert_eq!((tabular.select, tabular.select_column), (1, 0));
+        assert!(tabular.cell_popup.is_none());
+    }
+
+    #[test]
+    fn test_sort_selected() {
+        let df = df! {
+            "name" => ["a", "b", "c", "d"],
+            "price" => [Some(3), None, Some(1), Some(2)],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_right();
+
+        tabular.sort_selected(false).unwrap();
+        assert_eq!(prices(&tabular), [Some(1), Some(2), Some(3), None]);
+        assert_eq!(tabular.table_values.get(0, 0), Some("c"));
+
+        // sorting again in the same direction flips it, nulls stay last
+        tabular.sort_selected(false).unwrap();
+        assert_eq!(prices(&tabular), [Some(3), Some(2), Some(1), None]);
+        assert_eq!(
+            tabular.sort,
+            Some(Sort {
+                column: "price".to_owned(),
+                descending: true
+            })
+        );
+
+        tabular.reset_sort();
+        assert_eq!(prices(&tabular), [Some(3), None, Some(1), Some(2)]);
+        assert_eq!(tabular.sort, None);
+    }
+
+    #[test]
+    fn test_filter_selected() {
+        let df = df! {
+            "name" => ["a", "b", "c", "d"],
+            "price" => [Some(3), None, Some(1), Some(2)],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_right();
+
+        tabular.filter_selected("is not null").unwrap();
+        tabular.sort_selected(false).unwrap();
+        assert_eq!(prices(&tabular), [Some(1), Some(2), Some(3)]);
+        tabular.filter_selected("> 1").unwrap();
+        assert_eq!(prices(&tabular), [Some(2), Some(3)]);
+        assert!(tabular.filter_selected("> cheap").is_err());
+        assert_eq!(tabular.filters.len(), 2);
+
+        // the sort outlives the filters, and the original outlives both
+        tabular.clear_filters();
+        assert_eq!(prices(&tabular), [Some(1), Some(2), Some(3), None]);
+        tabular.reset_sort();
+        assert_eq!(prices(&tabular), [Some(3), None, Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_column_widths() {
+        let df = df! {
+            "id" => [1, 2, 3],
+            "description" => ["short", "a much longer text", "mid length"],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        assert_eq!(tabular.render_widths(), [2, 18]);
+
+        tabular.resize_selected(3);
+        tabular.select_right();
+        tabular.resize_selected(-10);
+        assert_eq!(tabular.render_widths(), [5, 8]);
+        tabular.resize_selected(-20);
+        assert_eq!(tabular.render_widths(), [5, 1]);
+        // resized past the widest the columns get
+        tabular.max_width = 8;
+        tabular.resize_selected(30);
+        assert_eq!(tabular.render_widths(), [5, 31]);
+
+        // fitting the visible rows only, the second row is out of view
+        tabular.toggle_fit_content();
+        tabular.offset = 2;
+        tabular.rendered_rows = 1;
+        assert_eq!(tabular.render_widths(), [2, 8]);
+        tabular.max_width = 0;
+        assert_eq!(tabular.render_widths(), [2, 11]);
+    }
+
+    #[test]
+    fn test_background_query() {
+        let df = df! {
+            "id" => [1, 2, 3],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df.clone());
+        let query = |id: i32| df.clone().lazy().filter(col("id").gt(lit(id)));
+        tabular.start_query("q1".to_owned(), query(1)).unwrap();
+        assert!(tabular.needs_tick());
+        tabular.wait_query().unwrap();
+        assert!(!tabular.needs_tick());
+        assert_eq!(tabular.data_frame.height(), 2);
+        assert_eq!(tabular.last_query.as_deref(), Some("q1"));
+
+        tabular.start_query("q2".to_owned(), query(2)).unwrap();
+        assert!(tabular.cancel_query());
+        assert!(!tabular.cancel_query());
+        assert_eq!(tabular.data_frame.height(), 2);
+        assert_eq!(tabular.last_query.as_deref(), Some("q1"));
+    }
+
+    #[test]
+    fn test_provenance() {
+        let df = df! { "id" => [1, 2, 3] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df.clone());
+        assert_eq!(tabular.provenance(), "(full)");
+        let query = "SELECT * FROM df WHERE id > 1".to_owned();
+        let frame = df.clone().lazy().filter(col("id").gt(lit(1)));
+        tabular.start_query(query.clone(), frame).unwrap();
+        tabular.wait_query().unwrap();
+        assert_eq!(tabular.provenance(), query);
+        tabular.set_data_frame(df);
+        assert_eq!(tabular.provenance(), "(command output)");
+        tabular.reset();
+        assert_eq!(tabular.provenance(), "(full)");
+    }
+
+    #[test]
+    fn test_edit_selected() {
+        let df = df! {
+            "name" => ["a", "b", "c"],
+            "price" => [3, 1, 2],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_column = 1;
+        tabular.sort_selected(false).unwrap();
+        tabular.select = 1;
+        assert!(tabular.edit_selected("x").is_err());
+        assert!(!tabular.modified);
+
+        // the second row of the sorted view is the third of the source
+        tabular.edit_selected("20").unwrap();
+        assert!(tabular.modified);
+        assert_eq!(prices(&tabular), [Some(1), Some(20), Some(3)]);
+        tabular.reset_sort();
+        assert_eq!(prices(&tabular), [Some(3), Some(1), Some(20)]);
+        tabular.edit_selected("").unwrap();
+        assert_eq!(prices(&tabular), [Some(3), None, Some(20)]);
+
+        tabular.set_data_frame(tabular.data_frame.head(Some(1)));
+        assert!(tabular.edit_selected("5").is_err());
+        tabular.reset();
+        assert_eq!(prices(&tabular), [Some(3), None, Some(20)]);
+    }
+
+    #[test]
+    fn test_delete_append_undo() {
+        let df = df! {
+            "name" => [Some("a"), None, Some("c")],
+            "price" => [Some(3), Some(1), None],
+            "sold" => [Some(true), None, Some(false)],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_column = 1;
+        assert!(tabular.undo().is_err());
+        tabular.sort_selected(false).unwrap();
+
+        // the first row of the sorted view is the second of the source
+        tabular.delete_selected_rows().unwrap();
+        assert!(tabular.modified);
+        assert_eq!(tabular.data_frame.height(), 2);
+        assert_eq!(prices(&tabular), [Some(3), None]);
+        assert_eq!(tabular.source().column("sold").unwrap().null_count(), 0);
+
+        tabular.append_row().unwrap();
+        assert_eq!(tabular.source().height(), 3);
+        assert_eq!(
+            tabular.source().get_row(2).unwrap().0,
+            vec![AnyValue::Null; 3]
+        );
+        assert_eq!(prices(&tabular), [Some(3), None, None]);
+        assert_eq!(tabular.select, 2);
+
+        tabular.undo().unwrap();
+        assert_eq!(prices(&tabular), [Some(3), None]);
+        tabular.undo().unwrap();
+        assert_eq!(prices(&tabular), [Some(1), Some(3), None]);
+        // the row comes back selected
+        assert_eq!(tabular.select, 0);
+        tabular.redo().unwrap();
+        assert_eq!(prices(&tabular), [Some(3), None]);
+        tabular.redo().unwrap();
+        assert_eq!(prices(&tabular), [Some(3), None, None]);
+        assert!(tabular.redo().is_err());
+        tabular.undo().unwrap();
+        tabular.undo().unwrap();
+
+        // the sort
+        tabular.undo().unwrap();
+        assert_eq!(tabular.sort, None);
+        assert_eq!(prices(&tabular), [Some(3), Some(1), None]);
+        assert!(tabular.undo().is_err());
+        tabular.redo().unwrap();
+        assert_eq!(prices(&tabular), [Some(1), Some(3), None]);
+        tabular.reset_sort();
+        assert_eq!(tabular.data_frame, tabular.source().clone());
+
+        tabular.select(2);
+        tabular.delete_selected_rows().unwrap();
+        assert_eq!(tabular.select, 1);
+        tabular.set_data_frame(tabular.data_frame.head(Some(1)));
+        assert!(tabular.delete_selected_rows().is_err());
+        assert!(tabular.append_row().is_err());
+        assert!(tabular.undo().is_err());
+        tabular.reset();
+        tabular.undo().unwrap();
+        assert_eq!(prices(&tabular), [Some(3), Some(1), None]);
+    }
+
+    #[test]
+    fn test_range() {
+        let df = df! {
+            "name" => ["a", "b", "c", "d", "e"],
+            "price" => [5, 1, 4, 2, 3],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        assert_eq!(tabular.selected_range(), None);
+        tabular.select = 3;
+        tabular.toggle_range();
+        tabular.select_up(2);
+        assert_eq!(tabular.selected_range(), Some(1..4));
+        assert_eq!(
+            prices_of(&tabular.selected_data_frame()),
+            [Some(1), Some(4), Some(2)]
+        );
+        tabular.toggle_range();
+        assert_eq!(tabular.selected_data_frame().height(), 5);
+
+        // the rows of the sorted view are spread over the source
+        tabular.select_column = 1;
+        tabular.sort_selected(false).unwrap();
+        assert_eq!(tabular.selected_range(), None);
+        tabular.select = 1;
+        tabular.start_range();
+        tabular.select_down(2);
+        tabular.delete_selected_rows().unwrap();
+        assert_eq!(tabular.selected_range(), None);
+        assert_eq!(prices(&tabular), [Some(1), Some(5)]);
+        tabular.undo().unwrap();
+        assert_eq!(
+            prices_of(tabular.source()),
+            [Some(5), Some(1), Some(4), Some(2), Some(3)]
+        );
+        assert_eq!(
+            prices(&tabular),
+            [Some(1), Some(2), Some(3), Some(4), Some(5)]
+        );
+        tabular.redo().unwrap();
+        assert_eq!(prices_of(tabular.source()), [Some(5), Some(1)]);
+    }
+
+    #[test]
+    fn test_undo_view() {
+        let df = df! {
+            "price" => [3, 1, 2],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.filter_selected("> 1").unwrap();
+        tabular.sort_selected(false).unwrap();
+        tabular.select = 1;
+        tabular.edit_selected("1").unwrap();
+        assert_eq!(prices(&tabular), [Some(2), Some(1)]);
+
+        tabular.undo().unwrap();
+        assert_eq!(prices(&tabular), [Some(2), Some(3)]);
+        tabular.undo().unwrap();
+        assert_eq!(prices(&tabular), [Some(3), Some(2)]);
+        tabular.undo().unwrap();
+        assert!(tabular.filters.is_empty());
+        assert_eq!(prices(&tabular), [Some(3), Some(1), Some(2)]);
+        tabular.redo().unwrap();
+        tabular.redo().unwrap();
+        assert_eq!(prices(&tabular), [Some(2), Some(3)]);
+
+        // a change forgets the undone ones
+        tabular.clear_filters();
+        assert!(tabular.redo().is_err());
+        assert_eq!(prices(&tabular), [Some(1), Some(2), Some(3)]);
+        // the view of a query result is not undone after the query
+        tabular.set_data_frame(tabular.data_frame.clone());
+        tabular.sort_selected(true).unwrap();
+        tabular.reset();
+        assert!(tabular.undo().is_err());
+    }
+
+    #[test]
+    fn test_load_page() {
+        let df = df! {
+            "id" => (0..1000).collect::<Vec<i32>>(),
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.page_size = 100;
+        tabular.rendered_rows = 10;
+        tabular.set_data_frame(tabular.data_frame.clone());
+        assert_eq!(tabular.table_values.window(), 0..100);
+        assert_eq!(tabular.table_values.height(), 1000);
+
+        tabular.select(500);
+        tabular.adjust_offset();
+        tabular.load_page().unwrap();
+        assert_eq!(tabular.table_values.window(), 446..546);
+        assert_eq!(tabular.table_values.get(500, 0), Some("500"));
+        assert_eq!(tabular.table_values.get(0, 0), None);
+
+        // moving within the page keeps it
+        tabular.page_down();
+        tabular.load_page().unwrap();
+        assert_eq!((tabular.offset, tabular.select), (501, 510));
+        assert_eq!(tabular.table_values.window(), 446..546);
+
+        tabular.select_last();
+        tabular.adjust_offset();
+        tabular.load_page().unwrap();
+        assert_eq!(tabular.table_values.window(), 900..1000);
+        tabular.page_down();
+        tabular.page_up();
+        assert_eq!((tabular.offset, tabular.select), (980, 989));
+
+        // searches cover the rows out of the page
+        assert!(tabular.search("12", SearchOptions::default()).unwrap());
+        assert_eq!(tabular.select, 12);
+    }
+
+    #[test]
+    fn test_search_selects_column() {
+        let df = df! {
+            "a" => ["x", "y", "z"],
+            "b" => ["x", "y", "z"],
+            "c" => ["x", "y", "z"],
+            "d" => ["x", "y", "match"],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.adjust_column_offset(5);
+        assert_eq!(tabular.column_offset, 0);
+
+        assert!(tabular.search("match", SearchOptions::default()).unwrap());
+        assert_eq!((tabular.select, tabular.select_column), (2, 3));
+        // the column past the visible ones is scrolled into view
+        tabular.adjust_column_offset(5);
+        assert_eq!(tabular.column_offset, 3);
+
+        assert!(tabular.search("y", SearchOptions::default()).unwrap());
+        assert!(tabular.search_next());
+        assert_eq!((tabular.select, tabular.select_column), (1, 1));
+    }
+
+    #[test]
+    fn test_lazy_search() {
+        let df = df! { "id" => (0..1000).collect::<Vec<i64>>() }.unwrap();
+        let lazy = LazySource {
+            frame: df.clone().lazy(),
+            height: df.height(),
+        };
+        let mut tabular = Tabular::new_lazy("test", "df", df.head(Some(100)), lazy);
+        // the rows out of the window are not searched
+        assert!(tabular.search("500", SearchOptions::default()).is_err());
+        assert!(tabular.search.is_none());
+    }
+
+    #[test]
+    fn test_widths_of_the_pages() {
+        let mut names = vec!["a"; 1000];
+        names[999] = "a much longer name";
+        let df = df! { "name" => names, "id" => (0..1000).collect::<Vec<i64>>() }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.page_size = 100;
+        tabular.select_right();
+        tabular.hide_selected().unwrap();
+        tabular.unhide_all();
+        // only the rows of the page are measured
+        assert_eq!(tabular.widths, [4, 2]);
+        tabular.select_last();
+        tabular.adjust_offset();
+        tabular.load_page().unwrap();
+        assert_eq!(tabular.widths, [18, 3]);
+        // and the columns stay as wide back at the top
+        tabular.select_first();
+        tabular.adjust_offset();
+        tabular.load_page().unwrap();
+        assert_eq!(tabular.widths, [18, 3]);
+    }
+
+    #[test]
+    fn test_defining_query() {
+        let df = df! { "id" => [2, 1, 3] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df.clone());
+        assert_eq!(
+            tabular.defining_query().as_deref(),
+            Some("SELECT * FROM df")
+        );
+        tabular.sort_selected(false).unwrap();
+        assert_eq!(tabular.defining_query(), None);
+        tabular.reset_sort();
+        tabular.edit_selected("5").unwrap();
+        assert_eq!(tabular.defining_query(), None);
+
+        let mut tabular = Tabular::new("test", "df", df);
+        let (query, result) = ("SELECT id FROM df".to_owned(), df! { "id" => [2] }.unwrap());
+        tabular.show_result(query, std::time::Duration::ZERO, result);
+        assert_eq!(
+            tabular.defining_query().as_deref(),
+            Some("SELECT id FROM df")
+        );
+    }
+
+    #[test]
+    fn test_cast_column() {
+        let df = df! { "id" => ["1", "2", "x", "-"], "price" => [3, 1, 4, 1] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        assert_eq!(
+            tabular
+                .cast_column("id", &DataType::Int64, true)
+                .unwrap_err()
+                .to_string(),
+            "2 values in id are not valid i64, as 'x'"
+        );
+        assert_eq!(tabular.dtypes, ["str", "i32"]);
+
+        assert_eq!(
+            tabular.cast_column("id", &DataType::Int64, false).unwrap(),
+            2
+        );
+        assert_eq!(tabular.dtypes, ["i64", "i32"]);
+        assert_eq!(tabular.source().column("id").unwrap().null_count(), 2);
+        assert!(tabular.modified);
+        tabular.undo().unwrap();
+        assert_eq!(tabular.dtypes, ["str", "i32"]);
+    }
+
+    #[test]
+    fn test_append_rows() {
+        let df = df! { "name" => ["a", "b", "c", "d"], "price" => [3, 1, 4, 1] }.unwrap();
+        let rows = df! { "name" => ["e"], "price" => [5] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_last();
+        tabular.append_rows(&rows).unwrap();
+        assert_eq!(tabular.data_frame.height(), 5);
+        assert_eq!(tabular.select, 4);
+
+        // scrolled up, the selection stays
+        tabular.select_first();
+        tabular.append_rows(&rows).unwrap();
+        assert_eq!((tabular.data_frame.height(), tabular.select), (6, 0));
+
+        // the appended rows go through the sort
+        tabular.select_right();
+        tabular.sort_selected(false).unwrap();
+        let row = df! { "name" => ["f"], "price" => [2] }.unwrap();
+        tabular.append_rows(&row).unwrap();
+        assert_eq!(prices(&tabular), [1, 1, 2, 3, 4, 5, 5].map(Some));
+
+        tabular.reload_source(rows).unwrap();
+        assert_eq!(prices(&tabular), [Some(5)]);
+        assert_eq!(tabular.select, 0);
+    }
+
+    #[test]
+    fn test_reload() {
+        use crate::{args::Args, reader::Source};
+        use clap::Parser;
+
+        let path = std::env::temp_dir().join(format!("tabiew-reload-{}.csv", std::process::id()));
+        std::fs::write(&path, "id,price\n1,3\n2,4\n").unwrap();
+        let origin = Origin {
+            source: Source::File(path.clone()),
+            table: None,
+            args: Arc::new(Args::parse_from(["tw", "--columns", "price"])),
+        };
+        let mut tabular = Tabular::new("test", "df", origin.read().unwrap().data_frame);
+        tabular.projection = vec!["price".to_owned()];
+        tabular.select = 1;
+        let prices = |tabular: &Tabular| {
+            let column = tabular.data_frame.column("price").unwrap();
+            column.i64().unwrap().to_vec()
+        };
+
+        std::fs::write(&path, "id,price\n1,3\n2,5\n3,6\n").unwrap();
+        tabular.reload(origin.read().unwrap()).unwrap();
+        assert_eq!(tabular.headers, ["price"]);
+        assert_eq!(prices(&tabular), [3, 5, 6].map(Some));
+        assert_eq!(tabular.select, 1);
+
+        std::fs::write(&path, "id,price\n1,7\n").unwrap();
+        tabular.reload(origin.read().unwrap()).unwrap();
+        assert_eq!(tabular.select, 0);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(origin.read().is_err());
+        assert_eq!(prices(&tabular), [Some(7)]);
+        let stdin = Origin {
+            source: Source::Stdin,
+            ..origin
+        };
+        let err = stdin.read().unwrap_err();
+        assert_eq!(err.to_string(), "stdin cannot be read again");
+    }
+
+    #[test]
+    fn test_projection() {
+        let df = df! { "price" => [3], "name" => ["a"] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.projection = vec!["price".to_owned(), "name".to_owned()];
+        let rows = df! { "id" => [2], "name" => ["b"], "price" => [1] }.unwrap();
+        tabular.append_rows(&rows).unwrap();
+        assert_eq!(tabular.headers, ["price", "name"]);
+        assert_eq!(prices(&tabular), [Some(3), Some(1)]);
+        assert!(tabular.take_dropped_columns().is_empty());
+
+        // a column gone from the file is dropped, with a warning
+        let rows = df! { "id" => [4], "price" => [5] }.unwrap();
+        tabular.reload_source(rows).unwrap();
+        assert_eq!(tabular.headers, ["price"]);
+        assert_eq!(tabular.projection, ["price"]);
+        assert_eq!(tabular.take_dropped_columns(), ["name"]);
+        assert!(tabular.take_dropped_columns().is_empty());
+
+        // with none of them left, all the columns are shown
+        let rows = df! { "id" => [6] }.unwrap();
+        tabular.reload_source(rows).unwrap();
+        assert_eq!(tabular.headers, ["id"]);
+        assert!(tabular.projection.is_empty());
+        assert_eq!(tabular.take_dropped_columns(), ["price"]);
+    }
+
+    #[test]
+    fn test_select_column_named() {
+        let df = df! {
+            "id" => [1], "price_usd" => [2], "Price" => [3], "name" => [4], "p" => [5]
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        // the exact match first, then the others in turn
+        assert_eq!(tabular.select_column_named("price").unwrap(), (2, 2));
+        assert_eq!(tabular.select_column, 2);
+        assert_eq!(tabular.select_column_named("price").unwrap(), (1, 2));
+        assert_eq!(tabular.select_column, 1);
+        assert_eq!(tabular.select_column_named("P").unwrap(), (2, 3));
+        assert_eq!(tabular.select_column, 2);
+        assert_eq!(tabular.select_column_named("na").unwrap(), (1, 1));
+        assert_eq!(tabular.select_column, 3);
+
+        tabular.hide_selected().unwrap();
+        assert!(tabular.select_column_named("nam").is_err());
+        assert!(tabular.select_column_named("x").is_err());
+    }
+
+    #[test]
+    fn test_empty() {
+        for df in [
+            df! { "id" => Vec::<i32>::new() }.unwrap(),
+            DataFrame::empty(),
+        ] {
+            let mut tabular = Tabular::new("test", "df", df);
+            assert!(tabular.is_empty());
+            tabular.rendered_rows = 10;
+            tabular.select_down(3);
+            tabular.page_down();
+            tabular.select_last();
+            tabular.select_random();
+            tabular.select_right();
+            tabular.toggle_bookmark();
+            assert_eq!((tabular.select, tabular.offset), (0, 0));
+            assert!(tabular.bookmarks.is_empty());
+            tabular.toggle_cell_popup();
+            tabular.switch_view();
+            tabular.toggle_record_view();
+            assert!(tabular.cell_popup.is_none());
+            assert!(tabular.detailed_view.is_none());
+            assert!(tabular.record_view.is_none());
+            assert!(tabular.render_widths().len() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let df = df! { "id" => [1, 2, 3, 4, 5], "price" => [3, 5, 1, 4, 2] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.toggle_bookmark();
+        tabular.select(2);
+        tabular.toggle_bookmark();
+        assert_eq!(tabular.bookmarks, BTreeSet::from([0, 2]));
+
+        // the bookmarks follow their rows when sorted
+        tabular.select_right();
+        tabular.sort_selected(false).unwrap();
+        assert!(tabular.is_bookmarked(0) && tabular.is_bookmarked(2));
+        tabular.select(0);
+        assert!(tabular.select_bookmark(true));
+        assert_eq!(tabular.select, 2);
+        assert!(tabular.select_bookmark(true));
+        assert_eq!(tabular.select, 0);
+        assert!(tabular.select_bookmark(false));
+        assert_eq!(tabular.select, 2);
+
+        // and when rows before them are deleted
+        tabular.reset_sort();
+        tabular.select(1);
+        tabular.delete_selected_rows().unwrap();
+        assert_eq!(tabular.bookmarks, BTreeSet::from([0, 1]));
+        tabular.undo().unwrap();
+        assert_eq!(tabular.bookmarks, BTreeSet::from([0, 2]));
+
+        tabular.set_data_frame(df! { "id" => [1] }.unwrap());
+        assert!(tabular.bookmarks.is_empty());
+        assert!(tabular.take_dropped_bookmarks());
+        assert!(!tabular.take_dropped_bookmarks());
+        assert!(!tabular.select_bookmark(true));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let df = df! {
+            "status" => ["ok", "error", "ok", "error"],
+            "price" => [3, 1, 4, 1],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.highlight("status = 'error'", Color::Red).unwrap();
+        tabular.highlight("price > 3", Color::Blue).unwrap();
+        assert!(tabular.highlight("status +", Color::Red).is_err());
+        let colors =
+            |tabular: &Tabular| (0..4).map(|row| tabular.row_color(row)).collect::<Vec<_>>();
+        assert_eq!(
+            colors(&tabular),
+            [None, Some(Color::Red), Some(Color::Blue), Some(Color::Red)]
+        );
+
+        // the rules follow the rows when they are sorted
+        tabular.select_right();
+        tabular.sort_selected(true).unwrap();
+        assert_eq!(
+            colors(&tabular),
+            [Some(Color::Blue), None, Some(Color::Red), Some(Color::Red)]
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        let df = df! {
+            "status" => ["ok", "error", "ok", "error"],
+            "name" => ["a", "b", "c", "d"],
+            "paid" => [true, false, true, true],
+            "price" => [3, 1, 4, 1],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        assert!(tabular
+            .column_symbols
+            .iter()
+            .all(|symbols| *symbols == ColumnSymbols::Plain));
+        tabular.set_symbols(true);
+        assert_eq!(
+            tabular.column_symbols,
+            [
+                ColumnSymbols::Category,
+                ColumnSymbols::Plain,
+                ColumnSymbols::Boolean,
+                ColumnSymbols::Plain
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let df = df! {
+            "id" => [1, 2, 3, 4],
+            "text" => ["short", "one two three four five six", "long words", "end"],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.rendered_rows = 4;
+        tabular.select_right();
+        tabular.toggle_wrap();
+        tabular.resize_selected(-17);
+        let widths = tabular.render_widths();
+        assert_eq!(widths[1], 10);
+        assert_eq!(tabular.row_heights(&widths), [1, 3]);
+
+        // the offset moves for the taller rows
+        tabular.select(2);
+        tabular.adjust_offset();
+        assert_eq!(tabular.offset, 1);
+        tabular.select(3);
+        tabular.adjust_offset();
+        assert_eq!(tabular.offset, 2);
+
+        let areas = CellAreas {
+            rows: 1..5,
+            row_heights: vec![1, 3],
+            ..Default::default()
+        };
+        assert_eq!(areas.row_at(1), Some(0));
+        assert_eq!(areas.row_at(4), Some(1));
+
+        tabular.toggle_wrap();
+        assert!(!tabular.wraps());
+    }
+
+    #[test]
+    fn test_move_selected() {
+        let df = df! { "a" => [1], "b" => [2], "c" => [3], "d" => [4] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.move_selected(false);
+        assert_eq!(tabular.headers, ["a", "b", "c", "d"]);
+        tabular.move_selected(true);
+        tabular.move_selected(true);
+        assert_eq!(tabular.headers, ["b", "c", "a", "d"]);
+        assert_eq!(tabular.select_column, 2);
+
+        // hidden columns are skipped over
+        tabular.select_column = 1;
+        tabular.hide_selected().unwrap();
+        tabular.move_selected(false);
+        assert_eq!(tabular.headers, ["a", "b", "d"]);
+        assert_eq!(tabular.select_column, 0);
+        let output = |tabular: &Tabular| tabular.selected_data_frame().get_column_names().join(",");
+        assert_eq!(output(&tabular), "a,b,d");
+        tabular.keep_column_order = true;
+        assert_eq!(output(&tabular), "a,b,d");
+        tabular.move_selected(true);
+        assert_eq!(output(&tabular), "a,b,d");
+        assert_eq!(tabular.headers, ["b", "a", "d"]);
+
+        let schema = tabular.source_frame().schema().unwrap();
+        assert_eq!(schema.iter_names().next().unwrap().as_str(), "a");
+        tabular.keep_column_order = false;
+        let schema = tabular.source_frame().schema().unwrap();
+        let names = schema
+            .iter_names()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["b", "c", "a", "d"]);
+    }
+
+    #[test]
+    fn test_restore() {
+        let df = df! { "name" => ["a", "b", "c", "d"], "price" => [3, 1, 4, 1] }.unwrap();
+        let mut tabular = Tabular::new("test", "df", df.clone());
+        tabular.select_right();
+        tabular.sort_selected(true).unwrap();
+        tabular.select = 2;
+        tabular.hide_selected().unwrap();
+        let place = tabular.place().unwrap();
+
+        let mut restored = Tabular::new("test", "df", df);
+        restored.restore(&place);
+        assert_eq!(restored.place(), Some(place.clone()));
+        assert_eq!(restored.sort, tabular.sort);
+        assert_eq!(restored.headers, ["name"]);
+        // the sort is not undone
+        assert!(restored.undo().is_err());
+
+        // rows and columns that are gone are skipped
+        let place = Place {
+            select: 10,
+            hidden: vec!["name".to_owned(), "price".to_owned()],
+            ..place
+        };
+        let df = df! { "name" => ["a"], "price" => [3] }.unwrap();
+        let mut restored = Tabular::new("test", "df", df);
+        restored.restore(&place);
+        assert_eq!((restored.select, restored.headers.len()), (0, 2));
+    }
+
+    #[test]
+    fn test_sort_again() {
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let df = df! {
+            "day" => [date(2), date(1), date(3)],
+            "price" => [2, 1, 3],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        let sort = |tabular: &mut Tabular| {
+            tabular.sort_selected_again().unwrap();
+            tabular.sort.clone().map(|sort| sort.descending)
+        };
+        // dates are sorted newest first, then flipped
+        assert_eq!(sort(&mut tabular), Some(true));
+        assert_eq!(sort(&mut tabular), Some(false));
+        tabular.select_right();
+        assert_eq!(sort(&mut tabular), Some(false));
+        assert_eq!(sort(&mut tabular), Some(true));
+
+        // the last order used for a column, which a place keeps
+        tabular.select_left();
+        assert_eq!(sort(&mut tabular), Some(false));
+        let place = tabular.place().unwrap();
+        let sorts = |place: &Place| {
+            place
+                .directions
+                .iter()
+                .map(|sort| (sort.column.clone(), sort.descending))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sorts(&place),
+            [("day".to_owned(), false), ("price".to_owned(), true)]
+        );
+        let mut restored = Tabular::new("test", "df", tabular.data_frame.clone());
+        restored.restore(&place);
+        restored.select_right();
+        assert_eq!(sort(&mut restored), Some(true));
+    }
+
+    #[test]
+    fn test_reset() {
+        let df = df! {
+            "name" => ["a", "b", "c", "d"],
+            "price" => [Some(3), None, Some(1), Some(2)],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_right();
+        tabular.sort_selected(true).unwrap();
+        tabular.filter_selected("is not null").unwrap();
+        tabular.select_left();
+        tabular.hide_selected().unwrap();
+        tabular.set_data_frame(tabular.data_frame.head(Some(1)));
+        tabular.last_query = Some("SELECT * FROM df LIMIT 1".to_owned());
+
+        tabular.reset();
+        assert_eq!(prices(&tabular), [Some(3), None, Some(1), Some(2)]);
+        assert_eq!(tabular.headers, ["name", "price"]);
+        assert!(tabular.filters.is_empty() && tabular.hidden.is_empty());
+        assert_eq!(tabular.sort, None);
+        assert_eq!(tabular.last_query, None);
+        assert_eq!((tabular.select, tabular.select_column), (0, 0));
+    }
+
+    #[test]
+    fn test_hide_columns() {
+        let df = df! {
+            "a" => [1],
+            "b" => [2],
+            "c" => [3],
+        }
+        .unwrap();
+        let mut tabular = Tabular::new("test", "df", df);
+        tabular.select_right();
+        tabular.hide_selected().unwrap();
+        assert_eq!(tabular.headers, ["a", "c"]);
+        assert_eq!(tabular.table_values.get_row(0), ["1", "3"]);
+        assert_eq!(tabular.data_frame.width(), 3);
+
+        tabular.hide_selected().unwrap();
+        assert!(tabular.hide_selected().is_err());
+        assert_eq!(tabular.headers, ["a"]);
+
+        tabular.unhide_all();
+        assert_eq!(tabular.headers, ["a", "b", "c"]);
+        assert_eq!(tabular.select_column, 0);
+    }
+
+    #[test]
+    fn test_mode() {
+        let mut status_bar = StatusBar::default();
+        assert_eq!(status_bar.mode(), Mode::Normal);
+        status_bar.command(":");
+        assert_eq!(status_bar.mode(), Mode::Command);
+        status_bar.command("/");
+        assert_eq!(status_bar.mode(), Mode::Search);
+        status_bar.error("oops");
+        assert_eq!(status_bar.mode(), Mode::Normal);
+    }
+
+    #[test]
+    fn test_app_step() {
+        let df = df! { "id" => [3, 1, 2] }.unwrap();
+        let mut sql_context = SQLContext::new();
+        let tabular = Tabular::new("test", "df", df);
+        tabular.register(&mut sql_context);
+        let mut app = App::new(Tabs::new(vec![tabular]), sql_context);
+        let keys = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                let key = match c {
+                    '\n' => KeyCode::Enter,
+                    c => KeyCode::Char(c),
+                };
+                app.step(Event::Key(key.into())).unwrap();
+            }
+        };
+
+        keys(&mut app, "sj");
+        assert_eq!(app.tabs.selected().table_values.get_row(0), ["1"]);
+        assert_eq!(app.tabs.selected().select, 1);
+
+        // ticks until the query in the background is done
+        let settle = |app: &mut App| {
+            for _ in 0..1000 {
+                if !app.needs_tick() {
+                    break;
+                }
+                app.step(Event::Tick).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        };
+        keys(&mut app, ":Q SELECT * FROM df WHERE id > 1\n");
+        settle(&mut app);
+        assert_eq!(app.tabs.selected().data_frame.height(), 2);
+        assert_eq!(app.status_bar.mode(), Mode::Normal);
+        let timing = app.tabs.selected().query_timing;
+        assert!(timing.is_some_and(|(_, rows)| rows == 2));
+
+        // a failed query shows in the status bar, the table staying as it was
+        keys(&mut app, ":Q SELECT * FROM orders\n");
+        settle(&mut app);
+        assert!(matches!(
+            &app.status_bar.state,
+            StatusBarState::Error(error) if error.contains("orders")
+        ));
+        assert_eq!(app.tabs.selected().data_frame.height(), 2);
+        // the next key dismisses the error
+        keys(&mut app, "jj");
+        assert!(matches!(app.status_bar.state, StatusBarState::Normal));
+
+        // the table is drawn again in the new size, the selection kept in view
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let rendered_rows = app.tabs.selected().rendered_rows;
+        app.step(Event::Resize(40, 6)).unwrap();
+        terminal.backend_mut().resize(40, 6);
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        assert!(app.tabs.selected().rendered_rows < rendered_rows);
+        assert_eq!(app.tabs.selected().select, 1);
+
+        assert!(app.running);
+        app.step(Event::Key(KeyCode::Char('q').into())).unwrap();
+        assert!(!app.running);
+    }
+}
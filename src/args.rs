@@ -1,11 +1,19 @@
 use std::path::PathBuf;
 use clap::{Parser, ValueEnum};
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 pub struct Args {
-    #[arg(help = "Path to the file to be opened.", required = true)]
-    pub file_name: PathBuf,
+    #[arg(help = "Path(s) or HTTP(S) URL(s) of the file(s) to be opened, each in its own tab. Reads from stdin if omitted or '-'.")]
+    pub file_name: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Format of the input data. Inferred from the file extension if not set.",
+        required = false,
+        value_enum
+    )]
+    pub format: Option<FileFormat>,
 
     #[arg(
         long,
@@ -21,6 +29,13 @@ pub struct Args {
     )]
     pub ignore_errors: bool,
 
+    #[arg(
+        long,
+        help = "Check that every row of CSV files has as many fields as the first one, reporting the lines of the rows that do not, and that no two columns have the same name.",
+        default_value_t = false
+    )]
+    pub strict: bool,
+
     #[arg(
         long,
         help = "Method to infer the schema while loading the file.",
@@ -32,11 +47,42 @@ pub struct Args {
 
     #[arg(
         long,
-        help = "Character used as the field separator or delimiter while loading the file.",
+        help = "Chrono format of date columns, e.g. %d/%m/%Y. Used when the schema is inferred safely.",
+        required = false
+    )]
+    pub date_format: Option<String>,
+
+    #[arg(
+        long,
+        help = "Chrono format of datetime columns, e.g. %Y-%m-%dT%H:%M:%S%#z. Used when the schema is inferred safely.",
+        required = false
+    )]
+    pub datetime_format: Option<String>,
+
+    #[arg(
+        long,
+        help = "Character used as the field separator or delimiter while loading the file. Detected from the content if not set.",
+        required = false
+    )]
+    pub separator: Option<char>,
+
+    #[arg(
+        long,
+        help = "Split the fields of the lines on runs of spaces and tabs, for tables of aligned columns.",
         required = false,
-        default_value_t = ','
+        conflicts_with_all = ["separator", "fixed_width"]
     )]
-    pub separator: char,
+    pub whitespace: bool,
+
+    #[arg(
+        long,
+        value_name = "OFFSETS",
+        help = "Comma-separated byte offsets where the fields of fixed-width lines start, e.g. 0,8,20.",
+        required = false,
+        value_delimiter = ',',
+        conflicts_with = "separator"
+    )]
+    pub fixed_width: Vec<usize>,
 
     #[arg(
         long,
@@ -46,6 +92,285 @@ pub struct Args {
     )]
     pub quote_char: char,
 
+    #[arg(
+        long,
+        help = "Character starting the comment lines of CSV files, which are skipped while loading them.",
+        required = false
+    )]
+    pub comment_char: Option<char>,
+
+    #[arg(
+        long = "null-value",
+        help = "String to be read as a missing value while loading CSV files, in addition to empty fields. Can be given multiple times.",
+        required = false
+    )]
+    pub null_values: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Character encoding of CSV files.",
+        required = false,
+        default_value_t = Encoding::Utf8,
+        value_enum
+    )]
+    pub encoding: Encoding,
+
+    #[arg(
+        long,
+        help = "Name or zero-based index of the worksheet to open from an Excel workbook. Defaults to the first worksheet.",
+        required = false
+    )]
+    pub sheet: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "A1:D100",
+        help = "Cells to read from the Excel worksheet, the first row of the range being the header unless --no-header is set. Defaults to the used area of the worksheet.",
+        required = false
+    )]
+    pub range: Option<String>,
+
+    #[arg(
+        long,
+        help = "Table to open from a SQLite database. Opens every table in its own tab if not set.",
+        required = false
+    )]
+    pub table: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        help = "Comma-separated names of the only columns to load, in this order. Parquet, Arrow and CSV files then skip reading the other columns.",
+        required = false,
+        value_delimiter = ','
+    )]
+    pub columns: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "ROWS",
+        help = "Load only the first rows of the files, reading no further.",
+        required = false,
+        conflicts_with = "sample"
+    )]
+    pub head: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "ROWS",
+        help = "Load a random sample of rows of the files, kept in their order.",
+        required = false
+    )]
+    pub sample: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Maximum time in seconds to spend fetching a file from an HTTP(S) URL.",
+        required = false
+    )]
+    pub timeout: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Memory-map Arrow IPC files instead of reading them into memory.",
+        default_value_t = false
+    )]
+    pub mmap: bool,
+
+    #[arg(
+        long,
+        help = "Read CSV and Parquet files on demand, keeping only the rows around the view in memory. Sorting, filters, and statistics then need a query.",
+        default_value_t = false
+    )]
+    pub lazy: bool,
+
+    #[arg(
+        long,
+        help = "Unnest the struct columns, as nested JSON objects, into columns named after their path, as address.city.",
+        default_value_t = false
+    )]
+    pub flatten: bool,
+
+    #[arg(
+        long,
+        help = "With --flatten, also explode the list columns into a row per item.",
+        default_value_t = false
+    )]
+    pub explode: bool,
+
+    #[arg(
+        long,
+        help = "Levels of nesting unnested by --flatten at most, deeper structs are kept as they are.",
+        required = false,
+        default_value_t = 8
+    )]
+    pub flatten_depth: usize,
+
+    #[arg(
+        long,
+        help = "Keep reading the rows appended to CSV files, staying at the bottom while the last row is selected.",
+        default_value_t = false
+    )]
+    pub follow: bool,
+
+    #[arg(
+        long,
+        help = "Number of rows formatted for display at a time, a page around the visible ones. With --lazy, also the number of rows collected at a time.",
+        required = false,
+        default_value_t = 1000
+    )]
+    pub page_size: usize,
+
+    #[arg(
+        long,
+        help = "Show null values as a dimmed ∅ to tell them from empty strings.",
+        required = false,
+        default_value_t = false
+    )]
+    pub highlight_nulls: bool,
+
+    #[arg(
+        long,
+        help = "Do not shade every other row, leaving all the rows on the background of the theme.",
+        required = false,
+        default_value_t = false
+    )]
+    pub no_stripes: bool,
+
+    #[arg(
+        long,
+        help = "Show booleans as symbols and color the values of string columns with few distinct ones, each value always in the same color. Only the display changes.",
+        required = false,
+        default_value_t = false
+    )]
+    pub symbols: bool,
+
+    #[arg(
+        long,
+        help = "Wrap the values of all the columns over several lines rather than cutting them.",
+        required = false,
+        default_value_t = false
+    )]
+    pub wrap: bool,
+
+    #[arg(
+        long,
+        help = "Show the data type of each column under its name.",
+        required = false,
+        default_value_t = false
+    )]
+    pub show_types: bool,
+
+    #[arg(
+        long,
+        value_name = "WIDTH",
+        help = "Widest a column is drawn, the longer values being cut with an ellipsis, or 0 for no limit. Resizing a column with + and - goes past it.",
+        required = false,
+        default_value_t = 40
+    )]
+    pub max_col_width: usize,
+
+    #[arg(
+        long,
+        help = "Number of columns kept on the left while scrolling horizontally.",
+        required = false,
+        default_value_t = 0
+    )]
+    pub freeze: usize,
+
+    #[arg(
+        long,
+        value_name = "FIRST",
+        help = "Show the row numbers on the left, counting from 1, or from 0 with --line-numbers=0.",
+        required = false,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "1",
+        value_parser = clap::value_parser!(u8).range(0..=1)
+    )]
+    pub line_numbers: Option<u8>,
+
+    #[arg(
+        long,
+        help = "Separate the thousands of numbers with commas. Only the display changes, not the values used by queries and exports.",
+        default_value_t = false
+    )]
+    pub thousands: bool,
+
+    #[arg(
+        long,
+        value_name = "DIGITS",
+        help = "Show floats with this many digits after the decimal point, in scientific notation when too large or too small for it.",
+        required = false
+    )]
+    pub float_precision: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SQL",
+        help = "Run the SQL query on the files and write its result with --output, without the terminal user interface.",
+        required = false
+    )]
+    pub query: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "File the result of --query is written to, in the format of its extension, or - for the standard output, the default.",
+        required = false,
+        requires = "query"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Format the result of --query is written in. Inferred from the extension of --output if not set, CSV on the standard output.",
+        required = false,
+        requires = "query",
+        value_enum
+    )]
+    pub output_format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        help = "Commands or SQL queries to run on launch, one per line. Defaults to init.tw in the config directory.",
+        required = false
+    )]
+    pub init: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Session saved with :session save to restore: its files are opened after the given ones, and its tables created again from their queries.",
+        required = false
+    )]
+    pub session: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Do not capture the mouse, leaving it to the terminal for text selection.",
+        required = false,
+        default_value_t = false
+    )]
+    pub no_mouse: bool,
+
+    #[arg(
+        long,
+        help = "Start at the top of the files rather than at the row, column, sort and hidden columns they were left with.",
+        required = false,
+        default_value_t = false
+    )]
+    pub no_restore: bool,
+
+    #[arg(
+        long,
+        help = "Milliseconds between two ticks driving animations. Nothing is redrawn without input while there is no animation.",
+        required = false,
+        default_value_t = 250,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub tick_rate: u64,
+
     #[arg(
         long,
         help = "Tabiew theme",
@@ -54,6 +379,58 @@ pub struct Args {
         default_value_t = AppTheme::Monokai
     )]
     pub theme: AppTheme,
+
+    #[arg(
+        long,
+        help = "TOML file with the fg, bg, accent, header, and selection colors of a custom theme.",
+        required = false
+    )]
+    pub theme_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Render without colors, as when the NO_COLOR environment variable is set.",
+        required = false,
+        default_value_t = false
+    )]
+    pub no_color: bool,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum FileFormat {
+    Csv,
+    Parquet,
+    Json,
+    /// JSON lines, one object per line.
+    #[value(alias = "jsonl")]
+    Ndjson,
+    Arrow,
+    Excel,
+    Sqlite,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    /// JSON lines, one object per line.
+    #[value(alias = "jsonl")]
+    Ndjson,
+    Parquet,
+    /// GitHub-flavored Markdown table.
+    #[value(alias = "md")]
+    Markdown,
+    /// Box-drawn table, as shown in the terminal.
+    Table,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Encoding {
+    Utf8,
+    Utf8Lossy,
+    Latin1,
+    #[value(name = "windows-1252", alias = "cp1252")]
+    Windows1252,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -64,10 +441,35 @@ pub enum InferSchema {
     Safe,
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone, Default, PartialEq, ValueEnum)]
 pub enum AppTheme {
+    #[default]
     Monokai,
-    Terminal
+    Terminal,
+    SolarizedDark,
+    SolarizedLight,
+    Nord,
+    /// No colors, as with --no-color
+    Monochrome,
+    /// Solarized light on light terminal backgrounds, Monokai otherwise
+    Auto,
+    /// Colors of the --theme-file
+    #[value(skip)]
+    Config,
+}
+
+impl OutputFormat {
+    /// Extension of the files written in the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Table => "txt",
+        }
+    }
 }
 
 impl From<&InferSchema> for Option<usize> {
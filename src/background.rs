@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+/// Brightness of the terminal background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Asks the terminal for its background color with an OSC 11 query, waiting at most
+/// `timeout` for the answer. The query is followed by a device attributes request,
+/// which every terminal answers, so terminals ignoring OSC 11 do not wait for the
+/// whole timeout. Returns `None` if the color is not reported.
+#[cfg(unix)]
+pub fn query_background(timeout: Duration) -> Option<Background> {
+    use std::{fs::OpenOptions, io::Write, os::fd::AsRawFd, time::Instant};
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let mut response = Vec::new();
+    if tty
+        .write_all(b"\x1b]11;?\x07\x1b[c")
+        .and_then(|_| tty.flush())
+        .is_ok()
+    {
+        let deadline = Instant::now() + timeout;
+        let mut buf = [0; 256];
+        while !has_device_attributes(&response) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut fds = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: fds points to a single valid pollfd for the duration of the call
+            let ready = unsafe { libc::poll(&mut fds, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                break;
+            }
+            // SAFETY: buf is valid for writes of buf.len() bytes
+            let n = unsafe { libc::read(fds.fd, buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                break;
+            }
+            response.extend_from_slice(&buf[..n as usize]);
+        }
+    }
+    let _ = crossterm::terminal::disable_raw_mode();
+    parse_background(&response)
+}
+
+#[cfg(not(unix))]
+pub fn query_background(_timeout: Duration) -> Option<Background> {
+    None
+}
+
+/// Whether `response` holds a complete device attributes answer, `ESC [ ? ... c`.
+fn has_device_attributes(response: &[u8]) -> bool {
+    response
+        .windows(3)
+        .position(|window| window == b"\x1b[?")
+        .is_some_and(|start| response[start..].contains(&b'c'))
+}
+
+/// Reads the background from an OSC 11 answer like `ESC ] 11 ; rgb:RRRR/GGGG/BBBB BEL`,
+/// where each component has one to four hex digits.
+fn parse_background(response: &[u8]) -> Option<Background> {
+    let response = String::from_utf8_lossy(response);
+    let (_, color) = response.split_once("]11;rgb:")?;
+    let color = color.split(['\x07', '\x1b']).next()?;
+    let mut components = color.split('/').map(|component| {
+        let max = 16_f64.powi(component.len() as i32) - 1.0;
+        u16::from_str_radix(component, 16)
+            .ok()
+            .filter(|_| (1..=4).contains(&component.len()))
+            .map(|value| value as f64 / max)
+    });
+    let (r, g, b) = (
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    );
+    // relative luminance of the sRGB primaries
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_background() {
+        assert_eq!(
+            parse_background(b"\x1b]11;rgb:fdfd/f6f6/e3e3\x07\x1b[?62;22c"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            parse_background(b"\x1b]11;rgb:00/2b/36\x1b\\"),
+            Some(Background::Dark)
+        );
+        assert_eq!(parse_background(b"\x1b[?1;2c"), None);
+        assert_eq!(parse_background(b"\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+
+    #[test]
+    fn test_has_device_attributes() {
+        assert!(!has_device_attributes(b"\x1b]11;rgb:0000/0000/0000\x07"));
+        assert!(!has_device_attributes(b"\x1b[?62;2"));
+        assert!(has_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+    }
+}
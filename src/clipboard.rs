@@ -0,0 +1,35 @@
+use std::io::{self, Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Copies the text to the system clipboard through the terminal with an OSC 52 escape
+/// sequence, which also works over SSH. Inside tmux the sequence is wrapped so it
+/// reaches the outer terminal.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text, std::env::var_os("TMUX").is_some()).as_bytes())?;
+    stdout.flush()
+}
+
+fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("tabiew", false), "\x1b]52;c;dGFiaWV3\x07");
+        assert_eq!(
+            osc52("tabiew", true),
+            "\x1bPtmux;\x1b\x1b]52;c;dGFiaWV3\x07\x1b\\"
+        );
+    }
+}
@@ -1,12 +1,41 @@
-use std::{collections::HashMap, error::Error};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
 
-use polars::{df, frame::DataFrame, lazy::frame::LazyFrame};
+use polars::{
+    df,
+    frame::DataFrame,
+    io::SerWriter,
+    lazy::frame::IntoLazy,
+    prelude::{CsvWriter, IpcWriter, JsonFormat, JsonWriter, ParquetWriter},
+};
 use polars_sql::SQLContext;
+use ratatui::style::Color;
 
-use crate::app::Tabular;
+use crate::{
+    app::{StatusBar, Tabs, Tabular},
+    args::FileFormat,
+    clipboard,
+    follow::Follow,
+    palette::PaletteEntry,
+    queries::SavedQueries,
+    session::Session,
+    theme::{theme_name, themes},
+    utils::{markdown_table, parse_dtype},
+};
 
-pub type ExecutionFunction =
-    fn(&str, &mut Tabular, &mut SQLContext, &mut bool) -> Result<(), Box<dyn Error>>;
+pub type ExecutionFunction = fn(
+    &str,
+    &mut Tabs,
+    &mut SQLContext,
+    &mut StatusBar,
+    &mut bool,
+) -> Result<(), Box<dyn Error>>;
 pub type ExecutionTable = HashMap<&'static str, ExecutionFunction>;
 pub enum Prefix {
     Short(&'static str),
@@ -48,7 +77,7 @@ impl Default for CommandList {
                 prefix: Prefix::Both(":Q", ":query"),
                 usage: ":Q <query>",
                 description:
-                    "Query the data in Structured Query Language(SQL). The table name is 'df'",
+                    "Query the data in Structured Query Language(SQL). The table name is 'df', or 'df1', 'df2', ... when several files are open",
                 function: command_query,
             },
             Command {
@@ -57,10 +86,34 @@ impl Default for CommandList {
                 description: "Quit Tabiew",
                 function: command_quit,
             },
+            Command {
+                prefix: Prefix::Long(":as"),
+                usage: ":as <name>",
+                description: "Register the table as displayed under <name> to query it, and open it in a new tab",
+                function: command_as,
+            },
+            Command {
+                prefix: Prefix::Long(":save-query"),
+                usage: ":save-query <name>",
+                description: "Save the last executed query under <name>",
+                function: command_save_query,
+            },
+            Command {
+                prefix: Prefix::Long(":run-query"),
+                usage: ":run-query <name>",
+                description: "Execute the query saved under <name>",
+                function: command_run_query,
+            },
+            Command {
+                prefix: Prefix::Long(":queries"),
+                usage: ":queries",
+                description: "List the names of the saved queries",
+                function: command_queries,
+            },
             Command {
                 prefix: Prefix::Long(":goto"),
                 usage: ":goto <line_index>",
-                description: "Jumps to the <line_index> line",
+                description: "Jumps to the <line_index> line, or to the last line for $ or a negative index",
                 function: command_goto,
             },
             Command {
@@ -75,12 +128,108 @@ impl Default for CommandList {
                 description: "Jump <lines> line(s) down",
                 function: command_select_down,
             },
+            Command {
+                prefix: Prefix::Long(":col"),
+                usage: ":col <name>",
+                description: "Select the column named <name>, or the next one whose name starts with it",
+                function: command_col,
+            },
+            Command {
+                prefix: Prefix::Long(":export"),
+                usage: ":export <path>",
+                description: "Write the table as displayed, or the selected rows, to a csv, json, parquet, or arrow file",
+                function: command_export,
+            },
+            Command {
+                prefix: Prefix::Long(":export!"),
+                usage: ":export! <path>",
+                description: "Same as :export, overwriting the file if it exists",
+                function: command_export_overwrite,
+            },
+            Command {
+                prefix: Prefix::Long(":sql-export"),
+                usage: ":sql-export <path> <query>",
+                description: "Run the SQL query and write its result to a file, in the format of its extension, without showing it",
+                function: command_sql_export,
+            },
+            Command {
+                prefix: Prefix::Long(":sql-export!"),
+                usage: ":sql-export! <path> <query>",
+                description: "Same as :sql-export, overwriting the file if it exists",
+                function: command_sql_export_overwrite,
+            },
+            Command {
+                prefix: Prefix::Long(":copy-md"),
+                usage: ":copy-md",
+                description: "Copy the table as displayed, or the selected rows, to the clipboard as a Markdown table",
+                function: command_copy_markdown,
+            },
+            Command {
+                prefix: Prefix::Long(":edit"),
+                usage: ":edit <value>",
+                description: "Set the selected cell to <value>, converted to the type of its column; an empty value is null",
+                function: command_edit,
+            },
+            Command {
+                prefix: Prefix::Long(":cast"),
+                usage: ":cast <column> <dtype>",
+                description: "Convert the column to i64, f64, str, bool, date, datetime or another type, the values that do not convert becoming null, or an error with --strict",
+                function: command_cast,
+            },
+            Command {
+                prefix: Prefix::Long(":delete-row"),
+                usage: ":delete-row",
+                description: "Delete the selected row, or the rows of the selected range",
+                function: command_delete_row,
+            },
+            Command {
+                prefix: Prefix::Long(":append-row"),
+                usage: ":append-row",
+                description: "Append a row of nulls and select it",
+                function: command_append_row,
+            },
+            Command {
+                prefix: Prefix::Long(":undo"),
+                usage: ":undo",
+                description: "Undo the last edit, deletion, appended row, sort or filter",
+                function: command_undo,
+            },
+            Command {
+                prefix: Prefix::Long(":redo"),
+                usage: ":redo",
+                description: "Redo the last undone change",
+                function: command_redo,
+            },
+            Command {
+                prefix: Prefix::Both(":w", ":write"),
+                usage: ":write",
+                description: "Write the edited table back to the file it was read from",
+                function: command_write,
+            },
+            Command {
+                prefix: Prefix::Long(":reload"),
+                usage: ":reload",
+                description: "Read the table again from its file, keeping the selection where it still is",
+                function: command_reload,
+            },
+            Command {
+                prefix: Prefix::Long(":reload!"),
+                usage: ":reload!",
+                description: "Same as :reload, discarding the edits that are not written",
+                function: command_reload_discard,
+            },
             Command {
                 prefix: Prefix::Long(":reset"),
                 usage: ":reset",
-                description: "Reset the original data frame",
+                description: "Discard the queries, sort, filters and hidden columns, and show the original data frame again",
                 function: command_reset,
             },
+            Command {
+                prefix: Prefix::Long(":history"),
+                usage: ":history",
+                description: "List the recent commands and queries to run one again",
+                function: command_history,
+            },
             Command {
                 prefix: Prefix::Long(":help"),
                 usage: ":help",
@@ -105,11 +254,65 @@ impl Default for CommandList {
                 description: "Query the original data frame ordering by requested columns",
                 function: command_order,
             },
+            Command {
+                prefix: Prefix::Long(":filter-column"),
+                usage: ":filter-column <predicate>",
+                description: "Keep the rows where the selected column matches <predicate>, e.g. '> 100', 'contains foo' or 'is null'",
+                function: command_filter_column,
+            },
+            Command {
+                prefix: Prefix::Long(":clear-filters"),
+                usage: ":clear-filters",
+                description: "Remove the column filters",
+                function: command_clear_filters,
+            },
+            Command {
+                prefix: Prefix::Long(":highlight"),
+                usage: ":highlight <expression> <color>",
+                description: "Color the rows matching an expression, or clear the colors",
+                function: command_highlight,
+            },
+            Command {
+                prefix: Prefix::Long(":column-order"),
+                usage: ":column-order <display|original>",
+                description: "Export, copy and query the columns in the order they are displayed in, after moving them with < and >, or in their original order",
+                function: command_column_order,
+            },
+            Command {
+                prefix: Prefix::Long(":max-col-width"),
+                usage: ":max-col-width <width>",
+                description: "Cut the values of the columns wider than <width> with …, or none with 0",
+                function: command_max_col_width,
+            },
+            Command {
+                prefix: Prefix::Long(":session"),
+                usage: ":session save <path>",
+                description: "Save the files and the tables opened with :as to <path>, to restore them with --session",
+                function: command_session,
+            },
+            Command {
+                prefix: Prefix::Long(":theme"),
+                usage: ":theme [<name>]",
+                description: "Draw with the theme of the given name, or the next one without a name",
+                function: command_theme,
+            },
         ])
     }
 }
 
 impl CommandList {
+    /// Commands listed in the palette, by their long name when they have one.
+    pub fn entries(&self) -> Vec<PaletteEntry> {
+        self.0
+            .iter()
+            .map(|cmd| PaletteEntry {
+                command: cmd.prefix.long().or(cmd.prefix.short()).unwrap_or_default(),
+                usage: cmd.usage,
+                description: cmd.description,
+            })
+            .collect()
+    }
+
     pub fn into_exec(self) -> ExecutionTable {
         self.0
             .into_iter()
@@ -153,17 +356,136 @@ impl CommandList {
 
 pub fn command_query(
     query: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    execute_query(query.to_owned(), tabular, sql)
+}
+
+/// Plans `query` and collects it in the background, its result is shown and the query
+/// remembered for `:save-query` once it is done.
+fn execute_query(
+    query: String,
     tabular: &mut Tabular,
     sql: &mut SQLContext,
+) -> Result<(), Box<dyn Error>> {
+    let frame = sql.execute(&query)?;
+    tabular.start_query(query, frame)
+}
+
+pub fn command_as(
+    name: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected();
+    tabular.require_loaded(":as")?;
+    let data_frame = tabular.data_frame.clone();
+    let derivation = tabular.defining_query();
+    open_table(name, data_frame, derivation, tabs, sql)
+}
+
+/// Registers `data_frame` under `name` to query it, and opens it in a new tab shown
+/// like the selected one. `derivation` is the query it is the result of, for sessions.
+pub fn open_table(
+    name: &str,
+    data_frame: DataFrame,
+    derivation: Option<String>,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+) -> Result<(), Box<dyn Error>> {
+    let name = name.trim();
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !identifier {
+        return Err(format!("invalid table name '{}'", name).into());
+    }
+    if sql.get_tables().iter().any(|table| table == name) {
+        return Err(format!("table '{}' already exists", name).into());
+    }
+    let tabular = tabs.selected();
+    sql.register(name, data_frame.clone().lazy());
+    let mut new_tabular = Tabular::new(name.to_owned(), name.to_owned(), data_frame);
+    new_tabular.derivation = derivation;
+    new_tabular.show_types = tabular.show_types;
+    new_tabular.highlight_nulls = tabular.highlight_nulls;
+    new_tabular.line_numbers = tabular.line_numbers;
+    new_tabular.stripes = tabular.stripes;
+    new_tabular.max_width = tabular.max_width;
+    new_tabular.first_line_number = tabular.first_line_number;
+    new_tabular.page_size = tabular.page_size;
+    new_tabular.set_number_format(tabular.number_format);
+    new_tabular.set_symbols(tabular.symbols);
+    new_tabular.column_widths = tabular.column_widths.clone();
+    new_tabular.fit_content = tabular.fit_content;
+    tabs.push(new_tabular);
+    Ok(())
+}
+
+pub fn command_save_query(
+    name: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err("invalid query name, use a single word".into());
+    }
+    let query = tabular.last_query.as_ref().ok_or("no query executed yet")?;
+    let mut queries = SavedQueries::load()?;
+    queries.insert(name, query.as_str());
+    queries.save()?;
+    status_bar.info(format!("saved query '{}'", name));
+    Ok(())
+}
+
+pub fn command_run_query(
+    name: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    let queries = SavedQueries::load()?;
+    let query = queries
+        .get(name.trim())
+        .ok_or_else(|| format!("no saved query '{}'", name.trim()))?;
+    execute_query(query.to_owned(), tabular, sql)
+}
+
+pub fn command_queries(
+    _: &str,
+    _: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    tabular.set_data_frame(sql.execute(query).and_then(LazyFrame::collect)?);
+    let queries = SavedQueries::load()?;
+    let names = queries.names().collect::<Vec<_>>();
+    if names.is_empty() {
+        return Err("no saved queries, use :save-query <name> to save one".into());
+    }
+    status_bar.info(format!("saved queries: {}", names.join(", ")));
     Ok(())
 }
+
 pub fn command_quit(
     _: &str,
-    _: &mut Tabular,
+    _: &mut Tabs,
     _: &mut SQLContext,
+    _: &mut StatusBar,
     running: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
     *running = false;
@@ -171,92 +493,620 @@ pub fn command_quit(
 }
 pub fn command_goto(
     idx: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    match idx.trim() {
+        "$" => tabular.select_last(),
+        idx => match idx.parse::<i64>() {
+            Ok(idx) if idx < 0 => tabular.select_last(),
+            Ok(idx) => tabular.select((idx as usize).saturating_sub(1)),
+            // too large for an i64, past the last line anyway
+            Err(_) if !idx.is_empty() && idx.bytes().all(|b| b.is_ascii_digit()) => {
+                tabular.select_last()
+            }
+            Err(_) => return Err(format!("invalid line index '{}'", idx).into()),
+        },
+    }
+    Ok(())
+}
+pub fn command_col(
+    name: &str,
+    tabs: &mut Tabs,
     _: &mut SQLContext,
+    status_bar: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    let idx: usize = idx.parse()?;
-    tabular.select(idx.saturating_sub(1));
+    // completed names that are not identifiers are quoted
+    let name = name.trim();
+    let name = name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+        .unwrap_or(name);
+    if name.is_empty() {
+        return Err("missing column name".into());
+    }
+    let tabular = tabs.selected_mut();
+    let (position, count) = tabular.select_column_named(name)?;
+    if count > 1 {
+        status_bar.info(format!(
+            "{} of {} columns starting with '{}'",
+            position, count, name
+        ));
+    }
     Ok(())
 }
+
 pub fn command_select_up(
     lines: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     _: &mut SQLContext,
+    _: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
     tabular.select_up(lines.parse()?);
     Ok(())
 }
 pub fn command_select_down(
     lines: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     _: &mut SQLContext,
+    _: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
     tabular.select_down(lines.parse()?);
     Ok(())
 }
 
 pub fn command_reset(
     _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.reset();
+    // a query may have replaced the table, e.g. with CREATE TABLE
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_copy_markdown(
+    _: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected();
+    tabular.require_loaded("copying the table")?;
+    let data_frame = tabular.selected_data_frame();
+    clipboard::copy(&markdown_table(&data_frame, tabular.number_format))?;
+    status_bar.info(format!(
+        "copied {} rows to the clipboard as Markdown",
+        data_frame.height()
+    ));
+    Ok(())
+}
+
+pub fn command_edit(
+    value: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.edit_selected(value)?;
+    // later queries see the edit
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_cast(
+    args: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let args = args.trim();
+    let (args, strict) = match args.strip_suffix("--strict") {
+        Some(args) => (args.trim_end(), true),
+        None => (args, false),
+    };
+    let (column, dtype) = args
+        .rsplit_once(' ')
+        .ok_or("usage: :cast <column> <dtype>")?;
+    let column = column.trim();
+    let dtype = parse_dtype(dtype).ok_or_else(|| format!("unknown data type '{}'", dtype))?;
+    let tabular = tabs.selected_mut();
+    let failed = tabular.cast_column(column, &dtype, strict)?;
+    // later queries see the new type
+    tabular.register(sql);
+    status_bar.info(match failed {
+        0 => format!("cast {} to {}", column, dtype),
+        1 => format!("cast {} to {}, 1 value became null", column, dtype),
+        failed => format!(
+            "cast {} to {}, {} values became null",
+            column, dtype, failed
+        ),
+    });
+    Ok(())
+}
+
+pub fn command_delete_row(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.delete_selected_rows()?;
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_append_row(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.append_row()?;
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_undo(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.undo()?;
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_redo(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.redo()?;
+    tabular.register(sql);
+    Ok(())
+}
+
+pub fn command_write(
+    _: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.require_loaded("writing")?;
+    if !tabular.projection.is_empty() {
+        return Err("the table holds some of the columns of the file, use :export".into());
+    }
+    let mut data_frame = tabular.source().clone();
+    let file = tabular
+        .file
+        .as_mut()
+        .ok_or("the table was not read from a file that can be written, use :export")?;
+    // the text of the values is not kept, only what they were read as
+    if matches!(file.format, FileFormat::Csv) && !file.warned {
+        file.warned = true;
+        return Err(
+            "the values are written as read, e.g. 007 as 7, :write again to write them".into(),
+        );
+    }
+    file.write(&mut data_frame)?;
+    tabular.modified = false;
+    status_bar.info(format!(
+        "wrote {} rows to {}",
+        data_frame.height(),
+        file.path.display()
+    ));
+    Ok(())
+}
+
+pub fn command_reload(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    if tabular.modified {
+        return Err("the table has edits that are not written, :reload! discards them".into());
+    }
+    reload(tabular, sql, status_bar)
+}
+
+pub fn command_reload_discard(
+    _: &str,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    reload(tabs.selected_mut(), sql, status_bar)
+}
+
+/// Reads the table again from its source, which the queries then see. The current data
+/// stays if it cannot be read.
+fn reload(
     tabular: &mut Tabular,
     sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+) -> Result<(), Box<dyn Error>> {
+    let origin = tabular
+        .origin
+        .clone()
+        .ok_or("the table was not read from a file")?;
+    let table = origin.read()?;
+    tabular.reload(table)?;
+    // followed from the end of what was read
+    if let (Some(follow), Some(file)) = (&tabular.follow, &tabular.file) {
+        let paused = follow.paused;
+        let schema = tabular.source().schema();
+        let mut follow = Follow::new(file, schema, origin.args.null_values.clone())?;
+        follow.paused = paused;
+        tabular.follow = Some(follow);
+    }
+    tabular.register(sql);
+    let rows = tabular.source().height();
+    let mut message = format!("read {} rows from {}", rows, origin.source);
+    if tabular.queried() {
+        message.push_str(", run the query again to see them");
+    }
+    status_bar.info(message);
+    Ok(())
+}
+
+pub fn command_export(
+    path: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    if Path::new(path.trim()).exists() {
+        return Err("file already exists, use :export! to overwrite it".into());
+    }
+    export(path, tabular, status_bar)
+}
+
+pub fn command_export_overwrite(
+    path: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    tabular.set_data_frame(
-        sql.execute("SELECT * FROM df")
-            .and_then(LazyFrame::collect)?,
-    );
-    tabular.select(0);
+    let tabular = tabs.selected_mut();
+    export(path, tabular, status_bar)
+}
+
+fn export(path: &str, tabular: &Tabular, status_bar: &mut StatusBar) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err("missing file path".into());
+    }
+    tabular.require_loaded("exporting")?;
+    let mut data_frame = tabular.selected_data_frame();
+    write_data_frame(path, &mut data_frame)?;
+    status_bar.info(format!(
+        "exported {} rows to {}",
+        data_frame.height(),
+        path.display()
+    ));
+    Ok(())
+}
+
+/// Writes `data_frame` to `path` in the format of its extension.
+fn write_data_frame(path: &Path, data_frame: &mut DataFrame) -> Result<(), Box<dyn Error>> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    write_data_frame_with(|| File::create(path), &extension, data_frame)
+}
+
+/// Writes `data_frame` in the format of the file `extension` to the writer `open`
+/// gives, which is only opened if the format is known.
+pub fn write_data_frame_with<W: Write>(
+    open: impl FnOnce() -> io::Result<W>,
+    extension: &str,
+    data_frame: &mut DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    match extension {
+        "csv" => CsvWriter::new(open()?).finish(data_frame)?,
+        "tsv" => CsvWriter::new(open()?)
+            .with_separator(b'\t')
+            .finish(data_frame)?,
+        "json" => JsonWriter::new(open()?)
+            .with_json_format(JsonFormat::Json)
+            .finish(data_frame)?,
+        "ndjson" | "jsonl" => JsonWriter::new(open()?)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(data_frame)?,
+        "parquet" => {
+            ParquetWriter::new(open()?).finish(data_frame)?;
+        }
+        "arrow" | "ipc" | "feather" => IpcWriter::new(open()?).finish(data_frame)?,
+        _ => {
+            return Err(
+                "unsupported file extension, use csv, tsv, json, ndjson, parquet, or arrow".into(),
+            )
+        }
+    }
+    Ok(())
+}
+
+pub fn command_sql_export(
+    args: &str,
+    _: &mut Tabs,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let (path, _) = args
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or_default();
+    if Path::new(path).exists() {
+        return Err("file already exists, use :sql-export! to overwrite it".into());
+    }
+    sql_export(args, sql, status_bar)
+}
+
+pub fn command_sql_export_overwrite(
+    args: &str,
+    _: &mut Tabs,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    sql_export(args, sql, status_bar)
+}
+
+/// Runs the query of `args`, after the path, and writes its result to the path without
+/// showing it.
+fn sql_export(
+    args: &str,
+    sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
+) -> Result<(), Box<dyn Error>> {
+    let Some((path, query)) = args.trim().split_once(char::is_whitespace) else {
+        return Err("expected a file path and a query".into());
+    };
+    let path = Path::new(path);
+    let mut data_frame = sql.execute(query.trim())?.collect()?;
+    write_data_frame(path, &mut data_frame)?;
+    status_bar.info(format!(
+        "exported {} rows to {}",
+        data_frame.height(),
+        path.display()
+    ));
+    Ok(())
+}
+
+pub fn command_history(
+    _: &str,
+    _: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    if status_bar.history().entries().is_empty() {
+        return Err("history is empty".into());
+    }
+    status_bar.show_history();
     Ok(())
 }
 
 pub fn command_help(
     _: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     _: &mut SQLContext,
+    _: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
     tabular.set_data_frame(CommandList::default().into_data_frame());
     Ok(())
 }
 
 pub fn command_select(
     query: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     sql: &mut SQLContext,
+    _: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    tabular.set_data_frame(
-        sql.execute(format!("SELECT {} FROM df", query).as_str())
-            .and_then(LazyFrame::collect)?,
-    );
-    Ok(())
+    let tabular = tabs.selected_mut();
+    let query = format!("SELECT {} FROM {}", query, tabular.table_name);
+    execute_query(query, tabular, sql)
 }
 
 pub fn command_filter(
     query: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     sql: &mut SQLContext,
+    _: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    tabular.set_data_frame(
-        sql.execute(format!("SELECT * FROM df WHERE {}", query).as_str())
-            .and_then(LazyFrame::collect)?,
-    );
-    Ok(())
+    let tabular = tabs.selected_mut();
+    let query = format!("SELECT * FROM {} WHERE {}", tabular.table_name, query);
+    execute_query(query, tabular, sql)
 }
 
 pub fn command_order(
     query: &str,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
+    sql: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    let query = format!("SELECT * FROM {} ORDER BY {}", tabular.table_name, query);
+    execute_query(query, tabular, sql)
+}
+pub fn command_filter_column(
+    predicate: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.filter_selected(predicate)
+}
+
+pub fn command_clear_filters(
+    _: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    _: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    tabular.clear_filters();
+    Ok(())
+}
+
+pub fn command_highlight(
+    rule: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let tabular = tabs.selected_mut();
+    let rule = rule.trim();
+    if rule == "clear" {
+        tabular.highlights.clear();
+        return Ok(());
+    }
+    let (expr, color) = rule
+        .rsplit_once(char::is_whitespace)
+        .ok_or("expected an expression and a color")?;
+    let color = Color::from_str(color).map_err(|_| format!("invalid color '{}'", color))?;
+    tabular.highlight(expr.trim(), color)?;
+    status_bar.info(format!(
+        "{} highlight rule(s), :highlight clear removes them",
+        tabular.highlights.len()
+    ));
+    Ok(())
+}
+
+pub fn command_column_order(
+    order: &str,
+    tabs: &mut Tabs,
     sql: &mut SQLContext,
+    status_bar: &mut StatusBar,
     _: &mut bool,
 ) -> Result<(), Box<dyn Error>> {
-    tabular.set_data_frame(
-        sql.execute(format!("SELECT * FROM df ORDER BY {}", query).as_str())
-            .and_then(LazyFrame::collect)?,
-    );
+    let tabular = tabs.selected_mut();
+    tabular.keep_column_order = match order.trim() {
+        "display" => false,
+        "original" => true,
+        _ => return Err("expected display or original".into()),
+    };
+    tabular.register(sql);
+    status_bar.info(format!(
+        "exports and queries use the {} column order",
+        order.trim()
+    ));
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn command_max_col_width(
+    width: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let width = width
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("expected a width, found '{}'", width.trim()))?;
+    tabs.selected_mut().max_width = width;
+    match width {
+        0 => status_bar.info("the columns are as wide as their values"),
+        width => status_bar.info(format!("the columns are at most {} wide", width)),
+    }
+    Ok(())
+}
+
+pub fn command_theme(
+    name: &str,
+    _: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let themes = themes();
+    status_bar.theme = match name.trim() {
+        "" => {
+            let idx = themes.iter().position(|theme| theme == &status_bar.theme);
+            themes[idx.map_or(0, |idx| (idx + 1) % themes.len())].clone()
+        }
+        "auto" => return Err("the auto theme is only picked on launch".into()),
+        name => match themes.iter().find(|theme| theme_name(theme) == name) {
+            Some(theme) => theme.clone(),
+            None => {
+                let names = themes.iter().map(theme_name).collect::<Vec<_>>().join(", ");
+                return Err(format!("unknown theme '{}', expected {}", name, names).into());
+            }
+        },
+    };
+    status_bar.info(format!("theme: {}", theme_name(&status_bar.theme)));
+    Ok(())
+}
+
+pub fn command_session(
+    args: &str,
+    tabs: &mut Tabs,
+    _: &mut SQLContext,
+    status_bar: &mut StatusBar,
+    _: &mut bool,
+) -> Result<(), Box<dyn Error>> {
+    let Some(("save", path)) = args.trim().split_once(char::is_whitespace) else {
+        return Err("expected save <path>".into());
+    };
+    let path = Path::new(path.trim());
+    let (session, skipped) = Session::from_tabs(tabs);
+    session.save(path)?;
+    match skipped.is_empty() {
+        true => status_bar.info(format!("saved the session to {}", path.display())),
+        false => status_bar.info(format!(
+            "saved the session to {}, without {} which cannot be read again",
+            path.display(),
+            skipped.join(", ")
+        )),
+    }
+    Ok(())
+}
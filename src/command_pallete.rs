@@ -62,6 +62,18 @@ impl CommandPalleteState {
         self.chars[self.cursor.0].iter().collect()
     }
 
+    pub fn before_cursor(&self) -> String {
+        self.chars[self.cursor.0][..self.cursor.1].iter().collect()
+    }
+
+    /// Replaces the characters from `start` to the cursor with `text`.
+    pub fn replace_before_cursor(&mut self, start: usize, text: &str) -> &mut Self {
+        let start = start.min(self.cursor.1);
+        self.chars[self.cursor.0].splice(start..self.cursor.1, text.chars());
+        self.cursor.1 = start + text.chars().count();
+        self
+    }
+
     pub fn command_len(&self) -> usize {
         self.chars[self.cursor.0].len()
     }
@@ -144,4 +156,17 @@ mod tests {
         state.input_char('r');
         assert_eq!(state.command(), "char")
     }
+
+    #[test]
+    fn replace_before_cursor_test() {
+        let mut state = CommandPalleteState::from(vec![":Q SELECT pr FROM df".to_owned()]);
+        state.move_bol();
+        (0..12).for_each(|_| {
+            state.move_right();
+        });
+        assert_eq!(state.before_cursor(), ":Q SELECT pr");
+        state.replace_before_cursor(10, "price");
+        assert_eq!(state.command(), ":Q SELECT price FROM df");
+        assert_eq!(state.cursor(), (0, 15));
+    }
 }
@@ -0,0 +1,157 @@
+use itertools::Itertools;
+
+/// Keywords followed by a table name.
+const TABLE_KEYWORDS: [&str; 4] = ["FROM", "JOIN", "INTO", "TABLE"];
+
+/// Keywords followed by a column name.
+const COLUMN_KEYWORDS: [&str; 9] = [
+    "SELECT", "WHERE", "BY", "AND", "OR", "ON", "HAVING", "DISTINCT", "NOT",
+];
+
+/// Commands taking column names rather than a full query.
+const COLUMN_COMMANDS: [&str; 7] = [":S", ":select", ":F", ":filter", ":O", ":order", ":col"];
+
+/// Completion candidates for the token under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Char index where the completed token starts.
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// Longest prefix shared by all the candidates.
+    pub fn common_prefix(&self) -> String {
+        let Some((first, rest)) = self.candidates.split_first() else {
+            return String::new();
+        };
+        rest.iter().fold(first.clone(), |prefix, candidate| {
+            prefix
+                .chars()
+                .zip(candidate.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        })
+    }
+}
+
+/// Completes the last token of `input`, the prompt text before the cursor. The first
+/// token completes to a command, the token after `FROM` or `JOIN` to a table, and the
+/// token after `SELECT`, `WHERE` and alike, or in `:select`, `:filter` and `:order`, to
+/// a column. Elsewhere columns and tables are both suggested. Matching is a
+/// case-insensitive prefix match and column names that are not plain identifiers
+/// are quoted.
+pub fn complete<'a>(
+    input: &str,
+    commands: impl IntoIterator<Item = &'a str>,
+    tables: impl IntoIterator<Item = &'a str>,
+    columns: impl IntoIterator<Item = &'a str>,
+) -> Completion {
+    let chars = input.chars().collect_vec();
+    let start = chars
+        .iter()
+        .rposition(|c| c.is_whitespace() || matches!(c, ',' | '(' | ')' | '='))
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let token = chars[start..].iter().collect::<String>();
+    let before = chars[..start].iter().collect::<String>();
+
+    let candidates = if before.trim().is_empty() {
+        commands.into_iter().map(str::to_owned).collect_vec()
+    } else {
+        let command = before.split_whitespace().next().unwrap_or_default();
+        let keyword = before
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .rev()
+            .map(str::to_uppercase)
+            .find(|word| {
+                TABLE_KEYWORDS.contains(&word.as_str()) || COLUMN_KEYWORDS.contains(&word.as_str())
+            });
+        let tables = tables.into_iter().map(quote);
+        let columns = columns.into_iter().map(quote);
+        match keyword {
+            Some(keyword) if TABLE_KEYWORDS.contains(&keyword.as_str()) => tables.collect(),
+            Some(_) => columns.collect(),
+            None if COLUMN_COMMANDS.contains(&command) => columns.collect(),
+            None => columns.chain(tables).collect(),
+        }
+    };
+
+    let pattern = token.trim_start_matches('"').to_lowercase();
+    Completion {
+        start,
+        candidates: candidates
+            .into_iter()
+            .filter(|candidate| {
+                candidate
+                    .trim_start_matches('"')
+                    .to_lowercase()
+                    .starts_with(&pattern)
+            })
+            .sorted()
+            .dedup()
+            .collect(),
+    }
+}
+
+fn quote(name: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if identifier {
+        name.to_owned()
+    } else {
+        format!("\"{}\"", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMANDS: [&str; 4] = [":Q", ":query", ":quit", ":goto"];
+    const TABLES: [&str; 2] = ["df", "sales"];
+    const COLUMNS: [&str; 3] = ["price", "product id", "sale_date"];
+
+    fn candidates(input: &str) -> Vec<String> {
+        complete(input, COMMANDS, TABLES, COLUMNS).candidates
+    }
+
+    #[test]
+    fn test_complete_commands() {
+        assert_eq!(candidates(":qu"), [":query", ":quit"]);
+        assert_eq!(candidates(":g"), [":goto"]);
+    }
+
+    #[test]
+    fn test_complete_by_keyword() {
+        assert_eq!(candidates(":Q SELECT p"), ["\"product id\"", "price"]);
+        assert_eq!(candidates(":Q SELECT price FROM s"), ["sales"]);
+        assert_eq!(candidates(":Q select * from df where PRI"), ["price"]);
+        assert_eq!(
+            candidates(":Q select * from df where \"pro"),
+            ["\"product id\""]
+        );
+        assert_eq!(candidates(":F s"), ["sale_date"]);
+        assert_eq!(candidates(":col p"), ["\"product id\"", "price"]);
+        assert_eq!(candidates(":goto s"), ["sale_date", "sales"]);
+    }
+
+    #[test]
+    fn test_complete_token_start() {
+        let completion = complete(":Q SELECT price,sa", COMMANDS, TABLES, COLUMNS);
+        assert_eq!(completion.start, 16);
+        assert_eq!(completion.candidates, ["sale_date"]);
+        assert_eq!(
+            complete(":qu", COMMANDS, TABLES, COLUMNS).common_prefix(),
+            ":qu"
+        );
+        assert_eq!(
+            complete(":Q select * from df where s", COMMANDS, TABLES, COLUMNS).common_prefix(),
+            "sale_date"
+        );
+    }
+}
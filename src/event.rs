@@ -1,6 +1,6 @@
 use crate::app::AppResult;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
-use std::sync::mpsc;
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -27,6 +27,10 @@ pub struct EventHandler {
     receiver: mpsc::Receiver<Event>,
     /// Event handler thread.
     handler: thread::JoinHandle<()>,
+    /// Time between two ticks.
+    tick_rate: Duration,
+    /// Time of the last tick.
+    last_tick: Instant,
 }
 
 impl EventHandler {
@@ -36,44 +40,45 @@ impl EventHandler {
         let (sender, receiver) = mpsc::channel();
         let handler = {
             let sender = sender.clone();
-            thread::spawn(move || {
-                let mut last_tick = Instant::now();
-                loop {
-                    let timeout = tick_rate
-                        .checked_sub(last_tick.elapsed())
-                        .unwrap_or(tick_rate);
-
-                    if event::poll(timeout).expect("failed to poll new events") {
-                        match event::read().expect("unable to read event") {
-                            CrosstermEvent::Key(e) => sender.send(Event::Key(e)),
-                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
-                            CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            CrosstermEvent::FocusGained => Ok(()),
-                            CrosstermEvent::FocusLost => Ok(()),
-                            CrosstermEvent::Paste(_) => unimplemented!(),
-                        }
-                        .expect("failed to send terminal event")
-                    }
-
-                    if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
-                        last_tick = Instant::now();
-                    }
+            // Ticks are made by the receiving side, so the thread sleeps until input
+            thread::spawn(move || loop {
+                match event::read().expect("unable to read event") {
+                    CrosstermEvent::Key(e) => sender.send(Event::Key(e)),
+                    CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
+                    CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
+                    CrosstermEvent::FocusGained => Ok(()),
+                    CrosstermEvent::FocusLost => Ok(()),
+                    CrosstermEvent::Paste(_) => unimplemented!(),
                 }
+                .expect("failed to send terminal event")
             })
         };
         Self {
             sender,
             receiver,
             handler,
+            tick_rate,
+            last_tick: Instant::now(),
         }
     }
 
     /// Receive the next event from the handler thread.
     ///
-    /// This function will always block the current thread if
-    /// there is no data available and it's possible for more data to be sent.
-    pub fn next(&self) -> AppResult<Event> {
-        Ok(self.receiver.recv()?)
+    /// With `ticking`, a [`Event::Tick`] is returned every tick rate when no other event
+    /// comes first. Otherwise, this function blocks the current thread until there is
+    /// an event, so an idle app does not wake up at all.
+    pub fn next(&mut self, ticking: bool) -> AppResult<Event> {
+        if !ticking {
+            return Ok(self.receiver.recv()?);
+        }
+        let timeout = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Ok(event),
+            Err(RecvTimeoutError::Timeout) => {
+                self.last_tick = Instant::now();
+                Ok(Event::Tick)
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 }
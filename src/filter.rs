@@ -0,0 +1,176 @@
+use polars::prelude::*;
+
+/// Quick filter on a single column, e.g. `> 100` or `contains foo` on `price`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnFilter {
+    pub column: String,
+    pub predicate: String,
+}
+
+impl ColumnFilter {
+    /// Checks `predicate` against the type of `column` before building the filter.
+    pub fn new(column: &str, dtype: &DataType, predicate: &str) -> Result<Self, String> {
+        let filter = Self {
+            column: column.to_owned(),
+            predicate: predicate.trim().to_owned(),
+        };
+        filter.expr(dtype).map(|_| filter)
+    }
+
+    /// Polars expression of the filter. Supported predicates are the comparisons `=`,
+    /// `!=`, `<`, `<=`, `>`, `>=` followed by a value, `contains`, `starts with` and
+    /// `ends with` followed by a text, `is null` and `is not null`. Values may be
+    /// quoted with `'` or `"`.
+    pub fn expr(&self, dtype: &DataType) -> Result<Expr, String> {
+        let column = col(&self.column);
+        let predicate = self.predicate.as_str();
+        let lower = predicate.to_lowercase();
+        let keyword = |keyword: &str| {
+            lower
+                .strip_prefix(keyword)
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+                .map(|_| unquote(&predicate[keyword.len()..]))
+        };
+
+        match lower.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["is", "null"] => return Ok(column.is_null()),
+            ["is", "not", "null"] => return Ok(column.is_not_null()),
+            _ => (),
+        }
+        let text = column.clone().cast(DataType::String).str();
+        if let Some(value) = keyword("contains") {
+            return Ok(text.contains_literal(lit(value)));
+        }
+        if let Some(value) = keyword("starts with") {
+            return Ok(text.starts_with(lit(value)));
+        }
+        if let Some(value) = keyword("ends with") {
+            return Ok(text.ends_with(lit(value)));
+        }
+
+        let operator_len = predicate
+            .find(|c| !matches!(c, '=' | '!' | '<' | '>'))
+            .unwrap_or(predicate.len());
+        let (operator, value) = predicate.split_at(operator_len);
+        let value = unquote(value);
+        if value.is_empty() {
+            return Err(format!("invalid filter '{}'", predicate));
+        }
+        let value = literal(value, dtype)?;
+        Ok(match operator {
+            "=" | "==" => column.eq(value),
+            "!=" | "<>" => column.neq(value),
+            "<" => column.lt(value),
+            "<=" => column.lt_eq(value),
+            ">" => column.gt(value),
+            ">=" => column.gt_eq(value),
+            _ => return Err(format!("invalid filter '{}'", predicate)),
+        })
+    }
+}
+
+impl std::fmt::Display for ColumnFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.column, self.predicate)
+    }
+}
+
+/// Combines the filters with AND, `None` if there is none.
+pub fn combine(filters: &[ColumnFilter], schema: &Schema) -> Result<Option<Expr>, String> {
+    filters
+        .iter()
+        .try_fold(None, |combined: Option<Expr>, filter| {
+            let dtype = schema
+                .get(&filter.column)
+                .ok_or_else(|| format!("column '{}' not found", filter.column))?;
+            let expr = filter.expr(dtype)?;
+            Ok(Some(match combined {
+                Some(combined) => combined.and(expr),
+                None => expr,
+            }))
+        })
+}
+
+/// Literal of `value` with the type of the column it is compared to.
+fn literal(value: &str, dtype: &DataType) -> Result<Expr, String> {
+    match dtype {
+        DataType::String => Ok(lit(value)),
+        DataType::Boolean => value
+            .to_lowercase()
+            .parse::<bool>()
+            .map(lit)
+            .map_err(|_| format!("expected true or false, found '{}'", value)),
+        dtype if dtype.is_numeric() => value
+            .parse::<f64>()
+            .map(lit)
+            .map_err(|_| format!("expected a number, found '{}'", value)),
+        dtype => Ok(lit(value).cast(dtype.clone())),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtered(filters: &[(&str, &str)]) -> Result<Vec<i32>, String> {
+        let df = df!(
+            "id" => [1, 2, 3, 4],
+            "name" => [Some("apple"), Some("banana"), None, Some("cherry pie")],
+            "price" => [Some(1.5), Some(120.0), Some(99.0), None],
+        )
+        .unwrap();
+        let filters = filters
+            .iter()
+            .map(|(column, predicate)| {
+                ColumnFilter::new(column, df.column(column).unwrap().dtype(), predicate)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let expr = combine(&filters, &df.schema())?.unwrap();
+        let df = df.lazy().filter(expr).collect().unwrap();
+        Ok(df
+            .column("id")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect())
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(filtered(&[("price", "> 100")]), Ok(vec![2]));
+        assert_eq!(filtered(&[("price", "<=99")]), Ok(vec![1, 3]));
+        assert_eq!(filtered(&[("id", "!= 2")]), Ok(vec![1, 3, 4]));
+        assert_eq!(filtered(&[("name", "= 'banana'")]), Ok(vec![2]));
+        assert!(filtered(&[("price", "> cheap")]).is_err());
+        assert!(filtered(&[("price", "~ 1")]).is_err());
+        assert!(filtered(&[("price", ">")]).is_err());
+    }
+
+    #[test]
+    fn test_text_and_null_predicates() {
+        assert_eq!(filtered(&[("name", "contains an")]), Ok(vec![2]));
+        assert_eq!(filtered(&[("name", "Starts With ch")]), Ok(vec![4]));
+        assert_eq!(filtered(&[("name", "ends with \"pie\"")]), Ok(vec![4]));
+        assert_eq!(filtered(&[("price", "contains 9")]), Ok(vec![3]));
+        assert_eq!(filtered(&[("name", "is null")]), Ok(vec![3]));
+        assert_eq!(filtered(&[("price", "IS NOT NULL")]), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_combined_filters() {
+        assert_eq!(
+            filtered(&[("price", "is not null"), ("name", "is not null")]),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(filtered(&[("id", ">= 2"), ("id", "< 4")]), Ok(vec![2, 3]));
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use polars::{
+    frame::DataFrame,
+    io::{
+        csv::read::{CommentPrefix, CsvParseOptions, CsvReadOptions, NullValues},
+        SerReader,
+    },
+    prelude::{DataType, Field, Schema, SchemaRef},
+};
+
+use crate::{
+    app::AppResult,
+    reader::{rename_duplicates, SourceFile},
+};
+
+/// Rows read from a followed file since the last poll.
+#[derive(Debug)]
+pub enum Growth {
+    /// Rows appended after the ones read before.
+    Appended(DataFrame),
+    /// All the rows of the file, which was truncated or replaced, as by a log rotation.
+    Reloaded(DataFrame),
+}
+
+/// CSV file read again as it grows with `--follow`, from the end of the last complete
+/// line read.
+#[derive(Debug)]
+pub struct Follow {
+    path: PathBuf,
+    separator: u8,
+    quote_char: u8,
+    comment_char: Option<u8>,
+    null_values: Vec<String>,
+    /// Schema of all the columns of the file, those loaded having their type and the
+    /// others, left out with `--columns`, read as strings.
+    schema: SchemaRef,
+    /// Bytes of the file read, up to the end of a line.
+    offset: u64,
+    /// Device and inode of the file on Unix, which change when it is replaced.
+    identity: Option<(u64, u64)>,
+    /// Whether the header of the file is still to be read, after it was replaced.
+    header_pending: bool,
+    has_header: bool,
+    /// Whether the rows appended are left for later, to look at the rows read.
+    pub paused: bool,
+}
+
+impl Follow {
+    /// Follows `file` from its current end, the rows before having been loaded with
+    /// `schema`, which the new rows are read with too. The new rows have all the columns
+    /// of the file, even those the rows before were loaded without.
+    pub fn new(file: &SourceFile, schema: Schema, null_values: Vec<String>) -> AppResult<Self> {
+        let metadata = fs::metadata(&file.path)?;
+        let mut follow = Self {
+            path: file.path.clone(),
+            separator: file.separator,
+            quote_char: file.quote_char,
+            comment_char: file.comment_char,
+            null_values,
+            schema: Arc::new(schema),
+            offset: metadata.len(),
+            identity: identity(&metadata),
+            header_pending: false,
+            has_header: file.has_header,
+            paused: false,
+        };
+        let mut start = Vec::new();
+        let mut reader = BufReader::new(File::open(&file.path)?);
+        // the header is the first line that is not a comment
+        while reader.read_until(b'\n', &mut start)? > 0 && follow.is_comment(&start) {
+            start.clear();
+        }
+        follow.read_header(&start)?;
+        Ok(follow)
+    }
+
+    fn is_comment(&self, line: &[u8]) -> bool {
+        self.comment_char.is_some() && line.first() == self.comment_char.as_ref()
+    }
+
+    /// Takes the columns of the file from the first line of `content` that is not a
+    /// comment, keeping the types of the columns known.
+    fn read_header(&mut self, content: &[u8]) -> AppResult<()> {
+        let Some(line) = content
+            .split(|byte| *byte == b'\n')
+            .find(|line| !self.is_comment(line) && !line.is_empty())
+        else {
+            return Ok(());
+        };
+        let header = CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(Some(0))
+            .with_parse_options(
+                CsvParseOptions::default()
+                    .with_separator(self.separator)
+                    .with_quote_char(Some(self.quote_char)),
+            )
+            .into_reader_with_file_handle(Cursor::new(line.to_vec()))
+            .finish()?;
+        let names = header.get_column_names();
+        let mut names = match self.has_header {
+            true => names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+            false => (1..=names.len()).map(|i| format!("column_{}", i)).collect(),
+        };
+        // named as in the table
+        rename_duplicates(&mut names);
+        self.schema = Arc::new(Schema::from_iter(names.into_iter().map(|name| {
+            let dtype = self.schema.get(&name).cloned().unwrap_or(DataType::String);
+            Field::new(&name, dtype)
+        })));
+        Ok(())
+    }
+
+    /// Reads the lines completed since the last poll, `None` if there are none. A last
+    /// line still being written is left for a later poll, and a file missing for a
+    /// moment while it is rotated is waited for.
+    pub fn poll(&mut self) -> AppResult<Option<Growth>> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let replaced = identity(&metadata) != self.identity || metadata.len() < self.offset;
+        if replaced {
+            self.identity = identity(&metadata);
+            self.offset = 0;
+            self.header_pending = self.has_header;
+        } else if metadata.len() == self.offset {
+            return Ok(None);
+        }
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut content = Vec::new();
+        file.take(metadata.len() - self.offset)
+            .read_to_end(&mut content)?;
+        let end = content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |pos| pos + 1);
+        content.truncate(end);
+        self.offset += end as u64;
+        let rows = self.parse(content)?;
+        Ok(match replaced {
+            true => Some(Growth::Reloaded(rows)),
+            false if rows.is_empty() => None,
+            false => Some(Growth::Appended(rows)),
+        })
+    }
+
+    fn parse(&mut self, content: Vec<u8>) -> AppResult<DataFrame> {
+        if content.is_empty() {
+            return Ok(DataFrame::empty_with_schema(&self.schema));
+        }
+        let has_header = std::mem::take(&mut self.header_pending);
+        if has_header {
+            // a file replaced may have other columns
+            self.read_header(&content)?;
+        }
+        Ok(CsvReadOptions::default()
+            .with_has_header(has_header)
+            .with_schema(Some(self.schema.clone()))
+            .with_parse_options(
+                CsvParseOptions::default()
+                    .with_separator(self.separator)
+                    .with_quote_char(Some(self.quote_char))
+                    .with_comment_prefix(self.comment_char.map(CommentPrefix::Single))
+                    .with_null_values(
+                        (!self.null_values.is_empty())
+                            .then(|| NullValues::AllColumns(self.null_values.clone())),
+                    ),
+            )
+            .into_reader_with_file_handle(Cursor::new(content))
+            .finish()?)
+    }
+}
+
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::args::FileFormat;
+
+    #[test]
+    fn test_poll() {
+        let path = std::env::temp_dir().join(format!("tabiew-follow-{}.csv", std::process::id()));
+        fs::write(&path, "id,name\n1,a\n").unwrap();
+        let file = SourceFile {
+            path: path.clone(),
+            format: FileFormat::Csv,
+            separator: b',',
+            quote_char: b'"',
+            comment_char: None,
+            null_value: None,
+            has_header: true,
+            warned: false,
+        };
+        let schema = Schema::from_iter([
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+        ]);
+        let mut follow = Follow::new(&file, schema, Vec::new()).unwrap();
+        assert!(follow.poll().unwrap().is_none());
+
+        let append = |text: &str| {
+            let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+        // the line is read once it is complete
+        append("2,b\n3,");
+        let Some(Growth::Appended(rows)) = follow.poll().unwrap() else {
+            panic!("expected appended rows");
+        };
+        assert_eq!(rows.column("id").unwrap().i64().unwrap().get(0), Some(2));
+        assert_eq!(rows.height(), 1);
+        assert!(follow.poll().unwrap().is_none());
+        append("c\n");
+        let Some(Growth::Appended(rows)) = follow.poll().unwrap() else {
+            panic!("expected appended rows");
+        };
+        assert_eq!(
+            rows.column("name").unwrap().str().unwrap().get(0),
+            Some("c")
+        );
+
+        // truncated, the file is read again with its header
+        fs::write(&path, "id,name\n9,z\n").unwrap();
+        let Some(Growth::Reloaded(rows)) = follow.poll().unwrap() else {
+            panic!("expected the rows of the file");
+        };
+        assert_eq!(rows.height(), 1);
+        assert_eq!(rows.column("id").unwrap().i64().unwrap().get(0), Some(9));
+        fs::remove_file(&path).unwrap();
+        assert!(follow.poll().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_poll_projected() {
+        let path = std::env::temp_dir().join(format!(
+            "tabiew-follow-projected-{}.csv",
+            std::process::id()
+        ));
+        fs::write(&path, "# comment\nid,name,score\n1,a,5\n").unwrap();
+        let file = SourceFile {
+            path: path.clone(),
+            format: FileFormat::Csv,
+            separator: b',',
+            quote_char: b'"',
+            comment_char: Some(b'#'),
+            null_value: None,
+            has_header: true,
+            warned: false,
+        };
+        // loaded with --columns score
+        let schema = Schema::from_iter([Field::new("score", DataType::Int64)]);
+        let mut follow = Follow::new(&file, schema, Vec::new()).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"2,b,6\n").unwrap();
+        let Some(Growth::Appended(rows)) = follow.poll().unwrap() else {
+            panic!("expected appended rows");
+        };
+        assert_eq!(rows.get_column_names(), ["id", "name", "score"]);
+        assert_eq!(rows.column("score").unwrap().i64().unwrap().get(0), Some(6));
+
+        // replaced with other columns
+        fs::write(&path, "score,note\n7,x\n").unwrap();
+        let Some(Growth::Reloaded(rows)) = follow.poll().unwrap() else {
+            panic!("expected the rows of the file");
+        };
+        assert_eq!(rows.get_column_names(), ["score", "note"]);
+        assert_eq!(rows.column("score").unwrap().i64().unwrap().get(0), Some(7));
+        fs::remove_file(&path).unwrap();
+    }
+}
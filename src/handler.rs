@@ -1,123 +1,556 @@
 use crate::{
-    app::{AppResult, StatusBar, StatusBarState, Tabular},
-    command::ExecutionTable,
+    alias::Aliases,
+    app::{AppResult, Mode, StatusBar, StatusBarState, Tabs},
+    clipboard,
+    command::{CommandList, ExecutionTable},
+    keymap::{Action, Keymap},
+    palette::Palette,
+    utils::{any_value_into_string, tab_separated},
 };
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use polars_sql::SQLContext;
 
-/// Handles the key events and updates the state of [`App`].
+/// Runs a command entered in the prompt, or read from the init script: a `/` search,
+/// or a command of the execution table once aliases are expanded.
+pub fn execute_command(
+    command: &str,
+    tabs: &mut Tabs,
+    status_bar: &mut StatusBar,
+    sql_context: &mut SQLContext,
+    running: &mut bool,
+    exec_tbl: &ExecutionTable,
+    aliases: &Aliases,
+) -> AppResult<()> {
+    let expanded = aliases.expand(command)?;
+    let command = expanded.as_deref().unwrap_or(command);
+    status_bar.normal();
+    if let Some(query) = command.strip_prefix('/') {
+        let tabular = tabs.selected_mut();
+        if query.is_empty() {
+            tabular.search = None;
+        } else if !tabular.search(query, status_bar.search_options)? {
+            return Err(format!("no matches for '{}'", query).into());
+        }
+        Ok(())
+    } else {
+        let (s1, s2) = command.split_once(' ').unwrap_or((command, ""));
+        let func = exec_tbl.get(s1).ok_or("command not found")?;
+        func(s2, tabs, sql_context, status_bar, running)
+    }
+}
+
+/// Handles the key events and updates the state of [`App`], depending on the mode. In
+/// the normal mode keys are looked up in the keymap and their actions performed, in the
+/// command and search modes they edit the prompt.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_key_events(
     key_event: KeyEvent,
-    tabular: &mut Tabular,
+    tabs: &mut Tabs,
     status_bar: &mut StatusBar,
     sql_context: &mut SQLContext,
     running: &mut bool,
     exec_tbl: &ExecutionTable,
+    aliases: &Aliases,
+    keymap: &Keymap,
 ) -> AppResult<()> {
-    match (&status_bar.state, key_event.code) {
-        (_, KeyCode::Esc) => status_bar.normal(),
+    let action = keymap.action(key_event);
+    // any other key cancels an action waiting for its second key
+    let repeated = status_bar
+        .pending_action
+        .take()
+        .is_some_and(|pending| Some(pending) == action);
+    let tabular = tabs.selected_mut();
+    match (status_bar.mode(), key_event.code) {
+        (Mode::Normal, KeyCode::Esc) if tabular.pending.is_some() => {
+            tabular.cancel_query();
+            status_bar.info("query cancelled")
+        }
+        (mode, KeyCode::Esc) => {
+            if mode == Mode::Normal {
+                tabular.range_start = None;
+            }
+            tabular.cell_popup = None;
+            tabular.view_popup = None;
+            tabular.stats = None;
+            tabular.value_counts = None;
+            tabular.histogram = None;
+            tabular.schema = None;
+            status_bar.history_popup = None;
+            status_bar.help = None;
+            status_bar.palette = None;
+            status_bar.normal()
+        }
 
-        (StatusBarState::Command(_), KeyCode::Enter) => {
-            if let Some(command) = status_bar.commit_prompt() {
-                let (s1, s2) = command.split_once(' ').unwrap_or((command.as_str(), ""));
-                if let Some(func) = exec_tbl.get(s1) {
-                    match func(s2, tabular, sql_context, running) {
-                        Ok(_) => status_bar.normal(),
-                        Err(error) => status_bar.error(error),
+        (_, _) if status_bar.palette.is_some() => {
+            let palette = status_bar.palette.as_mut().unwrap();
+            match key_event.code {
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    palette.input(c)
+                }
+                _ if action == Some(Action::Palette) => status_bar.palette = None,
+                KeyCode::Up => palette.up(),
+                KeyCode::Down => palette.down(),
+                KeyCode::Backspace => palette.delete_backward(),
+                KeyCode::Enter => {
+                    let chosen = palette.chosen().cloned();
+                    status_bar.palette = None;
+                    match chosen {
+                        // the arguments are typed in the prompt
+                        Some(entry) if entry.takes_arguments() => {
+                            status_bar.command(format!("{} ", entry.command))
+                        }
+                        Some(entry) => {
+                            if let Err(error) = execute_command(
+                                entry.command,
+                                tabs,
+                                status_bar,
+                                sql_context,
+                                running,
+                                exec_tbl,
+                                aliases,
+                            ) {
+                                status_bar.error(error);
+                            }
+                        }
+                        None => (),
                     }
-                } else {
-                    status_bar.error("command not found");
+                }
+                _ => (),
+            }
+        }
+
+        (Mode::Command | Mode::Search, KeyCode::Enter) => {
+            if let Some(command) = status_bar.commit_prompt() {
+                if let Err(error) = execute_command(
+                    &command,
+                    tabs,
+                    status_bar,
+                    sql_context,
+                    running,
+                    exec_tbl,
+                    aliases,
+                ) {
+                    status_bar.error(error);
                 }
             } else {
                 status_bar.error("invalid state");
             }
         }
 
-        (StatusBarState::Command(_), _) => {
-            // status_bar.command().input(key_event);
-            status_bar.input(key_event)
+        (Mode::Command, KeyCode::Tab) => {
+            let tables = sql_context.get_tables();
+            let columns = tabular.data_frame.get_column_names();
+            let alias_names = aliases.names().collect::<Vec<_>>();
+            status_bar.complete(
+                exec_tbl
+                    .keys()
+                    .copied()
+                    .chain(alias_names.iter().map(String::as_str)),
+                tables.iter().map(String::as_str),
+                columns,
+            )
         }
 
-        (StatusBarState::Normal, KeyCode::Char('q')) => *running = false,
-        (StatusBarState::Normal, KeyCode::Char('v')) => tabular.switch_view(),
-        (StatusBarState::Normal, KeyCode::Up | KeyCode::Char('k')) => {
-            if let Some(scroll) = &mut tabular.detailed_view {
-                scroll.up();
-            } else {
-                tabular.select_up(1);
+        (Mode::Search, KeyCode::Char('i')) if key_event.modifiers == KeyModifiers::ALT => {
+            let options = &mut status_bar.search_options;
+            options.ignore_case = !options.ignore_case;
+        }
+        (Mode::Search, KeyCode::Char('r')) if key_event.modifiers == KeyModifiers::ALT => {
+            let options = &mut status_bar.search_options;
+            options.regex = !options.regex;
+        }
+        (Mode::Search, KeyCode::Char('c')) if key_event.modifiers == KeyModifiers::ALT => {
+            let options = &mut status_bar.search_options;
+            options.column = !options.column;
+        }
+
+        (Mode::Command | Mode::Search, _) => status_bar.input(key_event),
+
+        // Any key dismisses a message
+        (Mode::Normal, _) if !matches!(status_bar.state, StatusBarState::Normal) => {
+            status_bar.normal();
+        }
+        (Mode::Normal, _) if status_bar.help.is_some() => match action {
+            Some(Action::Up) => status_bar.help.as_mut().unwrap().scroll.up(),
+            Some(Action::Down) => status_bar.help.as_mut().unwrap().scroll.down(),
+            Some(Action::Help | Action::Quit) => status_bar.help = None,
+            _ => (),
+        },
+        (Mode::Normal, _) if status_bar.history_popup.is_some() => match action {
+            Some(Action::Up) => status_bar.history_up(),
+            Some(Action::Down) => status_bar.history_down(),
+            Some(Action::CellPopup) => status_bar.select_history(),
+            Some(Action::Quit) => status_bar.history_popup = None,
+            _ => (),
+        },
+        (Mode::Normal, _) if tabular.cell_popup.is_some() => match action {
+            Some(Action::Up) => tabular.cell_popup.as_mut().unwrap().up(),
+            Some(Action::Down) => tabular.cell_popup.as_mut().unwrap().down(),
+            Some(Action::CellPopup | Action::Quit) => tabular.toggle_cell_popup(),
+            _ => (),
+        },
+        (Mode::Normal, _) if tabular.view_popup.is_some() => match action {
+            Some(Action::Up) => tabular.view_popup.as_mut().unwrap().up(),
+            Some(Action::Down) => tabular.view_popup.as_mut().unwrap().down(),
+            Some(Action::CellPopup | Action::ViewQuery | Action::Quit) => {
+                tabular.toggle_view_popup()
+            }
+            _ => (),
+        },
+        (Mode::Normal, _) if tabular.stats.is_some() => {
+            if matches!(
+                action,
+                Some(Action::CellPopup | Action::Stats | Action::Quit)
+            ) {
+                tabular.stats = None;
             }
         }
-        (StatusBarState::Normal, KeyCode::Down | KeyCode::Char('j')) => {
-            if let Some(scroll) = &mut tabular.detailed_view {
-                scroll.down();
-            } else {
-                tabular.select_down(1);
+        (Mode::Normal, _) if tabular.value_counts.is_some() => {
+            if matches!(
+                action,
+                Some(Action::CellPopup | Action::ValueCounts | Action::Quit)
+            ) {
+                tabular.value_counts = None;
             }
         }
-        (StatusBarState::Normal, KeyCode::Left | KeyCode::Char('h')) => {
-            if tabular.detailed_view.is_some() {
-                tabular.select_up(1)
+        (Mode::Normal, _) if tabular.schema.is_some() => {
+            let page = tabular.rendered_rows.max(1) as usize;
+            let schema = tabular.schema.as_mut().unwrap();
+            match action {
+                Some(Action::Up) => schema.up(1),
+                Some(Action::Down) => schema.down(1),
+                Some(Action::PageUp) => schema.up(page),
+                Some(Action::PageDown) => schema.down(page),
+                Some(Action::First) => schema.up(usize::MAX),
+                Some(Action::Last) => schema.down(usize::MAX),
+                Some(Action::CellPopup) => {
+                    tabular.select_column = schema.selected;
+                    tabular.schema = None;
+                }
+                Some(Action::Schema | Action::Quit) => tabular.schema = None,
+                _ => (),
             }
         }
-        (StatusBarState::Normal, KeyCode::Right | KeyCode::Char('l')) => {
-            if tabular.detailed_view.is_some() {
-                tabular.select_down(1)
+        (Mode::Normal, _) if tabular.histogram.is_some() => {
+            if matches!(
+                action,
+                Some(Action::CellPopup | Action::Histogram | Action::Quit)
+            ) {
+                tabular.histogram = None;
             }
         }
-        (StatusBarState::Normal, KeyCode::PageUp) => {
-            tabular.select_up(tabular.rendered_rows.into())
+        (Mode::Normal, _) => match action {
+            Some(Action::Quit) => *running = false,
+            Some(Action::Stats) if tabular.detailed_view.is_none() => {
+                if let Err(error) = tabular.toggle_stats() {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::ValueCounts) if tabular.detailed_view.is_none() => {
+                if let Err(error) = tabular.toggle_value_counts() {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::Histogram) if tabular.detailed_view.is_none() => {
+                if let Err(error) = tabular.toggle_histogram() {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::Schema) if tabular.detailed_view.is_none() => {
+                if let Err(error) = tabular.toggle_schema() {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::CellPopup) if tabular.detailed_view.is_none() => {
+                tabular.toggle_cell_popup()
+            }
+            Some(Action::ViewQuery) => tabular.toggle_view_popup(),
+            Some(Action::SwitchView) => tabular.switch_view(),
+            Some(Action::RecordView) => tabular.toggle_record_view(),
+            Some(Action::Up) => {
+                if let Some(scroll) = &mut tabular.detailed_view {
+                    scroll.up();
+                } else {
+                    tabular.select_up(1);
+                }
+            }
+            Some(Action::Down) => {
+                if let Some(scroll) = &mut tabular.detailed_view {
+                    scroll.down();
+                } else {
+                    tabular.select_down(1);
+                }
+            }
+            Some(Action::Left) => {
+                if tabular.detailed_view.is_some() {
+                    tabular.select_up(1)
+                } else {
+                    tabular.select_left()
+                }
+            }
+            Some(Action::Right) => {
+                if tabular.detailed_view.is_some() {
+                    tabular.select_down(1)
+                } else {
+                    tabular.select_right()
+                }
+            }
+            Some(Action::Sort) => {
+                if let Err(error) = tabular.sort_selected_again() {
+                    status_bar.error(error);
+                }
+            }
+            Some(action @ (Action::SortAscending | Action::SortDescending)) => {
+                if let Err(error) = tabular.sort_selected(action == Action::SortDescending) {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::Reset) => {
+                if let Err(error) = execute_command(
+                    ":reset",
+                    tabs,
+                    status_bar,
+                    sql_context,
+                    running,
+                    exec_tbl,
+                    aliases,
+                ) {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::PageUp) => tabular.page_up(),
+            Some(Action::PageDown) => tabular.page_down(),
+            Some(Action::First) => tabular.select_first(),
+            Some(Action::Last) => tabular.select_last(),
+            Some(Action::Command) => {
+                status_bar.command(":");
+            }
+            Some(Action::Search) => {
+                status_bar.command("/");
+            }
+            Some(Action::FilterColumn) => {
+                status_bar.command(":filter-column ");
+            }
+            Some(action @ (Action::SearchNext | Action::SearchPrev)) => {
+                let found = if action == Action::SearchNext {
+                    tabular.search_next()
+                } else {
+                    tabular.search_prev()
+                };
+                match &tabular.search {
+                    None => status_bar.error("no active search, press / to search"),
+                    Some(search) if !found => {
+                        status_bar.error(format!("no matches for '{}'", search.query()))
+                    }
+                    _ => (),
+                }
+            }
+            Some(Action::Goto) => {
+                status_bar.command(":goto ");
+                // A digit is the start of the row number
+                if matches!(key_event.code, KeyCode::Char(c) if c.is_ascii_digit()) {
+                    status_bar.input(key_event);
+                }
+            }
+            Some(Action::HalfPageUp) => tabular.select_up((tabular.rendered_rows / 2).into()),
+            Some(Action::HalfPageDown) => tabular.select_down((tabular.rendered_rows / 2).into()),
+            Some(Action::Freeze) => tabular.toggle_freeze(),
+            Some(Action::Widen) => tabular.resize_selected(1),
+            Some(Action::Narrow) => tabular.resize_selected(-1),
+            Some(Action::FitContent) => tabular.toggle_fit_content(),
+            Some(Action::Wrap) => tabular.toggle_wrap(),
+            Some(Action::ToggleTypes) => tabular.show_types = !tabular.show_types,
+            Some(Action::ToggleLineNumbers) => tabular.line_numbers = !tabular.line_numbers,
+            Some(Action::ToggleNulls) => tabular.highlight_nulls = !tabular.highlight_nulls,
+            Some(Action::ToggleStripes) => tabular.stripes = !tabular.stripes,
+            Some(Action::CopyCell | Action::CopyRow | Action::Edit) if tabular.is_empty() => {
+                status_bar.error("the table has no rows")
+            }
+            Some(action @ (Action::CopyCell | Action::CopyRow)) => {
+                let (what, text) = if action == Action::CopyCell {
+                    let cell = tabular
+                        .table_values
+                        .get(tabular.select, tabular.select_column);
+                    ("cell".to_owned(), cell.unwrap_or_default().to_owned())
+                } else if let Some(range) = tabular.selected_range() {
+                    if let Err(error) = tabular.require_loaded("copying rows") {
+                        status_bar.error(error);
+                        return Ok(());
+                    }
+                    (
+                        format!("{} rows", range.len()),
+                        tab_separated(&tabular.selected_data_frame(), tabular.number_format),
+                    )
+                } else {
+                    (
+                        "row".to_owned(),
+                        tabular.table_values.get_row(tabular.select).join("\t"),
+                    )
+                };
+                match clipboard::copy(&text) {
+                    Ok(_) => status_bar.info(format!("copied {} to the clipboard", what)),
+                    Err(error) => status_bar.error(error),
+                }
+            }
+            Some(Action::Hide) => {
+                if let Err(error) = tabular.hide_selected() {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::UnhideAll) => tabular.unhide_all(),
+            Some(action @ (Action::MoveLeft | Action::MoveRight)) => {
+                tabular.move_selected(action == Action::MoveRight);
+                // SELECT * lists the columns in the display order
+                tabular.register(sql_context);
+            }
+            Some(Action::Edit) => {
+                // the value as it is rather than as it is formatted, null being empty
+                let value = tabular
+                    .selected_value()
+                    .filter(|value| !value.is_null())
+                    .map(any_value_into_string)
+                    .unwrap_or_default();
+                status_bar.command(format!(":edit {}", value));
+            }
+            Some(
+                action @ (Action::DeleteRow | Action::AppendRow | Action::Undo | Action::Redo),
+            ) => {
+                let command = match action {
+                    // a range is deleted at once
+                    Action::DeleteRow if !repeated && tabular.range_start.is_none() => {
+                        status_bar.pending_action = Some(action);
+                        return Ok(());
+                    }
+                    Action::DeleteRow => ":delete-row",
+                    Action::AppendRow => ":append-row",
+                    Action::Undo => ":undo",
+                    _ => ":redo",
+                };
+                if let Err(error) = execute_command(
+                    command,
+                    tabs,
+                    status_bar,
+                    sql_context,
+                    running,
+                    exec_tbl,
+                    aliases,
+                ) {
+                    status_bar.error(error);
+                }
+            }
+            Some(Action::Follow) => match &mut tabular.follow {
+                Some(follow) => {
+                    follow.paused = !follow.paused;
+                    status_bar.info(match follow.paused {
+                        true => "following paused",
+                        false => "following resumed",
+                    });
+                }
+                None => status_bar.error("the table does not follow its file, see --follow"),
+            },
+            Some(Action::SelectRange) => tabular.toggle_range(),
+            Some(Action::ExtendUp) => {
+                tabular.start_range();
+                tabular.select_up(1);
+            }
+            Some(Action::ExtendDown) => {
+                tabular.start_range();
+                tabular.select_down(1);
+            }
+            Some(Action::Bookmark) => tabular.toggle_bookmark(),
+            Some(action @ (Action::NextBookmark | Action::PrevBookmark)) => {
+                let found = tabular.select_bookmark(action == Action::NextBookmark);
+                if !found {
+                    status_bar.error("no bookmarked rows, m bookmarks the selected row");
+                }
+            }
+            Some(Action::Random) => {
+                tabular.select_random();
+            }
+            Some(Action::ResetSort) => {
+                tabular.reset_sort();
+            }
+            Some(Action::NextTab) => tabs.select_next(),
+            Some(Action::PrevTab) => tabs.select_prev(),
+            Some(Action::Palette) => {
+                status_bar.palette = Some(Palette::new(CommandList::default().entries()))
+            }
+            Some(Action::Help) => status_bar.help = Some(keymap.help()),
+            _ => (),
+        },
+    }
+    Ok(())
+}
+
+/// Rows moved by one step of the mouse wheel.
+const SCROLL_ROWS: usize = 3;
+
+/// Handles the mouse events: the wheel moves the selection, or scrolls the open popup,
+/// and the horizontal wheel or Shift with the wheel selects the next or previous column.
+/// A left click selects the cell under the mouse, or closes the open popup.
+pub fn handle_mouse_events(mouse_event: MouseEvent, tabs: &mut Tabs, status_bar: &mut StatusBar) {
+    let tabular = tabs.selected_mut();
+    let shift = mouse_event.modifiers.contains(KeyModifiers::SHIFT);
+    match (mouse_event.kind, shift) {
+        (MouseEventKind::ScrollUp, false) if status_bar.history_popup.is_some() => {
+            status_bar.history_up()
         }
-        (StatusBarState::Normal, KeyCode::PageDown) => {
-            tabular.select_down(tabular.rendered_rows.into())
+        (MouseEventKind::ScrollDown, false) if status_bar.history_popup.is_some() => {
+            status_bar.history_down()
         }
-        (StatusBarState::Normal, KeyCode::Char('b'))
-            if key_event.modifiers == KeyModifiers::CONTROL =>
-        {
-            tabular.select_up(tabular.rendered_rows.into())
+        (MouseEventKind::ScrollUp, false) => {
+            if let Some(scroll) = &mut tabular.cell_popup {
+                scroll.up();
+            } else if let Some(scroll) = &mut tabular.view_popup {
+                scroll.up();
+            } else if let Some(scroll) = &mut tabular.detailed_view {
+                scroll.up();
+            } else {
+                tabular.select_up(SCROLL_ROWS);
+            }
         }
-        (StatusBarState::Normal, KeyCode::Char('f'))
-            if key_event.modifiers == KeyModifiers::CONTROL =>
-        {
-            tabular.select_down(tabular.rendered_rows.into())
-        }
-        (StatusBarState::Normal, KeyCode::Home | KeyCode::Char('g')) => tabular.select_first(),
-        (StatusBarState::Normal, KeyCode::End | KeyCode::Char('G')) => tabular.select_last(),
-        (StatusBarState::Normal, KeyCode::Char(':')) => {
-            status_bar.command(":");
-        }
-        (
-            StatusBarState::Normal,
-            KeyCode::Char('1')
-            | KeyCode::Char('2')
-            | KeyCode::Char('3')
-            | KeyCode::Char('4')
-            | KeyCode::Char('5')
-            | KeyCode::Char('6')
-            | KeyCode::Char('7')
-            | KeyCode::Char('8')
-            | KeyCode::Char('9'),
-        ) => {
-            status_bar.command(":goto ");
-            status_bar.input(key_event);
-        }
-        (StatusBarState::Normal, KeyCode::Char('u'))
-            if key_event.modifiers == KeyModifiers::CONTROL =>
-        {
-            tabular.select_up((tabular.rendered_rows / 2).into())
+        (MouseEventKind::ScrollDown, false) => {
+            if let Some(scroll) = &mut tabular.cell_popup {
+                scroll.down();
+            } else if let Some(scroll) = &mut tabular.view_popup {
+                scroll.down();
+            } else if let Some(scroll) = &mut tabular.detailed_view {
+                scroll.down();
+            } else {
+                tabular.select_down(SCROLL_ROWS);
+            }
         }
-        (StatusBarState::Normal, KeyCode::Char('d'))
-            if key_event.modifiers == KeyModifiers::CONTROL =>
+        (MouseEventKind::Down(MouseButton::Left), _)
+            if matches!(status_bar.state, StatusBarState::Normal)
+                && status_bar.history_popup.is_none() =>
         {
-            tabular.select_down((tabular.rendered_rows / 2).into())
+            if tabular.cell_popup.is_some()
+                || tabular.view_popup.is_some()
+                || tabular.stats.is_some()
+                || tabular.value_counts.is_some()
+                || tabular.histogram.is_some()
+                || tabular.schema.is_some()
+            {
+                tabular.cell_popup = None;
+                tabular.view_popup = None;
+                tabular.stats = None;
+                tabular.value_counts = None;
+                tabular.histogram = None;
+                tabular.schema = None;
+            } else if tabular.detailed_view.is_none() {
+                tabular.click(mouse_event.column, mouse_event.row)
+            }
         }
-        (StatusBarState::Normal, KeyCode::Char('R')) => {
-            tabular.select_random();
+        (MouseEventKind::ScrollLeft, _) | (MouseEventKind::ScrollUp, true)
+            if tabular.detailed_view.is_none() =>
+        {
+            tabular.select_left()
         }
-        (StatusBarState::Error(_), _) => {
-            status_bar.normal();
+        (MouseEventKind::ScrollRight, _) | (MouseEventKind::ScrollDown, true)
+            if tabular.detailed_view.is_none() =>
+        {
+            tabular.select_right()
         }
-
-        _ => {}
+        _ => (),
     }
-    Ok(())
 }
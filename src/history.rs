@@ -0,0 +1,114 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::utils::config_dir;
+
+/// Maximum number of entries kept in the history file.
+pub const HISTORY_CAPACITY: usize = 1000;
+
+/// Prompt history, oldest entry first, optionally persisted to a file.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Loads the history from `history` in the config directory. A missing or
+    /// unreadable file gives an empty history that is still saved on push.
+    pub fn load() -> Self {
+        match config_dir() {
+            Some(dir) => Self::from_file(dir.join("history")),
+            None => Self::default(),
+        }
+    }
+
+    pub fn from_file(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .map(|content| content.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+        let mut history = Self {
+            entries,
+            path: Some(path),
+        };
+        history.truncate();
+        history
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Appends `entry` unless it is empty or repeats the last entry, then saves the
+    /// history file. Failing to save never interrupts the app.
+    pub fn push(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        if entry.trim().is_empty() || self.entries.last() == Some(&entry) {
+            return;
+        }
+        self.entries.push(entry);
+        self.truncate();
+        let _ = self.save();
+    }
+
+    fn truncate(&mut self) {
+        let excess = self.entries.len().saturating_sub(HISTORY_CAPACITY);
+        self.entries.drain(..excess);
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::File::create(path)?;
+        for entry in &self.entries {
+            writeln!(file, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_dedup_and_capacity() {
+        let mut history = History::default();
+        history.push(":Q select * from df");
+        history.push(":Q select * from df");
+        history.push("");
+        history.push(":goto 2");
+        history.push(":Q select * from df");
+        assert_eq!(
+            history.entries(),
+            [":Q select * from df", ":goto 2", ":Q select * from df"]
+        );
+
+        for idx in 0..HISTORY_CAPACITY {
+            history.push(format!(":goto {}", idx));
+        }
+        assert_eq!(history.entries().len(), HISTORY_CAPACITY);
+        assert_eq!(history.entries()[0], ":goto 0");
+    }
+
+    #[test]
+    fn test_history_persistence() {
+        let path = std::env::temp_dir().join(format!("tabiew-history-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut history = History::from_file(path.clone());
+        history.push(":Q select 1");
+        history.push("/needle");
+        assert_eq!(
+            History::from_file(path.clone()).entries(),
+            [":Q select 1", "/needle"]
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,757 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    app::AppResult,
+    utils::{config_dir, Scroll},
+};
+
+/// What a key does in the table, outside of the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Stats,
+    ValueCounts,
+    Histogram,
+    Schema,
+    CellPopup,
+    ViewQuery,
+    SwitchView,
+    RecordView,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    First,
+    Last,
+    Goto,
+    Random,
+    SelectRange,
+    ExtendUp,
+    ExtendDown,
+    Bookmark,
+    NextBookmark,
+    PrevBookmark,
+    Sort,
+    SortAscending,
+    SortDescending,
+    ResetSort,
+    Reset,
+    Command,
+    Search,
+    SearchNext,
+    SearchPrev,
+    FilterColumn,
+    Freeze,
+    Widen,
+    Narrow,
+    FitContent,
+    Wrap,
+    ToggleTypes,
+    ToggleLineNumbers,
+    ToggleNulls,
+    ToggleStripes,
+    CopyCell,
+    CopyRow,
+    Hide,
+    UnhideAll,
+    MoveLeft,
+    MoveRight,
+    NextTab,
+    PrevTab,
+    Palette,
+    Edit,
+    DeleteRow,
+    AppendRow,
+    Undo,
+    Redo,
+    Follow,
+    Help,
+}
+
+/// Groups of the actions in the help overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Navigation,
+    Data,
+    Sql,
+    Misc,
+}
+
+impl Category {
+    const ALL: [Category; 4] = [
+        Category::Navigation,
+        Category::Data,
+        Category::Sql,
+        Category::Misc,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Navigation => "Navigation",
+            Category::Data => "Data",
+            Category::Sql => "SQL",
+            Category::Misc => "Misc",
+        }
+    }
+}
+
+/// Names of the actions in the keymap file.
+const ACTIONS: [(&str, Action); 62] = [
+    ("quit", Action::Quit),
+    ("stats", Action::Stats),
+    ("value-counts", Action::ValueCounts),
+    ("histogram", Action::Histogram),
+    ("schema", Action::Schema),
+    ("cell-popup", Action::CellPopup),
+    ("view-query", Action::ViewQuery),
+    ("switch-view", Action::SwitchView),
+    ("record-view", Action::RecordView),
+    ("up", Action::Up),
+    ("down", Action::Down),
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("page-up", Action::PageUp),
+    ("page-down", Action::PageDown),
+    ("half-page-up", Action::HalfPageUp),
+    ("half-page-down", Action::HalfPageDown),
+    ("first", Action::First),
+    ("last", Action::Last),
+    ("goto", Action::Goto),
+    ("random", Action::Random),
+    ("select-range", Action::SelectRange),
+    ("extend-up", Action::ExtendUp),
+    ("extend-down", Action::ExtendDown),
+    ("bookmark", Action::Bookmark),
+    ("next-bookmark", Action::NextBookmark),
+    ("prev-bookmark", Action::PrevBookmark),
+    ("sort", Action::Sort),
+    ("sort-ascending", Action::SortAscending),
+    ("sort-descending", Action::SortDescending),
+    ("reset-sort", Action::ResetSort),
+    ("reset", Action::Reset),
+    ("command", Action::Command),
+    ("search", Action::Search),
+    ("search-next", Action::SearchNext),
+    ("search-prev", Action::SearchPrev),
+    ("filter-column", Action::FilterColumn),
+    ("freeze", Action::Freeze),
+    ("widen", Action::Widen),
+    ("narrow", Action::Narrow),
+    ("fit-content", Action::FitContent),
+    ("wrap", Action::Wrap),
+    ("toggle-types", Action::ToggleTypes),
+    ("toggle-line-numbers", Action::ToggleLineNumbers),
+    ("toggle-nulls", Action::ToggleNulls),
+    ("toggle-stripes", Action::ToggleStripes),
+    ("copy-cell", Action::CopyCell),
+    ("copy-row", Action::CopyRow),
+    ("hide", Action::Hide),
+    ("unhide-all", Action::UnhideAll),
+    ("move-left", Action::MoveLeft),
+    ("move-right", Action::MoveRight),
+    ("next-tab", Action::NextTab),
+    ("prev-tab", Action::PrevTab),
+    ("palette", Action::Palette),
+    ("edit", Action::Edit),
+    ("delete-row", Action::DeleteRow),
+    ("append-row", Action::AppendRow),
+    ("undo", Action::Undo),
+    ("redo", Action::Redo),
+    ("follow", Action::Follow),
+    ("help", Action::Help),
+];
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn name(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(_, action)| action == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Action::Up
+            | Action::Down
+            | Action::Left
+            | Action::Right
+            | Action::PageUp
+            | Action::PageDown
+            | Action::HalfPageUp
+            | Action::HalfPageDown
+            | Action::First
+            | Action::Last
+            | Action::Goto
+            | Action::Random
+            | Action::SelectRange
+            | Action::ExtendUp
+            | Action::ExtendDown
+            | Action::Bookmark
+            | Action::NextBookmark
+            | Action::PrevBookmark
+            | Action::SwitchView
+            | Action::RecordView
+            | Action::NextTab
+            | Action::PrevTab => Category::Navigation,
+            Action::Stats
+            | Action::ValueCounts
+            | Action::Histogram
+            | Action::Schema
+            | Action::CellPopup
+            | Action::ViewQuery
+            | Action::Sort
+            | Action::SortAscending
+            | Action::SortDescending
+            | Action::ResetSort
+            | Action::Search
+            | Action::SearchNext
+            | Action::SearchPrev
+            | Action::FilterColumn
+            | Action::Hide
+            | Action::UnhideAll
+            | Action::MoveLeft
+            | Action::MoveRight
+            | Action::CopyCell
+            | Action::CopyRow
+            | Action::Edit
+            | Action::DeleteRow
+            | Action::AppendRow
+            | Action::Undo
+            | Action::Redo => Category::Data,
+            Action::Command | Action::Palette | Action::Reset => Category::Sql,
+            Action::Freeze
+            | Action::Widen
+            | Action::Narrow
+            | Action::FitContent
+            | Action::Wrap
+            | Action::ToggleTypes
+            | Action::ToggleLineNumbers
+            | Action::ToggleNulls
+            | Action::ToggleStripes
+            | Action::Follow
+            | Action::Help
+            | Action::Quit => Category::Misc,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Stats => "Show statistics of the selected column",
+            Action::ValueCounts => "Count the rows holding each value of the selected column",
+            Action::Histogram => "Show the histogram of the selected numeric column",
+            Action::Schema => "List the columns with their types and null counts",
+            Action::CellPopup => "Show the full value of the selected cell",
+            Action::ViewQuery => "Show the query the table is the result of",
+            Action::SwitchView => "Toggle the detailed view",
+            Action::RecordView => "Toggle the record view",
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Select the previous column",
+            Action::Right => "Select the next column",
+            Action::PageUp => "Scroll one page up",
+            Action::PageDown => "Scroll one page down",
+            Action::HalfPageUp => "Move half a page up",
+            Action::HalfPageDown => "Move half a page down",
+            Action::First => "Go to the first row",
+            Action::Last => "Go to the last row",
+            Action::Goto => "Go to a row number",
+            Action::Random => "Select a random row",
+            Action::SelectRange => {
+                "Start or drop a range of rows for copying, exporting or deleting"
+            }
+            Action::ExtendUp => "Extend the selected range a row up",
+            Action::ExtendDown => "Extend the selected range a row down",
+            Action::Bookmark => "Bookmark the selected row, or remove its bookmark",
+            Action::NextBookmark => "Go to the next bookmarked row",
+            Action::PrevBookmark => "Go to the previous bookmarked row",
+            Action::Sort => "Sort by the selected column, in the order last used for it",
+            Action::SortAscending => "Sort by the selected column, ascending",
+            Action::SortDescending => "Sort by the selected column, descending",
+            Action::ResetSort => "Restore the order before sorting",
+            Action::Reset => "Reset the table to the original data frame",
+            Action::Command => "Enter a command or an SQL query",
+            Action::Search => "Search the table",
+            Action::SearchNext => "Go to the next match",
+            Action::SearchPrev => "Go to the previous match",
+            Action::FilterColumn => "Filter the rows on the selected column",
+            Action::Freeze => "Freeze the columns up to the selected one",
+            Action::Widen => "Widen the selected column",
+            Action::Narrow => "Narrow the selected column",
+            Action::FitContent => "Size the columns to the visible rows",
+            Action::Wrap => "Wrap the values of the selected column over several lines",
+            Action::ToggleTypes => "Show or hide the data types",
+            Action::ToggleLineNumbers => "Show or hide the row numbers",
+            Action::ToggleNulls => "Show or hide null values",
+            Action::ToggleStripes => "Shade every other row or none",
+            Action::CopyCell => "Copy the selected cell",
+            Action::CopyRow => "Copy the selected row",
+            Action::Hide => "Hide the selected column",
+            Action::UnhideAll => "Show all hidden columns",
+            Action::MoveLeft => "Move the selected column left",
+            Action::MoveRight => "Move the selected column right",
+            Action::NextTab => "Select the next tab",
+            Action::PrevTab => "Select the previous tab",
+            Action::Palette => "Find a command by its name or description",
+            Action::Edit => "Edit the selected cell",
+            Action::DeleteRow => "Delete the selected row, pressed twice",
+            Action::AppendRow => "Append a blank row",
+            Action::Undo => "Undo the last edit, deletion, appended row, sort or filter",
+            Action::Redo => "Redo the last undone change",
+            Action::Follow => "Pause or resume following the file with --follow",
+            Action::Help => "Show or hide this help",
+        }
+    }
+}
+
+/// Names of the keys without a character of their own.
+const KEYS: [(&str, KeyCode); 14] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+/// Parses a key like `j`, `G`, `ctrl+d` or `pagedown`. `space` and `hash` stand for
+/// the keys that would be read as a separator or a comment.
+pub fn parse_key(key: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = key;
+    while rest.chars().count() > 1 {
+        let Some((modifier, tail)) = rest.split_once('+') else {
+            break;
+        };
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => break,
+        };
+        rest = tail;
+    }
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "hash" => KeyCode::Char('#'),
+            name => match name.strip_prefix('f').and_then(|num| num.parse().ok()) {
+                Some(num @ 1..=12) => KeyCode::F(num),
+                _ => KEYS
+                    .iter()
+                    .find(|(key_name, _)| *key_name == name)
+                    .map(|(_, code)| *code)?,
+            },
+        },
+    };
+    Some(normalize(KeyEvent::new(code, modifiers)))
+}
+
+/// Name of a key in the form [`parse_key`] reads, but with `#` as it is.
+pub fn key_name(key_event: KeyEvent) -> String {
+    let mut name = String::new();
+    for (modifier, prefix) in [
+        (KeyModifiers::CONTROL, "ctrl+"),
+        (KeyModifiers::ALT, "alt+"),
+        (KeyModifiers::SHIFT, "shift+"),
+    ] {
+        if key_event.modifiers.contains(modifier) {
+            name.push_str(prefix);
+        }
+    }
+    match key_event.code {
+        KeyCode::Char(' ') => name.push_str("space"),
+        KeyCode::Char(c) => name.push(c),
+        KeyCode::F(num) => name.push_str(&format!("f{}", num)),
+        code => name.push_str(
+            KEYS.iter()
+                .find(|(_, key_code)| *key_code == code)
+                .map(|(key_name, _)| *key_name)
+                .unwrap_or("?"),
+        ),
+    }
+    name
+}
+
+/// Drops what the code of a key already tells, terminals differ in whether they send
+/// Shift with an uppercase letter, a symbol or BackTab.
+fn normalize(key_event: KeyEvent) -> KeyEvent {
+    let mut modifiers = key_event.modifiers;
+    let code = match key_event.code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+            modifiers.remove(KeyModifiers::SHIFT);
+            KeyCode::Char(c.to_ascii_uppercase())
+        }
+        KeyCode::BackTab => {
+            modifiers.remove(KeyModifiers::SHIFT);
+            KeyCode::BackTab
+        }
+        code => code,
+    };
+    KeyEvent::new(code, modifiers)
+}
+
+/// Keys of the actions, the default bindings overridden by the `keymap` file in the
+/// config directory, read from lines like `ctrl+n = page-down`.
+#[derive(Debug, Clone)]
+pub struct Keymap(HashMap<KeyEvent, Action>);
+
+impl Keymap {
+    /// Loads the keymap from `keymap` in the config directory, if it exists, along with
+    /// warnings about the lines it skipped.
+    pub fn load() -> AppResult<(Self, Vec<String>)> {
+        match config_dir() {
+            Some(dir) => Self::from_file(&dir.join("keymap")),
+            None => Ok((Self::default(), Vec::new())),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> AppResult<(Self, Vec<String>)> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                let (keymap, warnings) = Self::parse(&content);
+                let warnings = warnings
+                    .into_iter()
+                    .map(|warning| format!("{}: {}", path.display(), warning))
+                    .collect();
+                Ok((keymap, warnings))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok((Self::default(), Vec::new())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Parses one binding per line on top of the defaults, skipping empty lines and `#`
+    /// comments. A key bound to `none` is unbound. Lines with an unknown key or action
+    /// are skipped with a warning.
+    pub fn parse(content: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, action)) = line.rsplit_once('=') else {
+                warnings.push(format!("line {}: expected <key> = <action>", idx + 1));
+                continue;
+            };
+            let (key, action) = (key.trim(), action.trim());
+            let Some(key_event) = parse_key(key) else {
+                warnings.push(format!("line {}: unknown key '{}'", idx + 1, key));
+                continue;
+            };
+            if action == "none" {
+                keymap.0.remove(&key_event);
+            } else if let Some(action) = Action::from_name(action) {
+                keymap.0.insert(key_event, action);
+            } else {
+                warnings.push(format!("line {}: unknown action '{}'", idx + 1, action));
+            }
+        }
+        (keymap, warnings)
+    }
+
+    /// Action bound to the key, if any.
+    pub fn action(&self, key_event: KeyEvent) -> Option<Action> {
+        self.0.get(&normalize(key_event)).copied()
+    }
+
+    /// Keys bound to `action`, characters first.
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        let mut keys = self
+            .0
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key_event, _)| *key_event)
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|key_event| {
+            (
+                !matches!(key_event.code, KeyCode::Char(_)),
+                !key_event.modifiers.is_empty(),
+                key_name(*key_event),
+            )
+        });
+        keys.into_iter().map(key_name).collect()
+    }
+
+    /// Help overlay listing the bound actions by category, with the keys that cannot be
+    /// rebound at the end.
+    pub fn help(&self) -> Help {
+        let sections = Category::ALL
+            .iter()
+            .map(|category| {
+                let mut entries = ACTIONS
+                    .iter()
+                    .filter(|(_, action)| action.category() == *category)
+                    .filter_map(|(_, action)| {
+                        let keys = self.keys(*action);
+                        (!keys.is_empty()).then(|| (keys.join(", "), action.description()))
+                    })
+                    .collect::<Vec<_>>();
+                if *category == Category::Misc {
+                    entries
+                        .extend(FIXED_KEYS.map(|(key, description)| (key.to_owned(), description)));
+                }
+                (*category, entries)
+            })
+            .filter(|(_, entries)| !entries.is_empty())
+            .collect();
+        Help {
+            sections,
+            scroll: Scroll::default(),
+        }
+    }
+}
+
+/// Keys handled before the keymap is looked up.
+const FIXED_KEYS: [(&str, &str); 2] = [
+    (
+        "esc",
+        "Close the popup, cancel the query or leave the prompt",
+    ),
+    ("tab", "Complete the command in the prompt"),
+];
+
+/// Keys and what they do, grouped by category, shown in an overlay.
+#[derive(Debug, Clone)]
+pub struct Help {
+    pub sections: Vec<(Category, Vec<(String, &'static str)>)>,
+    pub scroll: Scroll,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        let mut bindings = vec![
+            (KeyCode::Char('q').into(), Action::Quit),
+            (KeyCode::Char('i').into(), Action::Stats),
+            (KeyCode::Char('c').into(), Action::ValueCounts),
+            (KeyCode::Char('b').into(), Action::Histogram),
+            (KeyCode::Char('I').into(), Action::Schema),
+            (KeyCode::Enter.into(), Action::CellPopup),
+            (KeyCode::Char('Q').into(), Action::ViewQuery),
+            (KeyCode::Char('v').into(), Action::SwitchView),
+            (KeyCode::Char('T').into(), Action::RecordView),
+            (KeyCode::Up.into(), Action::Up),
+            (KeyCode::Char('k').into(), Action::Up),
+            (KeyCode::Down.into(), Action::Down),
+            (
+                KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT),
+                Action::ExtendUp,
+            ),
+            (
+                KeyEvent::new(KeyCode::Down, KeyModifiers::SHIFT),
+                Action::ExtendDown,
+            ),
+            (KeyCode::Char('V').into(), Action::SelectRange),
+            (KeyCode::Char('m').into(), Action::Bookmark),
+            (KeyCode::Char(']').into(), Action::NextBookmark),
+            (KeyCode::Char('[').into(), Action::PrevBookmark),
+            (KeyCode::Char('j').into(), Action::Down),
+            (KeyCode::Left.into(), Action::Left),
+            (KeyCode::Char('h').into(), Action::Left),
+            (KeyCode::Right.into(), Action::Right),
+            (KeyCode::Char('l').into(), Action::Right),
+            (KeyCode::PageUp.into(), Action::PageUp),
+            (ctrl('b'), Action::PageUp),
+            (KeyCode::PageDown.into(), Action::PageDown),
+            (ctrl('f'), Action::PageDown),
+            (ctrl('u'), Action::HalfPageUp),
+            (ctrl('d'), Action::HalfPageDown),
+            (KeyCode::Home.into(), Action::First),
+            (KeyCode::Char('g').into(), Action::First),
+            (KeyCode::End.into(), Action::Last),
+            (KeyCode::Char('G').into(), Action::Last),
+            (KeyCode::Char('r').into(), Action::Random),
+            (KeyCode::Char('s').into(), Action::Sort),
+            (KeyCode::Char('S').into(), Action::SortDescending),
+            (KeyCode::Char('R').into(), Action::ResetSort),
+            (ctrl('p'), Action::Palette),
+            (KeyCode::Char(':').into(), Action::Command),
+            (KeyCode::Char('/').into(), Action::Search),
+            (KeyCode::Char('n').into(), Action::SearchNext),
+            (KeyCode::Char('N').into(), Action::SearchPrev),
+            (KeyCode::Char('F').into(), Action::FilterColumn),
+            (KeyCode::Char('f').into(), Action::Freeze),
+            (KeyCode::Char('+').into(), Action::Widen),
+            (KeyCode::Char('-').into(), Action::Narrow),
+            (KeyCode::Char('=').into(), Action::FitContent),
+            (KeyCode::Char('W').into(), Action::Wrap),
+            (KeyCode::Char('t').into(), Action::ToggleTypes),
+            (KeyCode::Char('#').into(), Action::ToggleLineNumbers),
+            (KeyCode::Char('z').into(), Action::ToggleNulls),
+            (KeyCode::Char('Z').into(), Action::ToggleStripes),
+            (KeyCode::Char('y').into(), Action::CopyCell),
+            (KeyCode::Char('Y').into(), Action::CopyRow),
+            (KeyCode::Char('H').into(), Action::Hide),
+            (KeyCode::Char('U').into(), Action::UnhideAll),
+            (KeyCode::Char('<').into(), Action::MoveLeft),
+            (KeyCode::Char('>').into(), Action::MoveRight),
+            (
+                KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT),
+                Action::MoveLeft,
+            ),
+            (
+                KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT),
+                Action::MoveRight,
+            ),
+            (KeyCode::Char('e').into(), Action::Edit),
+            (KeyCode::Char('d').into(), Action::DeleteRow),
+            (KeyCode::Char('o').into(), Action::AppendRow),
+            (KeyCode::Char('u').into(), Action::Undo),
+            (ctrl('r'), Action::Redo),
+            (KeyCode::Char('P').into(), Action::Follow),
+            (KeyCode::Tab.into(), Action::NextTab),
+            (KeyCode::BackTab.into(), Action::PrevTab),
+        ];
+        bindings.push((KeyCode::Char('?').into(), Action::Help));
+        bindings.extend(('1'..='9').map(|c| (KeyCode::Char(c).into(), Action::Goto)));
+        Self(
+            bindings
+                .into_iter()
+                .map(|(key_event, action)| (normalize(key_event), action))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("j"), Some(KeyCode::Char('j').into()));
+        assert_eq!(
+            parse_key("ctrl+d"),
+            Some(key(KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("ctrl++"),
+            Some(key(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse_key("shift+g"), Some(KeyCode::Char('G').into()));
+        assert_eq!(parse_key("PageDown"), Some(KeyCode::PageDown.into()));
+        assert_eq!(parse_key("hash"), Some(KeyCode::Char('#').into()));
+        assert_eq!(parse_key("f5"), Some(KeyCode::F(5).into()));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("meta+j"), None);
+        assert_eq!(parse_key("ctrl+"), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let (keymap, warnings) = Keymap::parse(
+            "# vim users\nctrl+n = page-down\n= = none\nx = explode\nctrl+q quit\nmeta+q = quit",
+        );
+        assert_eq!(
+            keymap.action(key(KeyCode::Char('n'), KeyModifiers::CONTROL)),
+            Some(Action::PageDown)
+        );
+        assert_eq!(keymap.action(KeyCode::Char('=').into()), None);
+        assert_eq!(keymap.action(KeyCode::Char('x').into()), None);
+        assert_eq!(keymap.action(KeyCode::Char('j').into()), Some(Action::Down));
+        assert_eq!(
+            warnings,
+            [
+                "line 4: unknown action 'explode'",
+                "line 5: expected <key> = <action>",
+                "line 6: unknown key 'meta+q'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_action() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(key(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Action::Last)
+        );
+        assert_eq!(
+            keymap.action(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            Some(Action::PrevTab)
+        );
+        assert_eq!(
+            keymap.action(key(KeyCode::Char('f'), KeyModifiers::CONTROL)),
+            Some(Action::PageDown)
+        );
+        assert_eq!(
+            keymap.action(KeyCode::Char('f').into()),
+            Some(Action::Freeze)
+        );
+        assert_eq!(keymap.action(KeyCode::Char('5').into()), Some(Action::Goto));
+        for (name, action) in ACTIONS {
+            assert_eq!(Action::from_name(name), Some(action));
+            assert_eq!(action.name(), name);
+        }
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(
+            key_name(key(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            "ctrl+d"
+        );
+        assert_eq!(key_name(KeyCode::PageDown.into()), "pagedown");
+        assert_eq!(key_name(KeyCode::Char(' ').into()), "space");
+        for name in ["j", "ctrl+d", "alt+shift+up", "f5", "space", "backtab"] {
+            assert_eq!(key_name(parse_key(name).unwrap()), name);
+        }
+
+        let (keymap, _) = Keymap::parse("ctrl+n = down\nctrl+r = none\nenter = none");
+        assert_eq!(keymap.keys(Action::Down), ["j", "ctrl+n", "down"]);
+        assert!(keymap.keys(Action::Redo).is_empty());
+        assert_eq!(Keymap::default().keys(Action::Redo), ["ctrl+r"]);
+        // `:reset` resets the table
+        assert!(Keymap::default().keys(Action::Reset).is_empty());
+        let help = keymap.help();
+        let categories = help
+            .sections
+            .iter()
+            .map(|(category, _)| *category)
+            .collect::<Vec<_>>();
+        assert_eq!(categories, Category::ALL);
+        let (_, sql) = &help.sections[2];
+        assert_eq!(
+            sql,
+            &[
+                (":".to_owned(), Action::Command.description()),
+                ("ctrl+p".to_owned(), Action::Palette.description())
+            ]
+        );
+        let (_, data) = &help.sections[1];
+        assert!(data
+            .iter()
+            .all(|(_, description)| *description != Action::CellPopup.description()));
+    }
+}
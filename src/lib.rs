@@ -27,3 +27,54 @@ pub mod args;
 
 /// command pallete
 pub mod command_pallete;
+
+/// File readers
+pub mod reader;
+
+/// Table search
+pub mod search;
+
+/// Clipboard
+pub mod clipboard;
+
+/// Column statistics
+pub mod stats;
+
+/// Prompt history
+pub mod history;
+
+/// Saved queries
+pub mod queries;
+
+/// Prompt completion
+pub mod completion;
+
+/// Command aliases
+pub mod alias;
+
+/// Terminal background detection
+pub mod background;
+
+/// Column quick filters
+pub mod filter;
+
+/// Pretty-printing of nested values
+pub mod pretty;
+
+/// Queries running in the background
+pub mod pending;
+
+/// Files followed as they grow
+pub mod follow;
+
+/// Per-file view state
+pub mod state;
+
+/// Key bindings
+pub mod keymap;
+
+/// Command palette
+pub mod palette;
+
+/// Saved sessions
+pub mod session;
@@ -1,159 +1,527 @@
-use clap::Parser;
-use polars::frame::DataFrame;
-use polars::io::csv::read::{CsvParseOptions, CsvReadOptions};
-use polars::lazy::frame::IntoLazy;
-use polars::prelude::ParquetReader;
-use polars::prelude::*;
+use clap::{CommandFactory, Parser};
+use polars::prelude::PolarsError;
 use polars_sql::SQLContext;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::io::{self, Stderr};
-use tabiew::app::{AppResult, StatusBar, Tabular};
-use tabiew::args::{Args, InferSchema};
-use tabiew::command::{CommandList, ExecutionTable};
-use tabiew::event::{Event, EventHandler};
-use tabiew::handler::handle_key_events;
-use tabiew::theme::Styler;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal, Stderr, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tabiew::alias::Aliases;
+use tabiew::app::{App, AppResult, StatusBar, Tabs, Tabular};
+use tabiew::args::{AppTheme, Args, FileFormat};
+use tabiew::background::{query_background, Background};
+use tabiew::command::{open_table, write_data_frame_with};
+use tabiew::event::EventHandler;
+use tabiew::follow::Follow;
+use tabiew::history::History;
+use tabiew::keymap::Keymap;
+use tabiew::reader::{read_tables, Origin, Source, Table};
+use tabiew::session::Session;
+use tabiew::state::States;
+use tabiew::theme::{ConfigTheme, ThemeColors};
 use tabiew::tui::Tui;
-use tabiew::utils::infer_schema_safe;
+use tabiew::utils::{box_table, config_dir, human_size, markdown_table, NumberFormat, SPINNER};
 
-fn load_parquet_file(args: &Args) -> DataFrame {
-    let file = std::fs::File::open(&args.file_name).unwrap();
+fn main() -> AppResult<()> {
+    // Parse CLI, adding the files of the session to the given ones unless they are
+    // already given
+    let mut args = Args::parse();
+    let session = match args.session.as_deref().map(Session::from_file) {
+        Some(Ok(session)) => Some(session),
+        Some(Err(err)) => {
+            let path = args.session.as_deref().unwrap_or(Path::new(""));
+            eprintln!("tw: {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    if let Some(session) = &session {
+        let given = args
+            .file_name
+            .iter()
+            .filter_map(|path| fs::canonicalize(path).ok())
+            .collect::<HashSet<_>>();
+        args.file_name.extend(
+            session
+                .files
+                .iter()
+                .map(|(_, path)| path)
+                .filter(|path| !given.contains(*path))
+                .cloned(),
+        );
+    }
+    let args = Arc::new(args);
 
-    ParquetReader::new(file).finish().unwrap()
-}
+    // Load the data frames before the terminal is taken over, so errors can be printed
+    let mut tables = Vec::new();
+    let mut warnings = Vec::new();
+    let sources = Source::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("tw: {}\n\n{}", err, Args::command().render_usage());
+        std::process::exit(2);
+    });
+    for source in sources {
+        let message = match source.path().and_then(|path| fs::metadata(path).ok()) {
+            Some(metadata) => format!("Loading {} ({})", source, human_size(metadata.len())),
+            None => format!("Loading {}", source),
+        };
+        let loaded = load_in_background(&message, Duration::from_millis(args.tick_rate), || {
+            read_tables(&source, &args)
+        });
+        match loaded {
+            Ok(source_tables) => {
+                // listed in full in the terminal after quitting, summed up in the status bar
+                for report in source_tables
+                    .iter()
+                    .filter_map(|table| table.malformed.as_ref())
+                {
+                    for (line, fields) in &report.rows {
+                        eprintln!(
+                            "tw: warning: {}: line {} has {} fields, expected {}",
+                            source, line, fields, report.expected
+                        );
+                    }
+                    warnings.push(format!("{}: {}", source.name(), report));
+                }
+                for table in source_tables
+                    .iter()
+                    .filter(|table| !table.renamed.is_empty())
+                {
+                    for (name, new_name) in &table.renamed {
+                        eprintln!(
+                            "tw: warning: {}: column '{}' is duplicated, renamed '{}'",
+                            source, name, new_name
+                        );
+                    }
+                    let new_names = table.renamed.iter().map(|(_, new_name)| new_name.as_str());
+                    warnings.push(format!(
+                        "{}: duplicate columns renamed {}",
+                        source.name(),
+                        new_names.collect::<Vec<_>>().join(", ")
+                    ));
+                }
+                tables.extend(
+                    source_tables
+                        .into_iter()
+                        .map(|table| (source.clone(), table)),
+                )
+            }
+            Err(err) => {
+                eprintln!("tw: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
 
-fn load_json_file(args: &Args) -> DataFrame {
-    let file = std::fs::File::open(&args.file_name).unwrap();
+    // Setup the SQLContext. Tables from databases keep their own name, those of the
+    // files of the session the name they had, others are named df, or df1, df2, ... if
+    // there is more than one, and after their file, e.g. customers for customers.csv,
+    // unless another table has the name
+    let mut sql_context = SQLContext::new();
+    let session_name = |source: &Source| {
+        let path = fs::canonicalize(source.path()?).ok()?;
+        Some(session.as_ref()?.name(&path)?.to_owned())
+    };
+    let session_names = tables
+        .iter()
+        .filter(|(_, table)| table.name.is_none())
+        .filter_map(|(source, _)| session_name(source))
+        .collect::<HashSet<_>>();
+    let unnamed = tables
+        .iter()
+        .filter(|(source, table)| table.name.is_none() && session_name(source).is_none())
+        .count();
+    let mut unnamed_idx = 0;
+    let names = tables
+        .iter()
+        .map(
+            |(source, table)| match (&table.name, session_name(source)) {
+                (Some(name), _) => name.clone(),
+                (None, Some(name)) => name,
+                (None, None) if unnamed == 1 && !session_names.contains("df") => "df".to_owned(),
+                (None, None) => loop {
+                    unnamed_idx += 1;
+                    let name = format!("df{}", unnamed_idx);
+                    if !session_names.contains(&name) {
+                        break name;
+                    }
+                },
+            },
+        )
+        .collect::<Vec<_>>();
+    let mut taken = names.iter().cloned().collect::<HashSet<_>>();
 
-    JsonReader::new(file).finish().unwrap()
-}
+    // Instantiate app, at the place left in each file unless it changed since
+    let mut states = States::load();
+    let tabulars = tables
+        .into_iter()
+        .zip(names)
+        .map(|((source, table), table_name)| {
+            let name = match &table.name {
+                Some(name) => format!("{}:{}", source.name(), name),
+                None => source.name(),
+            };
+            let stem_name =
+                source
+                    .stem_name()
+                    .filter(|_| table.name.is_none())
+                    .filter(|stem_name| {
+                        let free = taken.insert(stem_name.clone());
+                        if !free {
+                            eprintln!(
+                            "tw: warning: {} is not registered as {}, another table has the name",
+                            source, stem_name
+                        );
+                        }
+                        free
+                    });
+            // told before the table is taken apart
+            let unfollowed = not_followed(&source, &table, &args);
+            let mut tabular = match table.lazy {
+                Some(lazy) => Tabular::new_lazy(name, table_name, table.data_frame, lazy),
+                None => Tabular::new(name, table_name, table.data_frame),
+            };
+            tabular.stem_name = stem_name;
+            tabular.register(&mut sql_context);
+            tabular.file = table.file;
+            tabular.truncation = table.truncation;
+            tabular.projection = args.columns.clone();
+            tabular.origin = Some(Origin {
+                source: source.clone(),
+                table: table.name.clone(),
+                args: args.clone(),
+            });
+            if args.follow {
+                let file = tabular
+                    .file
+                    .as_ref()
+                    .filter(|file| matches!(file.format, FileFormat::Csv));
+                match file.map(|file| {
+                    Follow::new(file, tabular.source().schema(), args.null_values.clone())
+                }) {
+                    Some(Ok(follow)) => tabular.follow = Some(follow),
+                    Some(Err(err)) => eprintln!("tw: warning: {} is not followed, {}", source, err),
+                    None => eprintln!("tw: warning: {} is not followed, {}", source, unfollowed),
+                }
+            }
+            tabular.frozen = args.freeze;
+            tabular.max_width = args.max_col_width;
+            tabular.show_types = args.show_types;
+            tabular.highlight_nulls = args.highlight_nulls;
+            tabular.wrap_all = args.wrap;
+            tabular.line_numbers = args.line_numbers.is_some();
+            tabular.stripes = !args.no_stripes;
+            tabular.first_line_number = args.line_numbers.unwrap_or(1).into();
+            tabular.page_size = args.page_size.max(1);
+            tabular.set_number_format(NumberFormat {
+                thousands: args.thousands,
+                float_precision: args.float_precision,
+            });
+            tabular.set_symbols(args.symbols);
+            // the place in part of the rows is not the place in the file
+            tabular.source_path = source
+                .path()
+                .filter(|_| table.name.is_none() && table.truncation.is_none())
+                .and_then(|path| fs::canonicalize(path).ok());
+            if let Some(place) = tabular
+                .source_path
+                .as_ref()
+                .filter(|_| !args.no_restore)
+                .and_then(|path| states.get(path))
+            {
+                tabular.restore(place);
+            }
+            tabular
+        })
+        .collect();
+    let mut tabs = Tabs::new(tabulars);
 
-fn load_csv_file(args: &Args) -> DataFrame {
-    let data_frame = {
-        let mut df = CsvReadOptions::default()
-            .with_ignore_errors(args.ignore_errors)
-            .with_infer_schema_length((&args.infer_schema).into())
-            .with_has_header(!args.no_header)
-            .with_parse_options(
-                CsvParseOptions::default()
-                    .with_quote_char((args.quote_char as u8).into())
-                    .with_separator(args.separator as u8),
-            )
-            .try_into_reader_with_file_path(Some(args.file_name.clone()))
-            .unwrap()
-            .finish()
-            .unwrap();
-        if matches!(args.infer_schema, InferSchema::Safe) {
-            infer_schema_safe(&mut df);
-        }
-        df
-    };
+    // Create the tables of the session again, in order as they may query each other
+    if let Some(session) = &session {
+        for (name, query) in &session.tables {
+            let result = sql_context
+                .execute(query)
+                .and_then(|frame| frame.collect())
+                .map_err(Into::into)
+                .and_then(|data_frame| {
+                    open_table(
+                        name,
+                        data_frame,
+                        Some(query.clone()),
+                        &mut tabs,
+                        &mut sql_context,
+                    )
+                });
+            if let Err(err) = result {
+                eprintln!(
+                    "tw: warning: table {} of the session is not restored, {}",
+                    name, err
+                );
+                warnings.push(format!("{}: not restored", name));
+            }
+        }
+    }
 
-    data_frame
-}
+    // Write the result of --query rather than showing the tables
+    if let Some(query) = &args.query {
+        let format = NumberFormat {
+            thousands: args.thousands,
+            float_precision: args.float_precision,
+        };
+        match write_query(query, &args, format, &mut sql_context) {
+            Err(err) if !broken_pipe(err.as_ref()) => {
+                eprintln!("tw: {}", err);
+                std::process::exit(1);
+            }
+            _ => return Ok(()),
+        }
+    }
 
-fn main() -> AppResult<()> {
-    // Parse CLI
-    let args = Args::parse();
-
-    let data_frame = match args.file_name.extension().unwrap().to_str() {
-        Some("parquet") => load_parquet_file(&args),
-        Some("csv") => load_csv_file(&args),
-        Some("json") => load_json_file(&args),
-        _ => unimplemented!(),
+    let mut app = App::new(tabs, sql_context);
+    app.status_bar = StatusBar::new(History::load());
+    if !warnings.is_empty() {
+        app.status_bar.error(warnings.join("; "));
+    }
+
+    // Command handling
+    app.aliases = match Aliases::load(&app.exec_tbl) {
+        Ok(aliases) => aliases,
+        Err(err) => {
+            eprintln!("tw: {}", err);
+            std::process::exit(1);
+        }
+    };
+    app.keymap = match Keymap::load() {
+        Ok((keymap, warnings)) => {
+            for warning in warnings {
+                eprintln!("tw: warning: {}", warning);
+            }
+            keymap
+        }
+        Err(err) => {
+            eprintln!("tw: warning: {}, using the default keymap", err);
+            Keymap::default()
+        }
     };
 
-    // Setup the SQLContext
-    let mut sql_context = SQLContext::new();
-    sql_context.register("df", data_frame.clone().lazy());
+    // Load the theme file, falling back to the terminal theme if it is invalid
+    let mut theme = args.theme.clone();
+    if let Some(path) = &args.theme_file {
+        match ThemeColors::from_file(path) {
+            Ok(colors) => {
+                ConfigTheme::set(colors);
+                theme = AppTheme::Config;
+            }
+            Err(err) => {
+                eprintln!("tw: warning: {}, using the terminal theme", err);
+                theme = AppTheme::Terminal;
+            }
+        }
+    }
 
-    // Instantiate app
-    let tabular = Tabular::new(data_frame);
-    let status_bar = StatusBar::default();
+    // No colors at all with --no-color or a non-empty NO_COLOR, see no-color.org
+    if args.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        theme = AppTheme::Monochrome;
+    }
 
-    // Command handling
-    let exec_tbl = CommandList::default().into_exec();
+    // Pick a light or dark theme from the terminal background, dark if it is unknown
+    if matches!(theme, AppTheme::Auto) {
+        theme = match query_background(Duration::from_millis(200)) {
+            Some(Background::Light) => AppTheme::SolarizedLight,
+            Some(Background::Dark) | None => AppTheme::Monokai,
+        };
+    }
+    app.status_bar.theme = theme;
+
+    // Run the init script, from --init or the config directory
+    let init = args.init.clone().or_else(|| {
+        config_dir()
+            .map(|dir| dir.join("init.tw"))
+            .filter(|path| path.exists())
+    });
+    if let Some(path) = init {
+        run_init_script(&path, &mut app);
+        if !app.running {
+            return Ok(());
+        }
+    }
+
+    // Restore the terminal before a panic message is printed
+    tabiew::tui::install_panic_hook();
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(args.tick_rate);
     let mut tui = Tui::new(terminal, events);
-    tui.init()?;
+    tui.init(!args.no_mouse)?;
 
     // Run the main loop
-    match args.theme {
-        tabiew::args::AppTheme::Monokai => main_loop::<tabiew::theme::Monokai>(
-            &mut tui,
-            tabular,
-            status_bar,
-            sql_context,
-            exec_tbl,
-        )?,
-        tabiew::args::AppTheme::Terminal => main_loop::<tabiew::theme::Terminal>(
-            &mut tui,
-            tabular,
-            status_bar,
-            sql_context,
-            exec_tbl,
-        )?,
-    }
-
-    // Exit the user interface.
+    let result = main_loop(&mut tui, &mut app);
+
+    // Exit the user interface, also when the main loop failed
     tui.exit()?;
+    result?;
+
+    // Save the place in each file, failing to do so only warns
+    for tabular in app.tabs.iter() {
+        if let (Some(path), Some(place)) = (&tabular.source_path, tabular.place()) {
+            states.insert(path, place);
+        }
+    }
+    if let Err(err) = states.save() {
+        eprintln!("tw: warning: the place in the files is not saved, {}", err);
+    }
     Ok(())
 }
 
-fn main_loop<Theme: Styler>(
-    tui: &mut Tui<CrosstermBackend<Stderr>>,
-    mut tabular: Tabular,
-    mut status_bar: StatusBar,
-    mut sql_context: SQLContext,
-    exec_tbl: ExecutionTable,
+/// Why `table` of `source` would not be followed with `--follow`, for a table that is
+/// not a CSV file which can be written back.
+fn not_followed(source: &Source, table: &Table, args: &Args) -> &'static str {
+    if source.path().is_none() {
+        "only files are followed, not pipes, stdin or URLs"
+    } else if table.lazy.is_some() {
+        "--lazy does not load the rows it would follow"
+    } else if table.truncation.is_some() {
+        "--head and --sample leave out rows before the new ones"
+    } else if !table.renamed.is_empty() {
+        "its duplicate columns are renamed"
+    } else if args.flatten && table.file.is_none() {
+        "--flatten changes its columns"
+    } else {
+        "--follow needs a UTF-8 CSV file that is not compressed"
+    }
+}
+
+/// Runs `query` and writes its result to `output` in the format of its extension, or
+/// to stdout as CSV if `output` is `-` or not given.
+fn write_query(
+    query: &str,
+    args: &Args,
+    format: NumberFormat,
+    sql: &mut SQLContext,
 ) -> AppResult<()> {
-    let mut running = true;
-
-    // Start the main loop.
-    while running {
-        // Render the user interface.
-        tui.draw::<Theme>(&mut tabular, &mut status_bar)?;
-        // Handle events.
-        match tui.events.next()? {
-            Event::Tick => {
-                tabular.tick();
-                status_bar.tick();
-            }
-            Event::Key(key_event) => {
-                #[cfg(target_os = "windows")]
-                {
-                    use crossterm::event::KeyEventKind;
-                    if matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
-                        handle_key_events(
-                            key_event,
-                            &mut tabular,
-                            &mut status_bar,
-                            &mut sql_context,
-                            &mut running,
-                            &exec_tbl,
-                        )?
-                    }
-                }
-                #[cfg(not(target_os = "windows"))]
-                {
-                    handle_key_events(
-                        key_event,
-                        &mut tabular,
-                        &mut status_bar,
-                        &mut sql_context,
-                        &mut running,
-                        &exec_tbl,
-                    )?
-                }
+    let mut data_frame = sql.execute(query)?.collect()?;
+    let path = args
+        .output
+        .as_deref()
+        .filter(|path| *path != Path::new("-"));
+    let extension = match (&args.output_format, path) {
+        (Some(output_format), _) => output_format.extension().to_owned(),
+        (None, Some(path)) => path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default(),
+        (None, None) => "csv".to_owned(),
+    };
+    let open = || -> io::Result<Box<dyn Write>> {
+        match path {
+            Some(path) => Ok(Box::new(fs::File::create(path)?)),
+            None => Ok(Box::new(io::stdout().lock())),
+        }
+    };
+    match extension.as_str() {
+        "md" | "markdown" => open()?.write_all(markdown_table(&data_frame, format).as_bytes())?,
+        "txt" => open()?.write_all(box_table(&data_frame, format).as_bytes())?,
+        extension => write_data_frame_with(open, extension, &mut data_frame)?,
+    }
+    Ok(())
+}
+
+/// Whether `err` comes from writing to a pipe closed early, as by `head`, which stops
+/// the output rather than failing it.
+fn broken_pipe(err: &(dyn Error + 'static)) -> bool {
+    let io_error = match err.downcast_ref::<PolarsError>() {
+        Some(PolarsError::IO { error, .. }) => Some(error.as_ref()),
+        _ => err.downcast_ref::<io::Error>(),
+    };
+    io_error.is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Runs `load` on a background thread. If it takes a while and stderr is a terminal,
+/// `message` is shown with a spinner and the elapsed time, redrawn every `tick_rate`,
+/// until the load is done.
+fn load_in_background<T: Send>(
+    message: &str,
+    tick_rate: Duration,
+    load: impl FnOnce() -> AppResult<T> + Send,
+) -> AppResult<T> {
+    let start = Instant::now();
+    let spin = io::stderr().is_terminal();
+    thread::scope(|scope| {
+        let handle = scope.spawn(|| load().map_err(|err| err.to_string()));
+        // The spinner shows up after a short delay so that quick loads do not flicker
+        let mut next_frame = start + Duration::from_millis(300);
+        let mut frame = 0;
+        while !handle.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            if spin && Instant::now() >= next_frame {
+                eprint!(
+                    "\r{} {}… {}s",
+                    SPINNER[frame % SPINNER.len()],
+                    message,
+                    start.elapsed().as_secs()
+                );
+                frame += 1;
+                next_frame += tick_rate;
             }
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
         }
+        if frame > 0 {
+            eprint!("\r\x1b[2K");
+        }
+        match handle.join() {
+            Ok(result) => result.map_err(Into::into),
+            Err(_) => Err("the loader stopped unexpectedly".into()),
+        }
+    })
+}
+
+/// Runs the lines of the init script as prompt commands, or as SQL queries when they
+/// do not start with `:` or `/`. Failing lines are reported without stopping.
+fn run_init_script(path: &Path, app: &mut App) {
+    let script = match fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(err) => {
+            eprintln!("tw: warning: {}: {}", path.display(), err);
+            return;
+        }
+    };
+    let mut failed = 0;
+    for (idx, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let command = if line.starts_with([':', '/']) {
+            line.to_owned()
+        } else {
+            format!(":Q {}", line)
+        };
+        // later lines may use the result, so queries are not left in the background
+        let result = app
+            .execute(&command)
+            .and_then(|_| app.tabs.selected_mut().wait_query());
+        if let Err(err) = result {
+            eprintln!("tw: warning: {}:{}: {}", path.display(), idx + 1, err);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        app.status_bar
+            .error(format!("{} line(s) of {} failed", failed, path.display()));
+    }
+}
+
+fn main_loop(tui: &mut Tui<CrosstermBackend<Stderr>>, app: &mut App) -> AppResult<()> {
+    while app.running {
+        tui.draw(app)?;
+        let event = tui.events.next(app.needs_tick())?;
+        app.step(event)?;
     }
     Ok(())
 }
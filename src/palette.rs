@@ -0,0 +1,176 @@
+/// Command listed in the palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub command: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+impl PaletteEntry {
+    /// Whether the command needs arguments, which are typed in the prompt.
+    pub fn takes_arguments(&self) -> bool {
+        self.usage.contains(' ')
+    }
+}
+
+/// Commands filtered by what is typed: a fuzzy match of the command name ranks
+/// first, then the commands whose description contains the text.
+#[derive(Debug)]
+pub struct Palette {
+    entries: Vec<PaletteEntry>,
+    query: String,
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl Palette {
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
+        let mut palette = Self {
+            entries,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.filter();
+        palette
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self) -> impl Iterator<Item = &PaletteEntry> {
+        self.matches.iter().map(|idx| &self.entries[*idx])
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Entry under the selection, `None` when nothing matches.
+    pub fn chosen(&self) -> Option<&PaletteEntry> {
+        self.matches
+            .get(self.selected)
+            .map(|idx| &self.entries[*idx])
+    }
+
+    pub fn input(&mut self, c: char) {
+        self.query.push(c);
+        self.filter();
+    }
+
+    pub fn delete_backward(&mut self) {
+        self.query.pop();
+        self.filter();
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.trim().trim_start_matches(':').to_lowercase();
+        let mut scored = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                fuzzy_score(&query, entry.command.trim_start_matches(':'))
+                    .or_else(|| {
+                        entry
+                            .description
+                            .to_lowercase()
+                            .find(&query)
+                            .map(|pos| DESCRIPTION_SCORE + pos)
+                    })
+                    .map(|score| (score, idx))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|(score, _)| *score);
+        self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = 0;
+    }
+}
+
+/// Added to the score of a match in the description, so it ranks after the names.
+const DESCRIPTION_SCORE: usize = 1000;
+
+/// Score of `text` holding the characters of `query` in order, the spread of the
+/// characters lower being better, or `None` if it does not. `query` is lowercase.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut score = 0;
+    let mut last = None;
+    for expected in query.chars() {
+        let (pos, _) = chars.find(|(_, c)| *c == expected)?;
+        score += match last {
+            Some(last) => pos - last - 1,
+            None => pos,
+        };
+        last = Some(pos);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        command: &'static str,
+        usage: &'static str,
+        description: &'static str,
+    ) -> PaletteEntry {
+        PaletteEntry {
+            command,
+            usage,
+            description,
+        }
+    }
+
+    fn commands(palette: &Palette) -> Vec<&str> {
+        palette.matches().map(|entry| entry.command).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "goto"), Some(0));
+        assert_eq!(fuzzy_score("go", "goto"), Some(0));
+        assert_eq!(fuzzy_score("gd", "godown"), Some(1));
+        assert_eq!(fuzzy_score("cf", "clear-filters"), Some(5));
+        assert_eq!(fuzzy_score("og", "goto"), None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut palette = Palette::new(vec![
+            entry(":goto", ":goto <line_index>", "Jumps to the line"),
+            entry(":godown", ":godown <lines>", "Jumps down"),
+            entry(":order", ":order <columns>", "Sort the rows"),
+            entry(":reset", ":reset", "Reset the table"),
+        ]);
+        assert_eq!(commands(&palette).len(), 4);
+        for c in ":GO".chars() {
+            palette.input(c);
+        }
+        assert_eq!(commands(&palette), [":goto", ":godown"]);
+        palette.down();
+        palette.down();
+        assert_eq!(palette.chosen().unwrap().command, ":godown");
+        assert!(palette.chosen().unwrap().takes_arguments());
+
+        palette.delete_backward();
+        palette.delete_backward();
+        for c in "sort".chars() {
+            palette.input(c);
+        }
+        assert_eq!(commands(&palette), [":order"]);
+        assert_eq!(palette.selected(), 0);
+        palette.input('x');
+        assert_eq!(palette.chosen(), None);
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use polars::{
+    error::PolarsResult,
+    frame::DataFrame,
+    lazy::frame::{InProcessQuery, LazyFrame},
+};
+
+use crate::{app::AppResult, utils::SPINNER};
+
+/// SQL query collected on the Polars thread pool while the interface keeps running.
+/// Only the planned [`LazyFrame`] crosses to the pool, the SQL context stays with the
+/// interface.
+pub struct PendingQuery {
+    query: String,
+    handle: InProcessQuery,
+    started: Instant,
+    frame: usize,
+}
+
+impl PendingQuery {
+    /// Starts collecting `frame`, the plan of `query`.
+    pub fn start(query: String, frame: LazyFrame) -> AppResult<Self> {
+        Ok(Self {
+            query,
+            handle: frame.collect_concurrently()?,
+            started: Instant::now(),
+            frame: 0,
+        })
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Frame of the spinner, which moves on with every tick.
+    pub fn spinner(&self) -> char {
+        SPINNER[self.frame % SPINNER.len()]
+    }
+
+    /// Result of the query once it is collected, otherwise moves the spinner on.
+    pub fn poll(&mut self) -> Option<PolarsResult<DataFrame>> {
+        self.frame += 1;
+        self.handle.fetch()
+    }
+
+    /// Waits for the result of the query.
+    pub fn wait(&self) -> PolarsResult<DataFrame> {
+        self.handle.fetch_blocking()
+    }
+
+    /// Asks Polars to stop the query. The pool may still be working on it for a moment
+    /// and fails if nothing receives the result, so a thread waits for it.
+    pub fn cancel(self) {
+        self.handle.cancel();
+        std::thread::spawn(move || {
+            let _ = self.handle.fetch_blocking();
+        });
+    }
+}
+
+impl std::fmt::Debug for PendingQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingQuery")
+            .field("query", &self.query)
+            .field("started", &self.started)
+            .finish()
+    }
+}
@@ -0,0 +1,356 @@
+use std::{iter::Peekable, str::Chars};
+
+use polars::prelude::AnyValue;
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{theme::Styler, utils::any_value_into_string};
+
+/// Nested value, from a JSON text or a Polars struct or list, that the cell popup
+/// pretty-prints.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nested {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Nested>),
+    Object(Vec<(String, Nested)>),
+}
+
+impl Nested {
+    /// Parses `text` as JSON if it holds an object or an array, plain values are
+    /// better shown as they are.
+    pub fn from_json(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if !text.starts_with(['{', '[']) {
+            return None;
+        }
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        parser.chars.peek().is_none().then_some(value)
+    }
+
+    /// Converts a struct or list value, `None` for the other types.
+    pub fn from_any_value(value: &AnyValue) -> Option<Self> {
+        match value {
+            AnyValue::List(_) | AnyValue::Struct(_, _, _) | AnyValue::StructOwned(_) => {
+                Some(Self::convert(value))
+            }
+            _ => None,
+        }
+    }
+
+    fn convert(value: &AnyValue) -> Self {
+        match value {
+            AnyValue::Null => Self::Null,
+            AnyValue::Boolean(value) => Self::Bool(*value),
+            AnyValue::List(series) => {
+                Self::Array(series.iter().map(|value| Self::convert(&value)).collect())
+            }
+            AnyValue::Struct(_, _, fields) => Self::Object(
+                fields
+                    .iter()
+                    .map(|field| field.name().to_string())
+                    .zip(value._iter_struct_av().map(|value| Self::convert(&value)))
+                    .collect(),
+            ),
+            AnyValue::StructOwned(payload) => Self::Object(
+                payload
+                    .1
+                    .iter()
+                    .map(|field| field.name().to_string())
+                    .zip(payload.0.iter().map(Self::convert))
+                    .collect(),
+            ),
+            value if value.dtype().is_numeric() => {
+                Self::Number(any_value_into_string(value.clone()))
+            }
+            value => Self::String(any_value_into_string(value.clone())),
+        }
+    }
+
+    /// Indented lines of the value, with colored keys and literals, broken to fit in
+    /// `width` columns.
+    pub fn lines(&self, width: usize, theme: &dyn Styler) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        self.write(&mut lines, Vec::new(), 0, false, theme);
+        lines
+            .into_iter()
+            .flat_map(|line| break_line(line, width.max(1)))
+            .collect()
+    }
+
+    /// Writes the value after the `prefix` of its first line, which holds the
+    /// indentation and the key in an object.
+    fn write(
+        &self,
+        lines: &mut Vec<Line<'static>>,
+        mut prefix: Vec<Span<'static>>,
+        depth: usize,
+        comma: bool,
+        theme: &dyn Styler,
+    ) {
+        let comma = if comma { "," } else { "" };
+        let (open, close, children) = match self {
+            Self::Array(values) if !values.is_empty() => (
+                "[",
+                "]",
+                values
+                    .iter()
+                    .map(|value| (Vec::new(), value))
+                    .collect::<Vec<_>>(),
+            ),
+            Self::Object(fields) if !fields.is_empty() => (
+                "{",
+                "}",
+                fields
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            vec![
+                                Span::styled(format!("{:?}", key), theme.table_header_cell(depth)),
+                                Span::raw(": "),
+                            ],
+                            value,
+                        )
+                    })
+                    .collect(),
+            ),
+            scalar => {
+                let span = match scalar {
+                    Self::Null => Span::styled("null", theme.json_literal()),
+                    Self::Bool(value) => Span::styled(value.to_string(), theme.json_literal()),
+                    Self::Number(value) => Span::styled(value.clone(), theme.json_literal()),
+                    Self::String(value) => {
+                        Span::styled(format!("{:?}", value), theme.json_string())
+                    }
+                    Self::Array(_) => Span::raw("[]"),
+                    Self::Object(_) => Span::raw("{}"),
+                };
+                prefix.extend([span, Span::raw(comma)]);
+                lines.push(Line::from(prefix));
+                return;
+            }
+        };
+
+        prefix.push(Span::raw(open));
+        lines.push(Line::from(prefix));
+        let count = children.len();
+        for (idx, (key, value)) in children.into_iter().enumerate() {
+            let mut prefix = vec![Span::raw("  ".repeat(depth + 1))];
+            prefix.extend(key);
+            value.write(lines, prefix, depth + 1, idx + 1 < count, theme);
+        }
+        lines.push(Line::from(format!(
+            "{}{}{}",
+            "  ".repeat(depth),
+            close,
+            comma
+        )));
+    }
+}
+
+/// Breaks `line` every `width` columns, keeping the styles of its spans.
+fn break_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+    let mut used = 0;
+    for span in line.spans {
+        let mut content = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if used + char_width > width && used > 0 {
+                let last = lines.last_mut().unwrap();
+                last.spans
+                    .push(Span::styled(std::mem::take(&mut content), span.style));
+                lines.push(Line::default());
+                used = 0;
+            }
+            content.push(c);
+            used += char_width;
+        }
+        lines
+            .last_mut()
+            .unwrap()
+            .spans
+            .push(Span::styled(content, span.style));
+    }
+    lines
+}
+
+/// Recursive descent parser of JSON values.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_whitespace();
+        self.chars.next_if_eq(&expected).map(|_| ())
+    }
+
+    fn value(&mut self) -> Option<Nested> {
+        self.skip_whitespace();
+        match self.chars.peek()? {
+            '{' => self.object(),
+            '[' => self.array(),
+            '"' => self.string().map(Nested::String),
+            't' => self.literal("true", Nested::Bool(true)),
+            'f' => self.literal("false", Nested::Bool(false)),
+            'n' => self.literal("null", Nested::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<Nested> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        if self.expect('}').is_some() {
+            return Some(Nested::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            if self.expect('}').is_some() {
+                return Some(Nested::Object(fields));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Option<Nested> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        if self.expect(']').is_some() {
+            return Some(Nested::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            if self.expect(']').is_some() {
+                return Some(Nested::Array(values));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(string),
+                '\\' => string.push(match self.chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => {
+                        let code = (0..4)
+                            .map(|_| self.chars.next())
+                            .collect::<Option<String>>()?;
+                        char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+                            .unwrap_or(char::REPLACEMENT_CHARACTER)
+                    }
+                    c @ ('"' | '\\' | '/') => c,
+                    _ => return None,
+                }),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Nested> {
+        let mut number = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c);
+        }
+        number.parse::<f64>().ok().map(|_| Nested::Number(number))
+    }
+
+    fn literal(&mut self, word: &str, value: Nested) -> Option<Nested> {
+        word.chars()
+            .all(|c| self.chars.next_if_eq(&c).is_some())
+            .then_some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::theme::Terminal;
+
+    fn text(value: &Nested, width: usize) -> Vec<String> {
+        value
+            .lines(width, &Terminal)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_from_json() {
+        let value = Nested::from_json(
+            r#" {"id": 7, "tags": ["a", "b\"c"], "meta": {}, "ok": true, "none": null} "#,
+        )
+        .unwrap();
+        assert_eq!(
+            text(&value, 80),
+            [
+                "{",
+                "  \"id\": 7,",
+                "  \"tags\": [",
+                "    \"a\",",
+                "    \"b\\\"c\"",
+                "  ],",
+                "  \"meta\": {},",
+                "  \"ok\": true,",
+                "  \"none\": null",
+                "}",
+            ]
+        );
+        assert_eq!(Nested::from_json("\"plain\""), None);
+        assert_eq!(Nested::from_json("[1, 2"), None);
+        assert_eq!(Nested::from_json("{\"a\": 1} trailing"), None);
+        assert_eq!(
+            Nested::from_json("[-1.5e3, \"\\u00e9\"]"),
+            Some(Nested::Array(vec![
+                Nested::Number("-1.5e3".to_owned()),
+                Nested::String("é".to_owned())
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_from_any_value() {
+        let df = df!("id" => [1, 2], "name" => ["a", "b"]).unwrap();
+        let structs = df.into_struct("row").into_series();
+        assert_eq!(
+            Nested::from_any_value(&structs.get(1).unwrap()),
+            Some(Nested::Object(vec![
+                ("id".to_owned(), Nested::Number("2".to_owned())),
+                ("name".to_owned(), Nested::String("b".to_owned())),
+            ]))
+        );
+        let list = AnyValue::List(Series::new("", [1, 2]));
+        assert_eq!(
+            text(&Nested::from_any_value(&list).unwrap(), 3),
+            ["[", "  1", ",", "  2", "]"]
+        );
+        assert_eq!(Nested::from_any_value(&AnyValue::Int32(1)), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::utils::config_dir;
+
+/// Named SQL queries, kept one `name = query` per line in a file.
+#[derive(Debug, Default)]
+pub struct SavedQueries {
+    queries: BTreeMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+impl SavedQueries {
+    /// Loads the queries from `queries` in the config directory.
+    pub fn load() -> io::Result<Self> {
+        let dir = config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        Self::from_file(dir.join("queries"))
+    }
+
+    /// Reads the queries from `path`, which may not exist yet.
+    pub fn from_file(path: PathBuf) -> io::Result<Self> {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let queries = content
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(name, query)| (name.trim().to_owned(), query.to_owned()))
+            .collect();
+        Ok(Self {
+            queries,
+            path: Some(path),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.queries.get(name).map(String::as_str)
+    }
+
+    /// Names of the queries in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.queries.keys().map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, query: impl Into<String>) {
+        // queries are stored one per line
+        let query = query
+            .into()
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.queries.insert(name.into(), query);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::File::create(path)?;
+        for (name, query) in &self.queries {
+            writeln!(file, "{} = {}", name, query)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_queries_round_trip() {
+        let path = std::env::temp_dir().join(format!("tabiew-queries-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut queries = SavedQueries::from_file(path.clone()).unwrap();
+        queries.insert("top", "SELECT *\nFROM df\nLIMIT 10");
+        queries.insert("eq", "SELECT * FROM df WHERE a = 1");
+        queries.save().unwrap();
+
+        let queries = SavedQueries::from_file(path.clone()).unwrap();
+        assert_eq!(queries.names().collect::<Vec<_>>(), ["eq", "top"]);
+        assert_eq!(queries.get("top"), Some("SELECT * FROM df LIMIT 10"));
+        assert_eq!(queries.get("eq"), Some("SELECT * FROM df WHERE a = 1"));
+        assert_eq!(queries.get("missing"), None);
+        fs::remove_file(path).unwrap();
+    }
+}
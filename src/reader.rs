@@ -0,0 +1,1829 @@
+mod compression;
+mod encoding;
+mod sqlite;
+mod xlsx;
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::{Debug, Display},
+    fs::{self, File},
+    io::{self, BufRead, Cursor, IsTerminal, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use polars::{
+    frame::DataFrame,
+    io::{
+        csv::read::{CommentPrefix, CsvEncoding, CsvParseOptions, CsvReadOptions, NullValues},
+        mmap::MmapBytesReader,
+        SerReader, SerWriter,
+    },
+    lazy::frame::{LazyCsvReader, LazyFileListReader, LazyFrame, ScanArgsParquet},
+    prelude::{
+        col, len, CsvWriter, IdxCa, IdxSize, IpcReader, IpcWriter, JsonFormat, JsonLineReader,
+        JsonReader, JsonWriter, ParquetReader, ParquetWriter,
+    },
+};
+
+use crate::{
+    app::AppResult,
+    args::{Args, Encoding, FileFormat, InferSchema},
+    utils::{flatten, infer_schema_safe, infer_temporal_safe},
+};
+
+use self::{
+    compression::{uncompressed_path, Compression},
+    encoding::{Decoder, SingleByte},
+    sqlite::Database,
+    xlsx::{grid_into_data_frame, Workbook},
+};
+
+/// Seekable input handed to the loaders, either an opened file or buffered stdin.
+pub type Input = Box<dyn MmapBytesReader>;
+
+/// Origin of a data frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    File(PathBuf),
+    /// Named pipe or other file that can only be read once, as a process substitution.
+    Pipe(PathBuf),
+    Url(String),
+    Stdin,
+}
+
+impl Source {
+    /// Sources given in the arguments, where `-` stands for stdin and HTTP(S) URLs are
+    /// fetched. Defaults to stdin if no file is given, which can only be read once.
+    pub fn from_args(args: &Args) -> AppResult<Vec<Source>> {
+        Self::from_args_with(args, io::stdin().is_terminal())
+    }
+
+    /// Same as [`Source::from_args`], failing rather than waiting on the keyboard when no
+    /// file is given and stdin is a terminal.
+    pub fn from_args_with(args: &Args, stdin_is_terminal: bool) -> AppResult<Vec<Source>> {
+        if args.file_name.is_empty() {
+            if stdin_is_terminal {
+                return Err("no file given, and stdin is a terminal rather than a pipe".into());
+            }
+            return Ok(vec![Source::Stdin]);
+        }
+        let stdin = args
+            .file_name
+            .iter()
+            .filter(|path| path.to_str() == Some("-"));
+        if stdin.count() > 1 {
+            return Err("stdin can only be read once, '-' is given more than once".into());
+        }
+        Ok(args
+            .file_name
+            .iter()
+            .map(|path| {
+                match path.to_str() {
+                    Some("-") => Source::Stdin,
+                    Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                        Source::Url(url.to_owned())
+                    }
+                    _ if is_pipe(path) => Source::Pipe(path.to_owned()),
+                    _ => Source::File(path.to_owned()),
+                }
+            })
+            .collect())
+    }
+
+    /// Path of the file, which can be read again, unlike pipes.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Source::File(path) => Some(path),
+            Source::Pipe(_) | Source::Url(_) | Source::Stdin => None,
+        }
+    }
+
+    /// Short name for display, i.e. the file name without its directory.
+    pub fn name(&self) -> String {
+        match self {
+            Source::File(path) | Source::Pipe(path) => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            Source::Url(url) => url_path(url)
+                .rsplit('/')
+                .find(|segment| !segment.is_empty())
+                .unwrap_or(url)
+                .to_owned(),
+            Source::Stdin => "stdin".to_owned(),
+        }
+    }
+
+    /// Name of the file without its extension as an SQL identifier, e.g. `customers`
+    /// from `customers.csv` or `customers.csv.gz`, or `sales_2024` from
+    /// `sales-2024.csv`. Characters other
+    /// than letters, digits and `_` become `_`, and names starting with a digit get a
+    /// leading `_`.
+    pub fn stem_name(&self) -> Option<String> {
+        if matches!(self, Source::Stdin) {
+            return None;
+        }
+        let name = uncompressed_path(Path::new(&self.name()));
+        let stem = name.file_stem()?.to_string_lossy();
+        let mut identifier = stem
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+            identifier.insert(0, '_');
+        }
+        (!identifier.is_empty()).then_some(identifier)
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::File(path) | Source::Pipe(path) => write!(f, "{}", path.display()),
+            Source::Url(url) => write!(f, "{}", url),
+            Source::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Data frame read from a source.
+#[derive(Debug)]
+pub struct Table {
+    /// Name of the table within the source, for sources holding several tables such as
+    /// databases.
+    pub name: Option<String>,
+    pub data_frame: DataFrame,
+    /// Scan of the file with `--lazy`, `data_frame` then only holds its first rows.
+    pub lazy: Option<LazySource>,
+    /// File the table can be written back to with `:write`.
+    pub file: Option<SourceFile>,
+    /// Rows of a CSV file with a wrong number of fields, checked with `--strict`.
+    pub malformed: Option<MalformedRows>,
+    /// How the rows were cut with `--head` or `--sample`, if some were left out.
+    pub truncation: Option<Truncation>,
+    /// Columns named like an earlier one, as pairs of their name in the file and the
+    /// name they were given.
+    pub renamed: Vec<(String, String)>,
+}
+
+/// Where a table was read from, to read it again with `:reload`.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub source: Source,
+    /// Name of the table within the source, for sources holding several tables.
+    pub table: Option<String>,
+    pub args: Arc<Args>,
+}
+
+impl Origin {
+    /// Reads the table again from its source, with all its columns. The standard input
+    /// and named pipes cannot be read again.
+    pub fn read(&self) -> AppResult<Table> {
+        if matches!(self.source, Source::Stdin | Source::Pipe(_)) {
+            return Err(format!("{} cannot be read again", self.source).into());
+        }
+        let mut args = Args::clone(&self.args);
+        args.columns.clear();
+        read_tables(&self.source, &args)?
+            .into_iter()
+            .find(|table| table.name == self.table)
+            .ok_or_else(|| {
+                let name = self.table.as_deref().unwrap_or_default();
+                format!("{} no longer has the table '{}'", self.source, name).into()
+            })
+    }
+}
+
+/// Rows loaded out of more with `--head` or `--sample`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    Head(usize),
+    Sample(usize),
+}
+
+impl Display for Truncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Truncation::Head(rows) => write!(f, "first {} rows", rows),
+            Truncation::Sample(rows) => write!(f, "sample of {} rows", rows),
+        }
+    }
+}
+
+/// File a table was read from, with the options to write it back in the same format.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub format: FileFormat,
+    pub separator: u8,
+    pub quote_char: u8,
+    /// Character starting the comment lines, which are skipped.
+    pub comment_char: Option<u8>,
+    /// Text of the null values, the first of `--null-value`, empty by default.
+    pub null_value: Option<String>,
+    pub has_header: bool,
+    /// Whether `:write` warned that a CSV file is written from the values as read.
+    pub warned: bool,
+}
+
+impl SourceFile {
+    /// File of the source if it is in a format that can be written back: a UTF-8 CSV,
+    /// Parquet, JSON, JSON lines, or an Arrow file that is not memory-mapped, and not
+    /// compressed.
+    fn new(source: &Source, format: &FileFormat, args: &Args) -> AppResult<Option<Self>> {
+        let Source::File(path) = source else {
+            return Ok(None);
+        };
+        if Compression::of_file(path).is_some() {
+            return Ok(None);
+        }
+        let separator = match format {
+            FileFormat::Csv if aligned(args) => return Ok(None),
+            FileFormat::Csv if matches!(args.encoding, Encoding::Utf8) => {
+                csv_separator(&mut open_input(source, args)?, source, args)?
+            }
+            FileFormat::Parquet | FileFormat::Json | FileFormat::Ndjson => b',',
+            FileFormat::Arrow if !args.mmap => b',',
+            _ => return Ok(None),
+        };
+        Ok(Some(Self {
+            path: path.clone(),
+            format: format.clone(),
+            separator,
+            quote_char: args.quote_char as u8,
+            comment_char: args.comment_char.map(|c| c as u8),
+            null_value: args.null_values.first().cloned(),
+            has_header: !args.no_header,
+            warned: false,
+        }))
+    }
+
+    /// Writes `data_frame` to a temporary file next to the source, then moves it over
+    /// the source so that a failed write leaves it as it was. The comment lines at the
+    /// top of a CSV file are kept, and it fails if there are others, between the rows,
+    /// which would be lost.
+    pub fn write(&self, data_frame: &mut DataFrame) -> AppResult<()> {
+        let comments = match (&self.format, self.comment_char) {
+            (FileFormat::Csv, Some(comment_char)) => {
+                leading_comments(&fs::read_to_string(&self.path)?, comment_char)?
+            }
+            _ => Vec::new(),
+        };
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tw-write");
+        let temporary = PathBuf::from(temporary);
+        let written = (|| -> AppResult<()> {
+            let mut file = File::create(&temporary)?;
+            match self.format {
+                FileFormat::Csv => {
+                    for line in &comments {
+                        writeln!(file, "{}", line)?;
+                    }
+                    CsvWriter::new(file)
+                        .with_separator(self.separator)
+                        .with_quote_char(self.quote_char)
+                        .with_null_value(self.null_value.clone().unwrap_or_default())
+                        .include_header(self.has_header)
+                        .finish(data_frame)?
+                }
+                FileFormat::Parquet => {
+                    ParquetWriter::new(file).finish(data_frame)?;
+                }
+                FileFormat::Json => JsonWriter::new(file)
+                    .with_json_format(JsonFormat::Json)
+                    .finish(data_frame)?,
+                FileFormat::Ndjson => JsonWriter::new(file)
+                    .with_json_format(JsonFormat::JsonLines)
+                    .finish(data_frame)?,
+                FileFormat::Arrow => IpcWriter::new(file).finish(data_frame)?,
+                FileFormat::Excel | FileFormat::Sqlite => {
+                    return Err("writing this format is not supported".into())
+                }
+            }
+            Ok(())
+        })();
+        match written.and_then(|_| Ok(fs::rename(&temporary, &self.path)?)) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let _ = fs::remove_file(&temporary);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Comment lines at the top of the CSV `content`, before the header or the first row.
+/// Fails if comment lines come later too.
+fn leading_comments(content: &str, comment_char: u8) -> AppResult<Vec<String>> {
+    let is_comment = |line: &&str| line.as_bytes().first() == Some(&comment_char);
+    let mut lines = content.lines();
+    let comments = lines
+        .by_ref()
+        .take_while(is_comment)
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if lines.any(|line| is_comment(&line)) {
+        return Err(
+            "the file has comment lines between the rows, which writing would drop, use :export"
+                .into(),
+        );
+    }
+    Ok(comments)
+}
+
+/// File scanned on demand rather than read into memory.
+#[derive(Clone)]
+pub struct LazySource {
+    pub frame: LazyFrame,
+    pub height: usize,
+}
+
+impl Debug for LazySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazySource")
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Loads the tables of the source. Databases yield the table selected in the arguments, or
+/// all of their tables if none is selected, while every other format yields a single
+/// table. Errors are prefixed with the name of the source.
+pub fn read_tables(source: &Source, args: &Args) -> AppResult<Vec<Table>> {
+    let load = || {
+        if args.lazy {
+            if let Some(table) = scan_table(source, args)? {
+                return Ok(vec![table]);
+            }
+        }
+        let (mut input, content_type) = match source {
+            Source::Url(url) => {
+                let (body, content_type) = fetch_url(url, args.timeout)?;
+                (decompress(Box::new(Cursor::new(body)))?, content_type)
+            }
+            _ => (open_input(source, args)?, None),
+        };
+        let format = match (&args.format, source) {
+            (Some(format), _) => format.clone(),
+            (None, Source::File(path)) => detect_format(path).unwrap_or(FileFormat::Csv),
+            (None, Source::Url(url)) => format_from_content(&peek(&mut input)?)
+                .or_else(|| content_type.as_deref().and_then(format_from_content_type))
+                .or_else(|| format_from_extension(Path::new(url_path(url))))
+                .unwrap_or(FileFormat::Csv),
+            (None, Source::Pipe(path)) => format_from_content(&peek(&mut input)?)
+                .or_else(|| format_from_extension(path))
+                .unwrap_or(FileFormat::Csv),
+            (None, Source::Stdin) => {
+                format_from_content(&peek(&mut input)?).unwrap_or(FileFormat::Csv)
+            }
+        };
+        // `:write` checks the projection of the table for the columns
+        let file = SourceFile::new(source, &format, args)?;
+        let malformed = match format {
+            FileFormat::Csv if args.strict && !aligned(args) => {
+                Some(check_csv(&mut input, source, args)?).filter(|malformed| malformed.count > 0)
+            }
+            _ => None,
+        };
+        let data_frame = match format {
+            // the malformed rows likely are why the file does not load
+            FileFormat::Csv => load_csv(input, source, args).map_err(|err| match &malformed {
+                Some(malformed) => {
+                    format!("{}, {}", first_line(&err.to_string()), malformed).into()
+                }
+                None => err,
+            }),
+            FileFormat::Parquet => load_parquet(input, args),
+            FileFormat::Json => load_json(input),
+            FileFormat::Ndjson => load_json_lines(input),
+            FileFormat::Arrow => load_ipc(input, source, args),
+            FileFormat::Excel => load_excel(input, args),
+            FileFormat::Sqlite => return load_sqlite(input, args),
+        }?;
+        let (data_frame, renamed) = deduplicate_columns(data_frame, args)?;
+        // written back, the file would get the new names
+        let file = file.filter(|_| renamed.is_empty());
+        let (data_frame, truncation) = truncate(select_columns(data_frame, args)?, args)?;
+        // written back, a table of some of the rows would lose the others
+        let file = file.filter(|_| truncation.is_none());
+        let (data_frame, file) = match args.flatten {
+            true => {
+                let flat = flatten(data_frame.clone(), args.flatten_depth, args.explode)?;
+                // written back, a flattened table would lose its nesting
+                let file = file.filter(|_| flat.schema() == data_frame.schema());
+                (flat, file)
+            }
+            false => (data_frame, file),
+        };
+        Ok(vec![Table {
+            name: None,
+            data_frame,
+            lazy: None,
+            file,
+            malformed,
+            truncation,
+            renamed,
+        }])
+    };
+    load().map_err(|err| format!("{}: {}", source, first_line(&err.to_string())).into())
+}
+
+/// Scans a CSV or Parquet file for `--lazy`, counting its rows and collecting the first
+/// ones. Other sources, and compressed files or CSV files in a single byte encoding
+/// which must be decoded first, are not scanned and give `None`.
+fn scan_table(source: &Source, args: &Args) -> AppResult<Option<Table>> {
+    let Source::File(path) = source else {
+        return Ok(None);
+    };
+    if Compression::of_file(path).is_some() {
+        return Ok(None);
+    }
+    let format = match &args.format {
+        Some(format) => format.clone(),
+        None => detect_format(path).unwrap_or(FileFormat::Csv),
+    };
+    let mut malformed = None;
+    let frame = match (format, &args.encoding) {
+        (FileFormat::Csv, _) if aligned(args) => return Ok(None),
+        (FileFormat::Csv, Encoding::Utf8 | Encoding::Utf8Lossy) => {
+            let mut input = open_input(source, args)?;
+            let separator = csv_separator(&mut input, source, args)?;
+            if args.strict {
+                malformed = Some(check_csv(&mut input, source, args)?)
+                    .filter(|malformed| malformed.count > 0);
+            }
+            LazyCsvReader::new(path)
+                .with_ignore_errors(args.ignore_errors)
+                .with_infer_schema_length((&args.infer_schema).into())
+                .with_has_header(!args.no_header)
+                .with_quote_char((args.quote_char as u8).into())
+                .with_comment_prefix(args.comment_char.map(String::from).as_deref())
+                .with_separator(separator)
+                .with_encoding(match args.encoding {
+                    Encoding::Utf8Lossy => CsvEncoding::LossyUtf8,
+                    _ => CsvEncoding::Utf8,
+                })
+                .with_null_values(
+                    (!args.null_values.is_empty())
+                        .then(|| NullValues::AllColumns(args.null_values.clone())),
+                )
+                .finish()?
+        }
+        (FileFormat::Parquet, _) => LazyFrame::scan_parquet(path, ScanArgsParquet::default())?,
+        _ => return Ok(None),
+    };
+    let schema = frame.clone().schema()?;
+    let names = schema
+        .iter_names()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let mut new_names = names.clone();
+    let renamed = rename_duplicates(&mut new_names);
+    if args.strict && !renamed.is_empty() {
+        return Err(duplicates_error(&renamed).into());
+    }
+    let (existing, new): (Vec<_>, Vec<_>) = names
+        .into_iter()
+        .zip(new_names)
+        .filter(|(name, new_name)| name != new_name)
+        .unzip();
+    let frame = frame.rename(existing, new);
+    let frame = match args.columns.is_empty() {
+        true => frame,
+        false => {
+            let schema = frame.clone().schema()?;
+            check_columns(&args.columns, schema.iter_names().map(|name| name.as_str()))?;
+            let columns = args.columns.iter().map(|name| col(name));
+            frame.select(columns.collect::<Vec<_>>())
+        }
+    };
+    if args.head.is_some() || args.sample.is_some() {
+        let (data_frame, truncation) = truncate_scan(frame, args)?;
+        return Ok(Some(Table {
+            name: None,
+            data_frame,
+            lazy: None,
+            file: None,
+            malformed,
+            truncation,
+            renamed,
+        }));
+    }
+    let height = scan_height(&frame)?;
+    let data_frame = frame
+        .clone()
+        .slice(0, args.page_size.max(1) as IdxSize)
+        .collect()?;
+    Ok(Some(Table {
+        name: None,
+        data_frame,
+        lazy: Some(LazySource { frame, height }),
+        file: None,
+        malformed,
+        truncation: None,
+        renamed,
+    }))
+}
+
+/// Whether the file at `path` is a pipe, a device or a socket rather than a regular file,
+/// which cannot be seeked.
+fn is_pipe(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.is_file() && !metadata.is_dir())
+}
+
+/// Opens the input file, or spills a pipe, stdin or the fetched URL into memory so that
+/// it can be seeked. Compressed inputs are decompressed into memory.
+pub fn open_input(source: &Source, args: &Args) -> AppResult<Input> {
+    let input: Input = match source {
+        Source::File(path) => Box::new(File::open(path).map_err(describe_io_error)?),
+        Source::Pipe(path) => {
+            let mut buf = Vec::new();
+            File::open(path)
+                .and_then(|mut file| file.read_to_end(&mut buf))
+                .map_err(describe_io_error)?;
+            Box::new(Cursor::new(buf))
+        }
+        Source::Url(url) => Box::new(Cursor::new(fetch_url(url, args.timeout)?.0)),
+        Source::Stdin => {
+            let mut buf = Vec::new();
+            io::stdin()
+                .read_to_end(&mut buf)
+                .map_err(describe_io_error)?;
+            Box::new(Cursor::new(buf))
+        }
+    };
+    decompress(input)
+}
+
+/// Decompresses the input into memory if it starts with the magic bytes of gzip or zstd.
+fn decompress(mut input: Input) -> AppResult<Input> {
+    let Some(compression) = Compression::from_content(&peek(&mut input)?) else {
+        return Ok(input);
+    };
+    let mut content = Vec::new();
+    compression
+        .decoder(input)?
+        .read_to_end(&mut content)
+        .map_err(|err| format!("could not decompress the input, {}", err))?;
+    Ok(Box::new(Cursor::new(content)))
+}
+
+/// Detects the format of the file from its first bytes, decompressed if need be,
+/// falling back to its extension when the content is inconclusive. JSON content is read
+/// as JSON lines if the extension says so.
+pub fn detect_format(path: &Path) -> Option<FileFormat> {
+    let mut head = Vec::with_capacity(PEEK_SIZE);
+    File::open(path)
+        .and_then(|file| match Compression::of_file(path) {
+            Some(compression) => compression.decoder(file),
+            None => Ok(Box::new(file)),
+        })
+        .and_then(|input| input.take(PEEK_SIZE as u64).read_to_end(&mut head))
+        .ok();
+    let extension = format_from_extension(path);
+    match format_from_content(&head) {
+        Some(FileFormat::Json) if matches!(extension, Some(FileFormat::Ndjson)) => extension,
+        Some(format) => Some(format),
+        None => extension,
+    }
+}
+
+const PEEK_SIZE: usize = 64;
+
+/// Format identified by the magic bytes at the start of the content, or JSON if the content
+/// starts with an object or an array.
+pub fn format_from_content(head: &[u8]) -> Option<FileFormat> {
+    if head.starts_with(b"PAR1") {
+        Some(FileFormat::Parquet)
+    } else if head.starts_with(b"ARROW1") {
+        Some(FileFormat::Arrow)
+    } else if head.starts_with(b"SQLite format 3\0") {
+        Some(FileFormat::Sqlite)
+    } else if head.starts_with(b"PK\x03\x04") || head.starts_with(&[0xd0, 0xcf, 0x11, 0xe0]) {
+        Some(FileFormat::Excel)
+    } else {
+        let text = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+        match text.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{' | b'[') => Some(FileFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Format implied by the Content-Type of an HTTP response.
+pub fn format_from_content_type(content_type: &str) -> Option<FileFormat> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "text/csv" | "text/tab-separated-values" => Some(FileFormat::Csv),
+        "application/json" => Some(FileFormat::Json),
+        "application/x-ndjson" | "application/jsonl" => Some(FileFormat::Ndjson),
+        "application/vnd.apache.parquet" | "application/x-parquet" => Some(FileFormat::Parquet),
+        "application/vnd.apache.arrow.file" => Some(FileFormat::Arrow),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        | "application/vnd.ms-excel" => Some(FileFormat::Excel),
+        "application/vnd.sqlite3" | "application/x-sqlite3" => Some(FileFormat::Sqlite),
+        _ => None,
+    }
+}
+
+/// Downloads the URL with curl into memory and returns the body along with the
+/// Content-Type of the response, if any.
+#[cfg(feature = "network")]
+fn fetch_url(url: &str, timeout: Option<u64>) -> AppResult<(Vec<u8>, Option<String>)> {
+    let mut command = std::process::Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--dump-header", "-", "--output", "-"]);
+    if let Some(timeout) = timeout {
+        command.args(["--max-time", &timeout.to_string()]);
+    }
+    let output = command.arg(url).output().map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => "curl is required to open URLs but was not found".into(),
+        _ => Box::<dyn Error>::from(err),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let msg = stderr.trim();
+        let msg = msg
+            .strip_prefix("curl: (")
+            .and_then(|rest| rest.split_once(") "))
+            .map(|(_, msg)| msg)
+            .unwrap_or(msg);
+        return Err(format!("failed to fetch the URL, {}", msg).into());
+    }
+    Ok(split_response(output.stdout))
+}
+
+#[cfg(not(feature = "network"))]
+fn fetch_url(_: &str, _: Option<u64>) -> AppResult<(Vec<u8>, Option<String>)> {
+    Err("opening URLs needs tw built with the network feature, `--features network`, and curl installed".into())
+}
+
+/// Splits the output of curl with `--dump-header -` into the body and the Content-Type
+/// of the last response, the headers of the redirects and of the response coming first.
+#[cfg(feature = "network")]
+fn split_response(mut output: Vec<u8>) -> (Vec<u8>, Option<String>) {
+    let mut content_type = None;
+    let mut start = 0;
+    while output[start..].starts_with(b"HTTP/") {
+        let Some(end) = output[start..]
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+        else {
+            break;
+        };
+        let headers = String::from_utf8_lossy(&output[start..start + end]);
+        content_type = headers
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.trim().to_owned())
+            .filter(|value| !value.is_empty());
+        start += end + 4;
+    }
+    (output.split_off(start), content_type)
+}
+
+fn url_path(url: &str) -> &str {
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    url.split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|idx| &rest[idx..]))
+        .unwrap_or("")
+}
+
+fn peek(input: &mut Input) -> AppResult<Vec<u8>> {
+    let mut head = Vec::with_capacity(PEEK_SIZE);
+    input.by_ref().take(PEEK_SIZE as u64).read_to_end(&mut head)?;
+    input.seek(SeekFrom::Start(0))?;
+    Ok(head)
+}
+
+/// Format implied by the extension of the path, the one before the compression suffix
+/// for a compressed file as `data.csv.gz`.
+pub fn format_from_extension(path: &Path) -> Option<FileFormat> {
+    match uncompressed_path(path).extension()?.to_str()? {
+        "csv" | "tsv" => Some(FileFormat::Csv),
+        "parquet" => Some(FileFormat::Parquet),
+        "json" => Some(FileFormat::Json),
+        "ndjson" | "jsonl" => Some(FileFormat::Ndjson),
+        "arrow" | "ipc" | "feather" => Some(FileFormat::Arrow),
+        "xlsx" | "xls" => Some(FileFormat::Excel),
+        "sqlite" | "sqlite3" | "db" => Some(FileFormat::Sqlite),
+        _ => None,
+    }
+}
+
+/// Reads a Parquet file, only the columns of `--columns` if any.
+pub fn load_parquet(input: Input, args: &Args) -> AppResult<DataFrame> {
+    let mut reader = ParquetReader::new(input);
+    if !args.columns.is_empty() {
+        let schema = reader.schema()?;
+        let names = schema.fields.iter().map(|field| field.name.as_str());
+        check_columns(&args.columns, names)?;
+        reader = reader.with_columns(Some(args.columns.clone()));
+    }
+    Ok(reader.with_n_rows(head_rows(args)).finish()?)
+}
+
+/// Reads a JSON document, or JSON lines if the content holds several objects one after
+/// the other.
+pub fn load_json(mut input: Input) -> AppResult<DataFrame> {
+    let mut content = Vec::new();
+    input.read_to_end(&mut content)?;
+    let input = Box::new(Cursor::new(content));
+    if several_json_values(input.get_ref()) {
+        load_json_lines(input)
+    } else {
+        Ok(JsonReader::new(input).finish()?)
+    }
+}
+
+/// Reads one JSON object per line. The schema is inferred from all the lines, so a key
+/// missing from some of them gives nulls.
+pub fn load_json_lines(input: Input) -> AppResult<DataFrame> {
+    Ok(JsonLineReader::new(input).infer_schema_len(None).finish()?)
+}
+
+/// Whether `content` goes on after its first JSON object or array is closed.
+fn several_json_values(content: &[u8]) -> bool {
+    let mut depth = 0usize;
+    let mut closed = false;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in content {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match byte {
+            byte if byte.is_ascii_whitespace() => (),
+            _ if closed => return true,
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                closed = depth == 0;
+            }
+            _ => (),
+        }
+    }
+    false
+}
+
+pub fn load_ipc(input: Input, source: &Source, args: &Args) -> AppResult<DataFrame> {
+    let mut reader = IpcReader::new(input);
+    if !args.columns.is_empty() {
+        let schema = reader.schema()?;
+        let names = schema.fields.iter().map(|field| field.name.as_str());
+        check_columns(&args.columns, names)?;
+        reader = reader.with_columns(Some(args.columns.clone()));
+    }
+    Ok(reader
+        .with_n_rows(head_rows(args))
+        .memory_mapped(
+            source
+                .path()
+                .filter(|path| args.mmap && Compression::of_file(path).is_none())
+                .map(ToOwned::to_owned),
+        )
+        .finish()?)
+}
+
+pub fn load_csv(mut input: Input, source: &Source, args: &Args) -> AppResult<DataFrame> {
+    if aligned(args) {
+        let csv = split_aligned(&mut input, args)?;
+        let args = Args {
+            separator: Some(','),
+            quote_char: '"',
+            comment_char: None,
+            whitespace: false,
+            fixed_width: Vec::new(),
+            ..args.clone()
+        };
+        return load_csv(Box::new(Cursor::new(csv)), source, &args);
+    }
+    // an empty file is a table without columns rather than an error
+    if peek(&mut input)?.is_empty() {
+        return Ok(DataFrame::empty());
+    }
+    let separator = csv_separator(&mut input, source, args)?;
+    let mut input = match args.encoding {
+        Encoding::Latin1 => decode_input(input, SingleByte::Latin1)?,
+        Encoding::Windows1252 => decode_input(input, SingleByte::Windows1252)?,
+        Encoding::Utf8 | Encoding::Utf8Lossy => input,
+    };
+    let options = CsvReadOptions::default()
+        .with_n_rows(head_rows(args))
+        .with_ignore_errors(args.ignore_errors)
+        .with_infer_schema_length((&args.infer_schema).into())
+        .with_has_header(!args.no_header)
+        .with_parse_options(
+            CsvParseOptions::default()
+                .with_quote_char((args.quote_char as u8).into())
+                .with_comment_prefix(args.comment_char.map(|c| CommentPrefix::Single(c as u8)))
+                .with_separator(separator)
+                .with_encoding(match args.encoding {
+                    Encoding::Utf8Lossy => CsvEncoding::LossyUtf8,
+                    _ => CsvEncoding::Utf8,
+                })
+                .with_null_values(
+                    (!args.null_values.is_empty())
+                        .then(|| NullValues::AllColumns(args.null_values.clone())),
+                ),
+        );
+    let options = match args.columns.is_empty() {
+        true => options,
+        false => {
+            // the header is read first to tell which of the columns are missing
+            let header = options
+                .clone()
+                .with_n_rows(Some(0))
+                .into_reader_with_file_handle(&mut input)
+                .finish()?;
+            input.seek(SeekFrom::Start(0))?;
+            check_columns(&args.columns, header.get_column_names())?;
+            options.with_columns(Some(args.columns.clone().into()))
+        }
+    };
+    let mut df = options.into_reader_with_file_handle(input).finish()?;
+    if matches!(args.infer_schema, InferSchema::Safe) {
+        infer_safe(&mut df, args);
+    }
+    Ok(df)
+}
+
+/// Transcodes the input into UTF-8. The CSV reader needs a seekable input, so the
+/// decoded bytes are kept in memory, but the raw bytes never are.
+fn decode_input(input: Input, encoding: SingleByte) -> AppResult<Input> {
+    let mut data = Vec::new();
+    Decoder::new(input, encoding)
+        .read_to_end(&mut data)
+        .map_err(describe_io_error)?;
+    Ok(Box::new(Cursor::new(data)))
+}
+
+pub fn load_excel(mut input: Input, args: &Args) -> AppResult<DataFrame> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data).map_err(describe_io_error)?;
+    if data.starts_with(&[0xd0, 0xcf, 0x11, 0xe0]) {
+        return Err(
+            "legacy .xls workbooks are not supported, save the workbook as .xlsx instead".into(),
+        );
+    }
+    let workbook = Workbook::new(data)?;
+    let grid = workbook.worksheet(args.sheet.as_deref().unwrap_or("0"), args.range.as_deref())?;
+    let mut df = grid_into_data_frame(
+        grid,
+        !args.no_header,
+        !matches!(args.infer_schema, InferSchema::No | InferSchema::Safe),
+    )?;
+    if matches!(args.infer_schema, InferSchema::Safe) {
+        infer_safe(&mut df, args);
+    }
+    Ok(df)
+}
+
+/// Parses date and datetime columns with the user formats first, so columns like
+/// 20241231 are not taken for integers.
+fn infer_safe(df: &mut DataFrame, args: &Args) {
+    infer_temporal_safe(
+        df,
+        args.date_format.as_deref(),
+        args.datetime_format.as_deref(),
+    );
+    infer_schema_safe(df);
+}
+
+pub fn load_sqlite(input: Input, args: &Args) -> AppResult<Vec<Table>> {
+    let mut database = Database::new(input)?;
+    let tables = database.tables()?;
+    let selected = match &args.table {
+        Some(name) => vec![tables
+            .iter()
+            .find(|table| &table.name == name)
+            .ok_or_else(|| {
+                format!(
+                    "table '{}' not found, available tables: {}",
+                    name,
+                    tables
+                        .iter()
+                        .map(|table| table.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?],
+        None => tables.iter().collect(),
+    };
+    if selected.is_empty() {
+        return Err("database has no tables".into());
+    }
+    selected
+        .into_iter()
+        .map(|table| {
+            let data_frame = select_columns(database.read_table(table)?, args)?;
+            let (data_frame, truncation) = truncate(data_frame, args)?;
+            Ok(Table {
+                name: Some(table.name.clone()),
+                data_frame,
+                lazy: None,
+                file: None,
+                malformed: None,
+                truncation,
+                renamed: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Renames the columns polars read under the name of an earlier column, which it calls
+/// `name_duplicated_0`, `name_duplicated_1`… after the first one, to `name_1`,
+/// `name_2`… instead, skipping the names already taken. Gives the renamed columns as
+/// pairs of their name in the file and their new name.
+pub fn rename_duplicates(names: &mut [String]) -> Vec<(String, String)> {
+    let mut taken = names.iter().cloned().collect::<HashSet<_>>();
+    let mut renamed = Vec::new();
+    for idx in 0..names.len() {
+        let earlier = &names[..idx];
+        let Some((name, _)) = names[idx]
+            .rsplit_once("_duplicated_")
+            .filter(|(name, count)| {
+                count.parse::<usize>().is_ok() && earlier.iter().any(|earlier| earlier == name)
+            })
+        else {
+            continue;
+        };
+        let name = name.to_owned();
+        let new_name = (1..)
+            .map(|count| format!("{}_{}", name, count))
+            .find(|new_name| !taken.contains(new_name))
+            .unwrap_or_default();
+        taken.insert(new_name.clone());
+        names[idx] = new_name.clone();
+        renamed.push((name, new_name));
+    }
+    renamed
+}
+
+/// Renames the duplicated columns of a data frame as [`rename_duplicates`] does, which is
+/// an error with `--strict`.
+fn deduplicate_columns(
+    mut data_frame: DataFrame,
+    args: &Args,
+) -> AppResult<(DataFrame, Vec<(String, String)>)> {
+    let mut names = data_frame
+        .get_column_names()
+        .into_iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let renamed = rename_duplicates(&mut names);
+    if args.strict && !renamed.is_empty() {
+        return Err(duplicates_error(&renamed).into());
+    }
+    data_frame.set_column_names(&names)?;
+    Ok((data_frame, renamed))
+}
+
+fn duplicates_error(renamed: &[(String, String)]) -> String {
+    format!(
+        "duplicate column name(s) {}",
+        renamed
+            .iter()
+            .map(|(name, _)| format!("'{}'", name))
+            .unique()
+            .join(", ")
+    )
+}
+
+/// Checks that the columns of `--columns` are all `available`, listing them otherwise.
+fn check_columns<'a>(
+    columns: &[String],
+    available: impl IntoIterator<Item = &'a str>,
+) -> AppResult<()> {
+    let available = available.into_iter().collect::<Vec<_>>();
+    match columns
+        .iter()
+        .find(|column| !available.contains(&column.as_str()))
+    {
+        Some(column) => Err(format!(
+            "column '{}' not found, available columns: {}",
+            column,
+            available.join(", ")
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Keeps the columns of `--columns` of a data frame read whole, in their order.
+fn select_columns(data_frame: DataFrame, args: &Args) -> AppResult<DataFrame> {
+    if args.columns.is_empty() {
+        return Ok(data_frame);
+    }
+    check_columns(&args.columns, data_frame.get_column_names())?;
+    Ok(data_frame.select(&args.columns)?)
+}
+
+/// Rows to read with `--head`, one more than are kept to tell whether the file has more.
+fn head_rows(args: &Args) -> Option<usize> {
+    args.head.map(|rows| rows.saturating_add(1))
+}
+
+/// Keeps the first rows with `--head`, or a random sample of them with `--sample`, of
+/// a data frame read whole or up to [`head_rows`].
+fn truncate(data_frame: DataFrame, args: &Args) -> AppResult<(DataFrame, Option<Truncation>)> {
+    match (args.head, args.sample) {
+        (Some(rows), _) if data_frame.height() > rows => {
+            Ok((data_frame.head(Some(rows)), Some(Truncation::Head(rows))))
+        }
+        (_, Some(rows)) => match sample_rows(data_frame.height(), rows) {
+            Some(sample) => Ok((data_frame.take(&sample)?, Some(Truncation::Sample(rows)))),
+            None => Ok((data_frame, None)),
+        },
+        _ => Ok((data_frame, None)),
+    }
+}
+
+/// Collects the first rows of a scan with `--head`, reading no further, or a random
+/// sample of its rows with `--sample`.
+fn truncate_scan(frame: LazyFrame, args: &Args) -> AppResult<(DataFrame, Option<Truncation>)> {
+    match args.head {
+        Some(rows) => {
+            let data_frame = frame.limit(rows.saturating_add(1) as IdxSize).collect()?;
+            let truncation = (data_frame.height() > rows).then_some(Truncation::Head(rows));
+            Ok((data_frame.head(Some(rows)), truncation))
+        }
+        None => truncate(frame.collect()?, args),
+    }
+}
+
+/// Indices of `rows` random rows out of `height` in order, `None` if there are no more
+/// than `rows` rows to sample from.
+fn sample_rows(height: usize, rows: usize) -> Option<IdxCa> {
+    if height <= rows {
+        return None;
+    }
+    let mut sample = rand::seq::index::sample(&mut rand::thread_rng(), height, rows)
+        .into_iter()
+        .map(|row| row as IdxSize)
+        .collect::<Vec<_>>();
+    sample.sort_unstable();
+    Some(IdxCa::from_vec("", sample))
+}
+
+/// Number of rows of a scan, counted without collecting them.
+fn scan_height(frame: &LazyFrame) -> AppResult<usize> {
+    frame
+        .clone()
+        .select([len()])
+        .collect()?
+        .get_columns()
+        .first()
+        .and_then(|column| column.get(0).ok()?.extract::<usize>())
+        .ok_or_else(|| "could not count the rows".into())
+}
+
+fn describe_io_error(err: io::Error) -> Box<dyn Error> {
+    match err.kind() {
+        io::ErrorKind::NotFound => "file not found".into(),
+        io::ErrorKind::PermissionDenied => "permission denied".into(),
+        _ => err.into(),
+    }
+}
+
+fn first_line(msg: &str) -> &str {
+    msg.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(msg)
+}
+
+/// Whether the file is a table of aligned columns, whose fields are split on whitespace
+/// with `--whitespace` or at the offsets of `--fixed-width` rather than at a separator.
+fn aligned(args: &Args) -> bool {
+    args.whitespace || !args.fixed_width.is_empty()
+}
+
+/// The fields of a table of aligned columns, trimmed, written as a CSV file to read with
+/// the options and the type inference of CSV files. Blank lines and comment lines are
+/// skipped, and the rows with less fields than others get empty ones.
+pub fn split_aligned(input: &mut impl Read, args: &Args) -> AppResult<Vec<u8>> {
+    let offsets = &args.fixed_width;
+    if offsets.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("the offsets of --fixed-width must increase".into());
+    }
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+    let comment = args.comment_char.map(|c| c as u8);
+    let rows = data
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .filter(|line| comment.is_none() || line.first() != comment.as_ref())
+        .map(|line| match offsets.is_empty() {
+            true => line
+                .split(u8::is_ascii_whitespace)
+                .filter(|field| !field.is_empty())
+                .collect::<Vec<_>>(),
+            false => (0..offsets.len())
+                .map(|idx| {
+                    let end = offsets.get(idx + 1).map_or(line.len(), |end| *end);
+                    let field = line.get(offsets[idx]..end.min(line.len()));
+                    field.unwrap_or_default().trim_ascii()
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let mut csv = Vec::with_capacity(data.len() + rows.len());
+    for (line, row) in rows.iter().enumerate() {
+        for idx in 0..width {
+            if idx > 0 {
+                csv.push(b',');
+            }
+            match row.get(idx).filter(|field| !field.is_empty()) {
+                Some(field) if field.iter().any(|byte| b",\"\r".contains(byte)) => {
+                    csv.push(b'"');
+                    for &byte in field.iter() {
+                        // quotes are doubled
+                        if byte == b'"' {
+                            csv.push(byte);
+                        }
+                        csv.push(byte);
+                    }
+                    csv.push(b'"');
+                }
+                Some(field) => csv.extend_from_slice(field),
+                // the columns without a name are named as the reader does
+                None if line == 0 && !args.no_header => {
+                    csv.extend_from_slice(format!("column_{}", idx + 1).as_bytes())
+                }
+                None => (),
+            }
+        }
+        csv.push(b'\n');
+    }
+    Ok(csv)
+}
+
+/// Separator given in the arguments, a tab for `.tsv` files, or otherwise the one detected
+/// from the first few kilobytes of the input, the comment lines aside. It is checked to
+/// differ from the quote and comment characters.
+fn csv_separator(input: &mut Input, source: &Source, args: &Args) -> AppResult<u8> {
+    let separator = detect_separator(input, source, args)?;
+    let quote = args.quote_char;
+    if quote as u8 == separator {
+        return Err(format!("the quote character '{}' is also the separator", quote).into());
+    }
+    match args.comment_char {
+        Some(comment) if comment as u8 == separator => {
+            Err(format!("the comment character '{}' is also the separator", comment).into())
+        }
+        Some(comment) if comment == quote => {
+            Err(format!("the comment character '{}' is also the quote", comment).into())
+        }
+        _ => Ok(separator),
+    }
+}
+
+fn detect_separator(input: &mut Input, source: &Source, args: &Args) -> AppResult<u8> {
+    if let Some(separator) = args.separator {
+        return Ok(separator as u8);
+    }
+    if source
+        .path()
+        .map(uncompressed_path)
+        .is_some_and(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+    {
+        return Ok(b'\t');
+    }
+    let mut sample = Vec::with_capacity(SNIFF_SIZE);
+    input
+        .by_ref()
+        .take(SNIFF_SIZE as u64)
+        .read_to_end(&mut sample)?;
+    input.seek(SeekFrom::Start(0))?;
+    // comment lines are blanked, which the sniffing skips, keeping the size of the sample
+    if let Some(comment) = args.comment_char {
+        sample
+            .split_mut(|&byte| byte == b'\n')
+            .filter(|line| line.first() == Some(&(comment as u8)))
+            .for_each(|line| line.fill(b' '));
+    }
+    Ok(sniff_separator(&sample, args.quote_char as u8).unwrap_or(b','))
+}
+
+const SNIFF_SIZE: usize = 16 * 1024;
+const SEPARATOR_CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Picks the candidate separator whose per-line field count is the most consistent across
+/// the sampled lines, preferring more fields on ties. Separators within quotes are ignored.
+pub fn sniff_separator(sample: &[u8], quote_char: u8) -> Option<u8> {
+    let mut lines = sample
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .collect::<Vec<_>>();
+    // The last line is likely cut off when the sample fills the whole buffer
+    if sample.len() >= SNIFF_SIZE && lines.len() > 1 {
+        lines.pop();
+    }
+    SEPARATOR_CANDIDATES
+        .into_iter()
+        .filter_map(|candidate| {
+            let counts = lines
+                .iter()
+                .map(|line| count_unquoted(line, candidate, quote_char))
+                .collect::<Vec<_>>();
+            let mode = counts
+                .iter()
+                .copied()
+                .max_by_key(|count| counts.iter().filter(|other| *other == count).count())?;
+            let consistent = counts.iter().filter(|count| **count == mode).count();
+            (mode > 0).then_some((candidate, consistent, mode))
+        })
+        .max_by_key(|(_, consistent, mode)| (*consistent, *mode))
+        .map(|(candidate, _, _)| candidate)
+}
+
+/// Listed rows of a [`MalformedRows`] report, the others are only counted.
+const MALFORMED_LISTED: usize = 100;
+
+/// Rows listed in the summary of a [`MalformedRows`] report.
+const MALFORMED_SUMMARY: usize = 5;
+
+/// Rows of a CSV file with another number of fields than its first row, as with
+/// unescaped quotes or newlines in a field, which the reader merges or drops.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MalformedRows {
+    /// Fields of the first row.
+    pub expected: usize,
+    /// Number of rows with another number of fields.
+    pub count: usize,
+    /// Line each of the first rows starts on, counting from 1, with its number of fields.
+    pub rows: Vec<(usize, usize)>,
+}
+
+impl Display for MalformedRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = self
+            .rows
+            .iter()
+            .take(MALFORMED_SUMMARY)
+            .map(|(line, fields)| format!("{} ({})", line, fields_count(*fields)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let more = match self.count > MALFORMED_SUMMARY {
+            true => format!(" and {} more", self.count - MALFORMED_SUMMARY),
+            false => String::new(),
+        };
+        write!(
+            f,
+            "{} row(s) have another number of fields than the {} of the first row, at line(s) {}{}",
+            self.count, self.expected, rows, more
+        )
+    }
+}
+
+fn fields_count(fields: usize) -> String {
+    match fields {
+        1 => "1 field".to_owned(),
+        _ => format!("{} fields", fields),
+    }
+}
+
+/// Checks the rows of a CSV input for `--strict`, which is then read from the start.
+fn check_csv(input: &mut Input, source: &Source, args: &Args) -> AppResult<MalformedRows> {
+    let separator = csv_separator(input, source, args)?;
+    let malformed = malformed_rows(
+        io::BufReader::new(input.by_ref()),
+        separator,
+        args.quote_char as u8,
+        args.comment_char.map(|c| c as u8),
+    )?;
+    input.seek(SeekFrom::Start(0))?;
+    Ok(malformed)
+}
+
+/// Finds the rows of CSV `input` with another number of fields than the first one.
+/// Separators and newlines within quotes belong to the field, so a quote left open makes
+/// the lines after it a single row, starting at the line of the quote. Blank lines and
+/// the lines starting with `comment_char` are skipped, as the reader does.
+pub fn malformed_rows(
+    input: impl BufRead,
+    separator: u8,
+    quote_char: u8,
+    comment_char: Option<u8>,
+) -> io::Result<MalformedRows> {
+    let mut malformed = MalformedRows::default();
+    let mut first = true;
+    let mut end_row = |line: usize, fields: usize| {
+        if first {
+            malformed.expected = fields;
+            first = false;
+        } else if fields != malformed.expected {
+            malformed.count += 1;
+            if malformed.rows.len() < MALFORMED_LISTED {
+                malformed.rows.push((line, fields));
+            }
+        }
+    };
+    let (mut quoted, mut blank, mut comment) = (false, true, false);
+    let (mut fields, mut line, mut start) = (1, 1, 1);
+    for byte in input.bytes() {
+        match byte? {
+            b'\n' => {
+                line += 1;
+                if !quoted {
+                    if !blank && !comment {
+                        end_row(start, fields);
+                    }
+                    (fields, start, blank, comment) = (1, line, true, false);
+                }
+            }
+            _ if comment => (),
+            byte if Some(byte) == comment_char && blank && !quoted => comment = true,
+            b'\r' => (),
+            byte if byte == quote_char => {
+                quoted = !quoted;
+                blank = false;
+            }
+            byte if byte == separator && !quoted => {
+                fields += 1;
+                blank = false;
+            }
+            _ => blank = false,
+        }
+    }
+    if !blank && !comment {
+        end_row(start, fields);
+    }
+    Ok(malformed)
+}
+
+fn count_unquoted(line: &[u8], separator: u8, quote_char: u8) -> usize {
+    let mut quoted = false;
+    line.iter()
+        .filter(|&&byte| {
+            if byte == quote_char {
+                quoted = !quoted;
+            }
+            byte == separator && !quoted
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use polars::df;
+
+    use super::*;
+
+    #[test]
+    fn test_format_from_content() {
+        assert!(matches!(
+            format_from_content(b"PAR1\x15\x04"),
+            Some(FileFormat::Parquet)
+        ));
+        assert!(matches!(
+            format_from_content(b"ARROW1\0\0"),
+            Some(FileFormat::Arrow)
+        ));
+        assert!(matches!(
+            format_from_content(b"PK\x03\x04\x14\0"),
+            Some(FileFormat::Excel)
+        ));
+        assert!(matches!(
+            format_from_content(b"\n  [{\"a\": 1}]"),
+            Some(FileFormat::Json)
+        ));
+        assert!(matches!(
+            format_from_content(b"\xef\xbb\xbf{\"a\": 1}"),
+            Some(FileFormat::Json)
+        ));
+        assert!(format_from_content(b"a,b\n1,2\n").is_none());
+        assert!(format_from_content(b"").is_none());
+    }
+
+    #[test]
+    fn test_stdin_once() {
+        let args = Args::parse_from(["tw"]);
+        assert_eq!(
+            Source::from_args_with(&args, false).unwrap(),
+            [Source::Stdin]
+        );
+        assert_eq!(
+            Source::from_args_with(&args, true).unwrap_err().to_string(),
+            "no file given, and stdin is a terminal rather than a pipe"
+        );
+
+        let args = Args::parse_from(["tw", "-", "data.csv"]);
+        assert_eq!(
+            Source::from_args(&args).unwrap(),
+            [Source::Stdin, Source::File(PathBuf::from("data.csv"))]
+        );
+        let args = Args::parse_from(["tw", "-", "data.csv", "-"]);
+        assert_eq!(
+            Source::from_args(&args).unwrap_err().to_string(),
+            "stdin can only be read once, '-' is given more than once"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_pipe() {
+        let path = std::env::temp_dir().join(format!("tabiew-pipe-{}", std::process::id()));
+        let name = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(name.as_ptr(), 0o600) }, 0);
+        let args = Args::parse_from(["tw", path.to_str().unwrap()]);
+        let source = Source::from_args(&args).unwrap().remove(0);
+        assert_eq!(source, Source::Pipe(path.clone()));
+        assert_eq!(source.path(), None);
+
+        let writer = std::thread::spawn({
+            let path = path.clone();
+            move || fs::write(path, "{\"a\": 1}\n{\"a\": 2}\n").unwrap()
+        });
+        let tables = read_tables(&source, &args).unwrap();
+        writer.join().unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(tables[0].data_frame.height(), 2);
+        assert!(tables[0].file.is_none());
+    }
+
+    #[test]
+    fn test_stem_name() {
+        let stem = |path: &str| Source::File(PathBuf::from(path)).stem_name();
+        assert_eq!(stem("data/customers.csv").as_deref(), Some("customers"));
+        assert_eq!(stem("sales-2024.csv").as_deref(), Some("sales_2024"));
+        assert_eq!(stem("2024 orders.parquet").as_deref(), Some("_2024_orders"));
+        assert_eq!(stem("notes").as_deref(), Some("notes"));
+        assert_eq!(stem("logs/events.json.gz").as_deref(), Some("events"));
+        assert_eq!(
+            Source::Url("https://example.com/files/orders.csv?raw=1".to_owned())
+                .stem_name()
+                .as_deref(),
+            Some("orders")
+        );
+        assert_eq!(Source::Stdin.stem_name(), None);
+    }
+
+    #[cfg(feature = "network")]
+    #[test]
+    fn test_split_response() {
+        let output = b"HTTP/1.1 301 Moved Permanently\r\nLocation: /b.csv\r\nContent-Type: text/html\r\n\r\nHTTP/2 200\r\ncontent-type: text/csv\r\n\r\na,b\r\n1,2\r\n";
+        let (body, content_type) = split_response(output.to_vec());
+        assert_eq!(body, b"a,b\r\n1,2\r\n");
+        assert_eq!(content_type.as_deref(), Some("text/csv"));
+        let (body, content_type) = split_response(b"HTTP/1.1 200 OK\r\n\r\n".to_vec());
+        assert!(body.is_empty());
+        assert_eq!(content_type, None);
+    }
+
+    #[test]
+    fn test_sniff_separator_comma() {
+        let sample = b"a,b,c\n1,2,3\n4,5,6\n";
+        assert_eq!(sniff_separator(sample, b'"'), Some(b','));
+    }
+
+    #[test]
+    fn test_sniff_separator_tab() {
+        let sample = b"name\tnote\nalice\tlikes a, b and c\nbob\tnone\n";
+        assert_eq!(sniff_separator(sample, b'"'), Some(b'\t'));
+    }
+
+    #[test]
+    fn test_sniff_separator_semicolon_with_decimal_commas() {
+        let sample = b"price;amount\n1,5;2\n3,25;4\n10;1,75\n";
+        assert_eq!(sniff_separator(sample, b'"'), Some(b';'));
+    }
+
+    #[test]
+    fn test_sniff_separator_pipe_ignores_quoted() {
+        let sample = b"id|text\n1|\"x, y, z\"\n2|\"a,b\"\n3|c\n";
+        assert_eq!(sniff_separator(sample, b'"'), Some(b'|'));
+    }
+
+    #[test]
+    fn test_load_csv_null_values() {
+        let csv = "id,name,score\n1,alice,NA\n2,NA,10\n3,NULL,\n";
+        let args = Args::parse_from(["tw", "--null-value", "NA", "--null-value", "NULL"]);
+        let df = load_csv(
+            Box::new(Cursor::new(csv.as_bytes().to_vec())),
+            &Source::Stdin,
+            &args,
+        )
+        .unwrap();
+        assert_eq!(df.column("name").unwrap().null_count(), 2);
+        assert_eq!(df.column("score").unwrap().null_count(), 2);
+        assert_eq!(
+            df.column("score").unwrap().dtype(),
+            &polars::datatypes::DataType::Int64
+        );
+    }
+
+    #[test]
+    fn test_load_columns() {
+        let csv = "id,name,score\n1,alice,3\n2,bob,10\n";
+        let args = Args::parse_from(["tw", "--columns", "score,id"]);
+        let input = || Box::new(Cursor::new(csv.as_bytes().to_vec()));
+        let df = load_csv(input(), &Source::Stdin, &args).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "score"]);
+        let mut df = select_columns(df, &args).unwrap();
+        assert_eq!(df.get_column_names(), ["score", "id"]);
+        assert_eq!(df.height(), 2);
+
+        let mut parquet = Vec::new();
+        ParquetWriter::new(&mut parquet).finish(&mut df).unwrap();
+        let args = Args::parse_from(["tw", "--columns", "id"]);
+        let df = load_parquet(Box::new(Cursor::new(parquet.clone())), &args).unwrap();
+        assert_eq!(df.get_column_names(), ["id"]);
+
+        let args = Args::parse_from(["tw", "--columns", "id,age"]);
+        let err = load_csv(input(), &Source::Stdin, &args).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 'age' not found, available columns: id, name, score"
+        );
+        let err = load_parquet(Box::new(Cursor::new(parquet)), &args).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 'age' not found, available columns: score, id"
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let df = df! { "id" => (0..100).collect::<Vec<i32>>() }.unwrap();
+        let args = Args::parse_from(["tw", "--head", "10"]);
+        let (head, truncation) = truncate(df.clone(), &args).unwrap();
+        assert_eq!(head.height(), 10);
+        assert_eq!(truncation, Some(Truncation::Head(10)));
+        let args = Args::parse_from(["tw", "--head", "100"]);
+        let (head, truncation) = truncate(df.clone(), &args).unwrap();
+        assert_eq!((head.height(), truncation), (100, None));
+
+        let args = Args::parse_from(["tw", "--sample", "10"]);
+        let (sample, truncation) = truncate(df.clone(), &args).unwrap();
+        let ids = sample.column("id").unwrap().i32().unwrap();
+        let ids = ids.into_no_null_iter().collect::<Vec<_>>();
+        assert_eq!(ids.len(), 10);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(truncation, Some(Truncation::Sample(10)));
+
+        // nothing is left out of a table of fewer rows
+        let args = Args::parse_from(["tw", "--sample", "100"]);
+        let (sample, truncation) = truncate(df, &args).unwrap();
+        assert_eq!((sample.height(), truncation), (100, None));
+    }
+
+    #[test]
+    fn test_load_empty_csv() {
+        let args = Args::parse_from(["tw"]);
+        let load = |csv: &str| {
+            load_csv(
+                Box::new(Cursor::new(csv.as_bytes().to_vec())),
+                &Source::Stdin,
+                &args,
+            )
+            .unwrap()
+        };
+        let df = load("id,name\n");
+        assert_eq!(df.get_column_names(), ["id", "name"]);
+        assert_eq!(df.height(), 0);
+        let df = load("");
+        assert_eq!(df.shape(), (0, 0));
+    }
+
+    #[test]
+    fn test_load_csv_without_null_values() {
+        let csv = "id,name\n1,NA\n2,\n";
+        let args = Args::parse_from(["tw"]);
+        let df = load_csv(
+            Box::new(Cursor::new(csv.as_bytes().to_vec())),
+            &Source::Stdin,
+            &args,
+        )
+        .unwrap();
+        assert_eq!(df.column("name").unwrap().null_count(), 1);
+    }
+
+    #[test]
+    fn test_load_csv_windows_1252() {
+        let csv = b"name;price\ncaf\xe9;\x80 3\n\x93tea\x94;\x80 2\n";
+        let args = Args::parse_from(["tw", "--encoding", "windows-1252"]);
+        let df = load_csv(Box::new(Cursor::new(csv.to_vec())), &Source::Stdin, &args).unwrap();
+        let names = df.column("name").unwrap();
+        assert_eq!(names.str().unwrap().get(0), Some("café"));
+        assert_eq!(names.str().unwrap().get(1), Some("\u{201c}tea\u{201d}"));
+        assert_eq!(df.column("price").unwrap().str().unwrap().get(0), Some("€ 3"));
+    }
+
+    #[test]
+    fn test_several_json_values() {
+        assert!(several_json_values(b"{\"a\": 1}\n{\"a\": 2}\n"));
+        assert!(several_json_values(b"{\"a\": {\"b\": [1]}} {\"a\": 2}"));
+        assert!(!several_json_values(b"{\n  \"a\": {\"b\": [1, 2]}\n}\n"));
+        assert!(!several_json_values(b"[{\"a\": \"}\\\" {\"}]"));
+        assert!(!several_json_values(b""));
+    }
+
+    #[test]
+    fn test_load_json_lines() {
+        let json = "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"tag\": true}\n";
+        let df = load_json(Box::new(Cursor::new(json.as_bytes().to_vec()))).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "name", "tag"]);
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.column("name").unwrap().null_count(), 1);
+        assert_eq!(df.column("tag").unwrap().null_count(), 1);
+
+        let json = "[{\"id\": 1}, {\"id\": 2}]";
+        let df = load_json(Box::new(Cursor::new(json.as_bytes().to_vec()))).unwrap();
+        assert_eq!(df.height(), 2);
+    }
+
+    #[test]
+    fn test_malformed_rows() {
+        let csv = "a,b\r\n1,\"x\ny, z\"\n\n2,y,extra\n3\n4,\"\"\"q\"\"\"\n";
+        let malformed = malformed_rows(csv.as_bytes(), b',', b'"', None).unwrap();
+        assert_eq!(
+            malformed,
+            MalformedRows {
+                expected: 2,
+                count: 2,
+                rows: vec![(5, 3), (6, 1)],
+            }
+        );
+        assert_eq!(
+            malformed.to_string(),
+            "2 row(s) have another number of fields than the 2 of the first row, at line(s) 5 (3 fields), 6 (1 field)"
+        );
+        let csv = "# a comment\na,b\n1,2\n# another, comment, here\n\"x\n#y\",3\n";
+        let malformed = malformed_rows(csv.as_bytes(), b',', b'"', Some(b'#')).unwrap();
+        assert_eq!(malformed.expected, 2);
+        assert_eq!(malformed.count, 0);
+    }
+
+    #[test]
+    fn test_load_aligned() {
+        let load = |text: &str, args: &[&str]| {
+            load_csv(
+                Box::new(Cursor::new(text.as_bytes().to_vec())),
+                &Source::Stdin,
+                &Args::parse_from([&["tw"], args].concat()),
+            )
+        };
+        let strs = |df: &DataFrame, name: &str| {
+            let column = df.column(name).unwrap().str().unwrap();
+            column.into_iter().map(|v| v.unwrap_or("-")).join("|")
+        };
+        let text = "id   name     score\n 1   a,b      2.5\n\n 2\t\"c\"  7\n 3   d\n";
+        let df = load(text, &["--whitespace"]).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "name", "score"]);
+        assert!(df.column("id").unwrap().dtype().is_integer());
+        assert_eq!(strs(&df, "name"), "a,b|\"c\"|d");
+        assert_eq!(
+            df.column("score").unwrap().f64().unwrap().to_vec(),
+            [Some(2.5), Some(7.0), None]
+        );
+
+        let text = "20240101  north 12\n20240102        7\n20240103  south\n";
+        let df = load(text, &["--fixed-width", "0,10,16", "--no-header"]).unwrap();
+        assert_eq!(df.get_column_names(), ["column_1", "column_2", "column_3"]);
+        assert_eq!(strs(&df, "column_2"), "north|-|south");
+        assert_eq!(
+            df.column("column_3").unwrap().i64().unwrap().to_vec(),
+            [Some(12), Some(7), None]
+        );
+        assert!(load(text, &["--fixed-width", "0,10,5"]).is_err());
+    }
+
+    #[test]
+    fn test_rename_duplicates() {
+        let names = "a a_duplicated_0 a_1 a_duplicated_1 b_duplicated_0";
+        let mut names = names.split(' ').map(str::to_owned).collect::<Vec<_>>();
+        let renamed = rename_duplicates(&mut names);
+        assert_eq!(names, ["a", "a_2", "a_1", "a_3", "b_duplicated_0"]);
+        let new_names = renamed.iter().map(|(_, new_name)| new_name.as_str());
+        assert_eq!(new_names.collect::<Vec<_>>(), ["a_2", "a_3"]);
+
+        let load = |args: &[&str]| {
+            let args = Args::parse_from([&["tw"], args].concat());
+            let input = Box::new(Cursor::new(b"id,name,name\n1,a,b\n".to_vec()));
+            deduplicate_columns(load_csv(input, &Source::Stdin, &args).unwrap(), &args)
+        };
+        let (df, renamed) = load(&[]).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "name", "name_1"]);
+        assert_eq!(renamed, [("name".to_owned(), "name_1".to_owned())]);
+        assert_eq!(
+            load(&["--strict"]).unwrap_err().to_string(),
+            "duplicate column name(s) 'name'"
+        );
+    }
+
+    #[test]
+    fn test_load_csv_with_comments() {
+        let csv = "# exported today\nid;name\n1;a\n# a; comment; between; rows\n2;b\n#3;c\n";
+        let load = |args: &[&str]| {
+            load_csv(
+                Box::new(Cursor::new(csv.as_bytes().to_vec())),
+                &Source::Stdin,
+                &Args::parse_from([&["tw"], args].concat()),
+            )
+        };
+        let df = load(&["--comment-char", "#"]).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "name"]);
+        assert_eq!(
+            df.column("id").unwrap().i64().unwrap().to_vec(),
+            [Some(1), Some(2)]
+        );
+        let err = load(&["--comment-char", ";"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the comment character ';' is also the separator"
+        );
+        let err = load(&["--comment-char", "#", "--quote-char", "#"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the comment character '#' is also the quote"
+        );
+    }
+
+    #[test]
+    fn test_read_head() {
+        let path = std::env::temp_dir().join(format!("tabiew-head-{}.csv", std::process::id()));
+        fs::write(&path, "id\n1\n2\n3\n").unwrap();
+        let read = |options: &[&str]| {
+            let path = path.to_str().unwrap();
+            let args = Args::parse_from([&["tw"], options, &[path]].concat());
+            let source = Source::from_args(&args).unwrap().remove(0);
+            read_tables(&source, &args).unwrap().remove(0)
+        };
+        // a file of exactly `--head` rows is read whole, and can be written back
+        let table = read(&["--head", "3"]);
+        assert_eq!((table.data_frame.height(), table.truncation), (3, None));
+        assert!(table.file.is_some());
+        let table = read(&["--head", "2"]);
+        assert_eq!(
+            (table.data_frame.height(), table.truncation),
+            (2, Some(Truncation::Head(2)))
+        );
+        assert!(table.file.is_none());
+
+        let table = read(&["--lazy", "--head", "3"]);
+        assert_eq!((table.data_frame.height(), table.truncation), (3, None));
+        let table = read(&["--lazy", "--head", "2"]);
+        assert_eq!(
+            (table.data_frame.height(), table.truncation),
+            (2, Some(Truncation::Head(2)))
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_write_csv_round_trip() {
+        let path = std::env::temp_dir().join(format!("tabiew-write-{}.csv", std::process::id()));
+        let csv = "# exported today\nid,name,score\n1,alice,NA\n2,NA,10\n";
+        fs::write(&path, csv).unwrap();
+        let args = Args::parse_from([
+            "tw",
+            "--comment-char",
+            "#",
+            "--null-value",
+            "NA",
+            path.to_str().unwrap(),
+        ]);
+        let source = Source::from_args(&args).unwrap().remove(0);
+        let mut table = read_tables(&source, &args).unwrap().remove(0);
+        let file = table.file.unwrap();
+        file.write(&mut table.data_frame).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), csv);
+
+        // comment lines between the rows would be lost
+        fs::write(
+            &path,
+            "# exported today\nid,name,score\n# first\n1,alice,NA\n",
+        )
+        .unwrap();
+        let err = file.write(&mut table.data_frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the file has comment lines between the rows, which writing would drop, use :export"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_sniff_separator_single_column() {
+        let sample = b"value\n1\n2\n";
+        assert_eq!(sniff_separator(sample, b'"'), None);
+    }
+}
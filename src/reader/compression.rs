@@ -0,0 +1,95 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::MultiGzDecoder;
+
+/// Compression of a file, recognized by its magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_content(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    pub fn of_file(path: &Path) -> Option<Self> {
+        let mut head = Vec::with_capacity(4);
+        File::open(path)
+            .and_then(|file| file.take(4).read_to_end(&mut head))
+            .ok()?;
+        Self::from_content(&head)
+    }
+
+    /// Reader decompressing `inner` as it is read.
+    pub fn decoder<'a>(&self, inner: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(MultiGzDecoder::new(inner)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(inner)?),
+        })
+    }
+}
+
+/// Path without the extension of a compressed file, as `data.csv` for `data.csv.gz`.
+pub fn uncompressed_path(path: &Path) -> PathBuf {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gz" | "gzip" | "zst" | "zstd") => path.with_extension(""),
+        _ => path.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+
+    use super::*;
+
+    #[test]
+    fn test_decoder() {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"a,b\n1,2\n").unwrap();
+        let gzip = encoder.finish().unwrap();
+        let zstd = zstd::encode_all(&b"a,b\n1,2\n"[..], 0).unwrap();
+
+        for (content, compression) in [(gzip, Compression::Gzip), (zstd, Compression::Zstd)] {
+            assert_eq!(Compression::from_content(&content), Some(compression));
+            let mut text = String::new();
+            compression
+                .decoder(&content[..])
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            assert_eq!(text, "a,b\n1,2\n");
+        }
+        assert_eq!(Compression::from_content(b"a,b\n"), None);
+    }
+
+    #[test]
+    fn test_uncompressed_path() {
+        assert_eq!(
+            uncompressed_path(Path::new("data.csv.gz")),
+            Path::new("data.csv")
+        );
+        assert_eq!(
+            uncompressed_path(Path::new("dir/data.json.zst")),
+            Path::new("dir/data.json")
+        );
+        assert_eq!(
+            uncompressed_path(Path::new("data.csv")),
+            Path::new("data.csv")
+        );
+    }
+}
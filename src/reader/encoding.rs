@@ -0,0 +1,105 @@
+use std::io::{self, Read};
+
+/// Characters for the bytes 0x80..=0x9f in Windows-1252. Bytes left undefined by the
+/// code page map to the matching C1 control character, like Latin-1.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleByte {
+    Latin1,
+    Windows1252,
+}
+
+impl SingleByte {
+    fn decode(&self, byte: u8) -> char {
+        match self {
+            SingleByte::Windows1252 if (0x80..0xa0).contains(&byte) => {
+                WINDOWS_1252_HIGH[(byte - 0x80) as usize]
+            }
+            _ => byte as char,
+        }
+    }
+}
+
+/// Reader transcoding a single-byte encoded stream into UTF-8 chunk by chunk.
+pub struct Decoder<R> {
+    inner: R,
+    encoding: SingleByte,
+    chunk: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(inner: R, encoding: SingleByte) -> Self {
+        Self {
+            inner,
+            encoding,
+            chunk: vec![0; 64 * 1024],
+            output: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let n = self.inner.read(&mut self.chunk)?;
+        self.output.clear();
+        self.pos = 0;
+        for &byte in &self.chunk[..n] {
+            if byte.is_ascii() {
+                self.output.push(byte);
+            } else {
+                let mut buf = [0; 4];
+                self.output
+                    .extend_from_slice(self.encoding.decode(byte).encode_utf8(&mut buf).as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.output.len() {
+            self.fill()?;
+        }
+        let n = buf.len().min(self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8], encoding: SingleByte) -> String {
+        let mut out = String::new();
+        Decoder::new(bytes, encoding)
+            .read_to_string(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        assert_eq!(
+            decode(b"caf\xe9,\xa3\x80", SingleByte::Latin1),
+            "café,£\u{80}"
+        );
+    }
+
+    #[test]
+    fn test_decode_windows_1252() {
+        assert_eq!(
+            decode(b"\x93quoted\x94 \x80 5\x81", SingleByte::Windows1252),
+            "\u{201c}quoted\u{201d} € 5\u{81}"
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Seek, SeekFrom},
+};
+
+use polars::{
+    frame::DataFrame,
+    prelude::{NamedFrom, Series},
+};
+
+use crate::app::AppResult;
+
+/// Value stored in a database record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// Read-only access to the tables of a SQLite database file. Pages are read on demand, so
+/// the database never has to fit in memory as a whole.
+pub struct Database<R> {
+    reader: R,
+    page_size: usize,
+    usable_size: usize,
+}
+
+/// Table listed in the schema of a database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub name: String,
+    root_page: u32,
+    columns: Vec<String>,
+    rowid_column: Option<usize>,
+    without_rowid: bool,
+}
+
+impl<R: Read + Seek> Database<R> {
+    pub fn new(mut reader: R) -> AppResult<Self> {
+        let mut header = [0_u8; 100];
+        reader.seek(SeekFrom::Start(0))?;
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| "not a SQLite database")?;
+        if !header.starts_with(b"SQLite format 3\0") {
+            return Err("not a SQLite database".into());
+        }
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+            1 => 65536,
+            size => size as usize,
+        };
+        // pages are powers of two from 512 to 65536 bytes, with at least 480 of them left
+        // after the reserved bytes, which the cell sizes are computed from
+        let reserved = header[20] as usize;
+        if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
+            return Err("corrupted SQLite database".into());
+        }
+        let usable_size = page_size
+            .checked_sub(reserved)
+            .filter(|size| *size >= 480)
+            .ok_or("corrupted SQLite database")?;
+        if u32::from_be_bytes([header[56], header[57], header[58], header[59]]) > 1 {
+            return Err("only UTF-8 encoded SQLite databases are supported".into());
+        }
+        Ok(Self {
+            reader,
+            page_size,
+            usable_size,
+        })
+    }
+
+    /// User tables of the database in schema order, skipping SQLite's internal tables.
+    pub fn tables(&mut self) -> AppResult<Vec<TableSchema>> {
+        Ok(self
+            .table_rows(1)?
+            .into_iter()
+            .filter_map(|(_, values)| match values.as_slice() {
+                [Value::Text(kind), Value::Text(name), _, Value::Integer(root_page), Value::Text(sql), ..]
+                    if kind == "table" && !name.starts_with("sqlite_") =>
+                {
+                    let (columns, rowid_column) = parse_columns(sql);
+                    Some(TableSchema {
+                        name: name.to_owned(),
+                        root_page: *root_page as u32,
+                        columns,
+                        rowid_column,
+                        without_rowid: sql.to_ascii_uppercase().contains("WITHOUT ROWID"),
+                    })
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Reads the whole table into a data frame. INTEGER, REAL, TEXT, and BLOB values map to
+    /// i64, f64, str, and binary columns. Columns mixing integers and reals become f64, and
+    /// columns mixing text with other values become str.
+    pub fn read_table(&mut self, table: &TableSchema) -> AppResult<DataFrame> {
+        if table.without_rowid {
+            return Err(format!("WITHOUT ROWID table '{}' is not supported", table.name).into());
+        }
+        let mut columns = vec![Vec::new(); table.columns.len()];
+        for (rowid, values) in self.table_rows(table.root_page)? {
+            let mut values = values.into_iter();
+            for (idx, column) in columns.iter_mut().enumerate() {
+                let value = values.next().unwrap_or(Value::Null);
+                if Some(idx) == table.rowid_column && value == Value::Null {
+                    column.push(Value::Integer(rowid));
+                } else {
+                    column.push(value);
+                }
+            }
+        }
+        let series = table
+            .columns
+            .iter()
+            .zip(columns)
+            .map(|(name, values)| values_into_series(name, values))
+            .collect::<Vec<_>>();
+        Ok(DataFrame::new(series)?)
+    }
+
+    fn page(&mut self, number: u32) -> AppResult<Vec<u8>> {
+        let number = number.checked_sub(1).ok_or("corrupted SQLite database")?;
+        let mut page = vec![0; self.page_size];
+        self.reader
+            .seek(SeekFrom::Start(number as u64 * self.page_size as u64))?;
+        self.reader
+            .read_exact(&mut page)
+            .map_err(|_| "corrupted SQLite database")?;
+        Ok(page)
+    }
+
+    /// Rowids and records of the table b-tree rooted at the given page, in rowid order.
+    fn table_rows(&mut self, root: u32) -> AppResult<Vec<(i64, Vec<Value>)>> {
+        let mut rows = Vec::new();
+        let mut stack = vec![root];
+        let mut visited = HashSet::new();
+        while let Some(number) = stack.pop() {
+            if !visited.insert(number) {
+                return Err("corrupted SQLite database".into());
+            }
+            let page = self.page(number)?;
+            let start = if number == 1 { 100 } else { 0 };
+            let kind = *page.get(start).ok_or("corrupted SQLite database")?;
+            let cell_count = read_u16(&page, start + 3)? as usize;
+            match kind {
+                0x05 => {
+                    let mut children = (0..cell_count)
+                        .map(|idx| {
+                            let cell = read_u16(&page, start + 12 + idx * 2)? as usize;
+                            read_u32(&page, cell)
+                        })
+                        .collect::<AppResult<Vec<_>>>()?;
+                    children.push(read_u32(&page, start + 8)?);
+                    stack.extend(children.into_iter().rev());
+                }
+                0x0d => {
+                    for idx in 0..cell_count {
+                        let cell = read_u16(&page, start + 8 + idx * 2)? as usize;
+                        let (payload_size, len1) = read_varint(&page, cell)?;
+                        let (rowid, len2) = read_varint(&page, cell + len1)?;
+                        let payload =
+                            self.payload(&page, cell + len1 + len2, payload_size as usize)?;
+                        rows.push((rowid, parse_record(&payload)?));
+                    }
+                }
+                _ => return Err("corrupted SQLite database".into()),
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Payload of a table leaf cell, following the overflow pages if it does not fit in
+    /// the page.
+    fn payload(&mut self, page: &[u8], offset: usize, size: usize) -> AppResult<Vec<u8>> {
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        let local = if size <= max_local {
+            size
+        } else {
+            let min_local = (usable - 12) * 32 / 255 - 23;
+            let local = min_local + (size - min_local) % (usable - 4);
+            if local <= max_local {
+                local
+            } else {
+                min_local
+            }
+        };
+        let mut payload = page
+            .get(offset..offset + local)
+            .ok_or("corrupted SQLite database")?
+            .to_vec();
+        if local < size {
+            let mut next = read_u32(page, offset + local)?;
+            while payload.len() < size && next != 0 {
+                let overflow = self.page(next)?;
+                next = read_u32(&overflow, 0)?;
+                let take = (size - payload.len()).min(usable - 4);
+                payload.extend_from_slice(&overflow[4..4 + take]);
+            }
+        }
+        Ok(payload)
+    }
+}
+
+fn values_into_series(name: &str, values: Vec<Value>) -> Series {
+    let non_null = || values.iter().filter(|value| **value != Value::Null);
+    if non_null().all(|value| matches!(value, Value::Integer(_))) && non_null().next().is_some() {
+        let ints = values
+            .iter()
+            .map(|value| match value {
+                Value::Integer(v) => Some(*v),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Series::new(name, ints)
+    } else if non_null().all(|value| matches!(value, Value::Integer(_) | Value::Real(_)))
+        && non_null().next().is_some()
+    {
+        let reals = values
+            .iter()
+            .map(|value| match value {
+                Value::Integer(v) => Some(*v as f64),
+                Value::Real(v) => Some(*v),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Series::new(name, reals)
+    } else if non_null().all(|value| matches!(value, Value::Blob(_))) && non_null().next().is_some()
+    {
+        let blobs = values
+            .iter()
+            .map(|value| match value {
+                Value::Blob(v) => Some(v.as_slice()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Series::new(name, blobs)
+    } else {
+        let strings = values
+            .into_iter()
+            .map(|value| match value {
+                Value::Null => None,
+                Value::Integer(v) => Some(v.to_string()),
+                Value::Real(v) => Some(v.to_string()),
+                Value::Text(v) => Some(v),
+                Value::Blob(v) => Some(String::from_utf8_lossy(&v).into_owned()),
+            })
+            .collect::<Vec<_>>();
+        Series::new(name, strings)
+    }
+}
+
+/// Decodes a record into its values, according to its header of serial types.
+fn parse_record(payload: &[u8]) -> AppResult<Vec<Value>> {
+    let (header_size, mut pos) = read_varint(payload, 0)?;
+    let mut serial_types = Vec::new();
+    while pos < header_size as usize {
+        let (serial_type, len) = read_varint(payload, pos)?;
+        serial_types.push(serial_type);
+        pos += len;
+    }
+
+    let mut body = header_size as usize;
+    serial_types
+        .into_iter()
+        .map(|serial_type| {
+            let size = match serial_type {
+                0 | 8 | 9 => 0,
+                1..=4 => serial_type as usize,
+                5 => 6,
+                6 | 7 => 8,
+                n if n >= 12 => (n as usize - 12 - (n as usize % 2)) / 2,
+                _ => return Err("corrupted SQLite database".into()),
+            };
+            let bytes = payload
+                .get(body..body + size)
+                .ok_or("corrupted SQLite database")?;
+            body += size;
+            Ok(match serial_type {
+                0 => Value::Null,
+                1..=6 => {
+                    let sign = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+                    let mut buf = [sign; 8];
+                    buf[8 - size..].copy_from_slice(bytes);
+                    Value::Integer(i64::from_be_bytes(buf))
+                }
+                7 => Value::Real(f64::from_be_bytes(bytes.try_into()?)),
+                8 => Value::Integer(0),
+                9 => Value::Integer(1),
+                n if n % 2 == 0 => Value::Blob(bytes.to_vec()),
+                _ => Value::Text(String::from_utf8_lossy(bytes).into_owned()),
+            })
+        })
+        .collect()
+}
+
+/// Column names of a `CREATE TABLE` statement, along with the index of the column aliasing
+/// the rowid (an `INTEGER PRIMARY KEY` column) if any.
+fn parse_columns(sql: &str) -> (Vec<String>, Option<usize>) {
+    const TABLE_CONSTRAINTS: [&str; 5] = ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"];
+
+    let Some(start) = sql.find('(') else {
+        return (Vec::new(), None);
+    };
+    let mut definitions = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut current = String::new();
+    for c in sql[start + 1..].chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => break,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                definitions.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+    }
+    definitions.push(current);
+
+    let mut columns = Vec::new();
+    let mut rowid_column = None;
+    for definition in definitions.iter().map(|def| def.trim()) {
+        let upper = definition.to_ascii_uppercase();
+        let first_word = upper.split_whitespace().next().unwrap_or_default();
+        if TABLE_CONSTRAINTS.contains(&first_word) {
+            continue;
+        }
+        let (name, rest) = match definition.chars().next() {
+            Some(open @ ('"' | '`' | '[' | '\'')) => {
+                let close = if open == '[' { ']' } else { open };
+                let end = definition[1..]
+                    .find(close)
+                    .map(|i| i + 1)
+                    .unwrap_or(definition.len());
+                (
+                    &definition[1..end],
+                    definition.get(end + 1..).unwrap_or_default(),
+                )
+            }
+            _ => definition
+                .split_once(char::is_whitespace)
+                .unwrap_or((definition, "")),
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let rest = rest.to_ascii_uppercase();
+        if rest.split_whitespace().next() == Some("INTEGER")
+            && rest.contains("PRIMARY KEY")
+            && !rest.contains("DESC")
+        {
+            rowid_column = Some(columns.len());
+        }
+        columns.push(name.to_owned());
+    }
+    (columns, rowid_column)
+}
+
+fn read_varint(data: &[u8], pos: usize) -> AppResult<(i64, usize)> {
+    let mut value = 0_u64;
+    for idx in 0..9 {
+        let byte = *data.get(pos + idx).ok_or("corrupted SQLite database")?;
+        if idx == 8 {
+            return Ok((((value << 8) | byte as u64) as i64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Ok((value as i64, idx + 1));
+        }
+    }
+    unreachable!()
+}
+
+fn read_u16(data: &[u8], pos: usize) -> AppResult<u16> {
+    Ok(u16::from_be_bytes(
+        data.get(pos..pos + 2)
+            .ok_or("corrupted SQLite database")?
+            .try_into()?,
+    ))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> AppResult<u32> {
+    Ok(u32::from_be_bytes(
+        data.get(pos..pos + 4)
+            .ok_or("corrupted SQLite database")?
+            .try_into()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_header() {
+        let header = |page_size: u16, reserved: u8| {
+            let mut header = b"SQLite format 3\0".to_vec();
+            header.resize(100, 0);
+            header[16..18].copy_from_slice(&page_size.to_be_bytes());
+            header[20] = reserved;
+            std::io::Cursor::new(header)
+        };
+        for (page_size, reserved) in [(0, 0), (2, 0), (1000, 0), (512, 33)] {
+            let error = Database::new(header(page_size, reserved)).err().unwrap();
+            assert_eq!(error.to_string(), "corrupted SQLite database");
+        }
+        assert!(Database::new(header(4096, 0)).is_ok());
+        assert!(Database::new(header(1, 255)).is_ok());
+    }
+
+    #[test]
+    fn test_read_varint() {
+        assert_eq!(read_varint(&[0x05], 0).unwrap(), (5, 1));
+        assert_eq!(read_varint(&[0x81, 0x00], 0).unwrap(), (128, 2));
+        assert_eq!(read_varint(&[0x00, 0x83, 0x60], 1).unwrap(), (480, 2));
+        assert_eq!(read_varint(&[0xff; 9], 0).unwrap(), (-1, 9));
+    }
+
+    #[test]
+    fn test_parse_record() {
+        // header: size 5, types NULL, i8, f64, text of length 2
+        let mut payload = vec![0x05, 0x00, 0x01, 0x07, 0x11, 0xfe];
+        payload.extend(2.5_f64.to_be_bytes());
+        payload.extend(b"hi");
+        assert_eq!(
+            parse_record(&payload).unwrap(),
+            vec![
+                Value::Null,
+                Value::Integer(-2),
+                Value::Real(2.5),
+                Value::Text("hi".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let (columns, rowid) = parse_columns(
+            "CREATE TABLE \"user list\" (id INTEGER PRIMARY KEY, \"full name\" TEXT NOT NULL, \
+             score REAL DEFAULT (0.0), [tag] , CONSTRAINT u UNIQUE (id, score))",
+        );
+        assert_eq!(columns, vec!["id", "full name", "score", "tag"]);
+        assert_eq!(rowid, Some(0));
+
+        let (columns, rowid) = parse_columns("CREATE TABLE t(a INT PRIMARY KEY, b)");
+        assert_eq!(columns, vec!["a", "b"]);
+        assert_eq!(rowid, None);
+    }
+}
@@ -0,0 +1,961 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+    ops::Range,
+};
+
+use chrono::{NaiveDateTime, TimeDelta};
+use flate2::read::DeflateDecoder;
+use polars::{
+    frame::DataFrame,
+    prelude::{DataType, NamedFrom, Series, TimeUnit},
+};
+
+use crate::app::AppResult;
+
+/// Value of a single worksheet cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    String(String),
+    Number(f64),
+    /// Number in a date or time format, in days since the Unix epoch, the fraction being
+    /// the time of the day.
+    Date(f64),
+    Bool(bool),
+}
+
+impl CellValue {
+    fn is_empty(&self) -> bool {
+        matches!(self, CellValue::Empty)
+    }
+
+    fn into_string(self) -> Option<String> {
+        match self {
+            CellValue::Empty => None,
+            CellValue::String(v) => Some(v),
+            CellValue::Number(v) => Some(format!("{}", v)),
+            CellValue::Date(v) if v.fract() == 0.0 => {
+                Some(date_time(v).format("%Y-%m-%d").to_string())
+            }
+            CellValue::Date(v) => Some(date_time(v).format("%Y-%m-%d %H:%M:%S").to_string()),
+            CellValue::Bool(v) => Some(format!("{}", v)),
+        }
+    }
+}
+
+/// Days since the Unix epoch of day 0 of the 1900 date system, 1899-12-30 as Excel
+/// counts 1900 as a leap year, and of the 1904 one.
+const EPOCH_1900: f64 = -25569.0;
+const EPOCH_1904: f64 = -24107.0;
+
+/// Milliseconds since the Unix epoch of a date in days since then.
+fn millis(days: f64) -> i64 {
+    (days * 86_400_000.0).round() as i64
+}
+
+fn date_time(days: f64) -> NaiveDateTime {
+    NaiveDateTime::UNIX_EPOCH + TimeDelta::milliseconds(millis(days))
+}
+
+/// Read-only view of an Office Open XML workbook (.xlsx).
+pub struct Workbook {
+    archive: Archive,
+    sheets: Vec<(String, String)>,
+    shared_strings: Vec<String>,
+    /// Whether each cell style formats numbers as dates or times.
+    date_styles: Vec<bool>,
+    /// Day 0 of the dates of the workbook, in days since the Unix epoch.
+    epoch: f64,
+}
+
+impl Workbook {
+    pub fn new(data: Vec<u8>) -> AppResult<Self> {
+        let archive = Archive::new(data)?;
+        let relations = archive
+            .read_string("xl/_rels/workbook.xml.rels")
+            .map(|xml| parse_relations(&xml))
+            .unwrap_or_default();
+        let workbook = archive.read_string("xl/workbook.xml")?;
+        let sheets = parse_sheets(&workbook)
+            .into_iter()
+            .filter_map(|(name, rel_id)| {
+                let target = relations.get(&rel_id)?;
+                let path = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_owned(),
+                    None => format!("xl/{}", target),
+                };
+                Some((name, path))
+            })
+            .collect();
+        let shared_strings = archive
+            .read_string("xl/sharedStrings.xml")
+            .map(|xml| parse_shared_strings(&xml))
+            .unwrap_or_default();
+        let date_styles = archive
+            .read_string("xl/styles.xml")
+            .map(|xml| parse_date_styles(&xml))
+            .unwrap_or_default();
+        Ok(Self {
+            archive,
+            sheets,
+            shared_strings,
+            date_styles,
+            epoch: match date_1904(&workbook) {
+                true => EPOCH_1904,
+                false => EPOCH_1900,
+            },
+        })
+    }
+
+    /// Names of the worksheets in workbook order.
+    pub fn sheet_names(&self) -> Vec<&str> {
+        self.sheets.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Cells of the worksheet selected by name or zero-based index, trimmed to the used
+    /// area of the sheet, or cut to an A1-style `range` such as `A1:D100`.
+    pub fn worksheet(&self, sheet: &str, range: Option<&str>) -> AppResult<Vec<Vec<CellValue>>> {
+        let (name, path) = self
+            .sheets
+            .iter()
+            .find(|(name, _)| name == sheet)
+            .or_else(|| sheet.parse::<usize>().ok().and_then(|i| self.sheets.get(i)))
+            .ok_or_else(|| {
+                format!(
+                    "worksheet '{}' not found, available worksheets: {}",
+                    sheet,
+                    self.sheet_names().join(", ")
+                )
+            })?;
+        let grid = parse_worksheet(
+            &self.archive.read_string(path)?,
+            &self.shared_strings,
+            &self.date_styles,
+            self.epoch,
+        );
+        let Some(range) = range else {
+            return Ok(trim_grid(grid));
+        };
+        let (rows, columns) = parse_range(range)
+            .ok_or_else(|| format!("invalid range '{}', expected cells like A1:D100", range))?;
+        let grid = cut_grid(grid, rows, columns);
+        if grid.is_empty() {
+            let error = format!("the range {} of worksheet '{}' holds no cells", range, name);
+            return Err(error.into());
+        }
+        Ok(grid)
+    }
+}
+
+/// Builds a data frame out of a grid of cells, taking the column names from the first row
+/// if `has_header` is set. Columns whose values are all numbers, all dates or all booleans
+/// keep their type if `typed` is set, every other column is read as strings.
+pub fn grid_into_data_frame(
+    mut grid: Vec<Vec<CellValue>>,
+    has_header: bool,
+    typed: bool,
+) -> AppResult<DataFrame> {
+    let width = grid.iter().map(Vec::len).max().unwrap_or_default();
+    let headers = if has_header && !grid.is_empty() {
+        grid.remove(0)
+            .into_iter()
+            .map(CellValue::into_string)
+            .chain(std::iter::repeat(None))
+            .take(width)
+            .enumerate()
+            .map(|(idx, name)| name.unwrap_or_else(|| format!("column_{}", idx + 1)))
+            .collect()
+    } else {
+        (1..=width)
+            .map(|idx| format!("column_{}", idx))
+            .collect::<Vec<_>>()
+    };
+
+    let mut columns = vec![Vec::with_capacity(grid.len()); width];
+    for row in grid {
+        let len = row.len();
+        for (idx, cell) in row.into_iter().enumerate() {
+            columns[idx].push(cell);
+        }
+        for column in columns.iter_mut().skip(len) {
+            column.push(CellValue::Empty);
+        }
+    }
+
+    let series = headers
+        .iter()
+        .zip(columns)
+        .map(|(name, cells)| cells_into_series(name, cells, typed))
+        .collect::<Vec<_>>();
+    Ok(DataFrame::new(series)?)
+}
+
+fn cells_into_series(name: &str, cells: Vec<CellValue>, typed: bool) -> Series {
+    let mut values = cells.iter().filter(|cell| !cell.is_empty()).peekable();
+    if typed && values.peek().is_some() {
+        if cells
+            .iter()
+            .all(|cell| matches!(cell, CellValue::Empty | CellValue::Number(_)))
+        {
+            let numbers = cells
+                .iter()
+                .map(|cell| match cell {
+                    CellValue::Number(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            return if numbers
+                .iter()
+                .flatten()
+                .all(|v| v.fract() == 0.0 && v.abs() < i64::MAX as f64)
+            {
+                let ints = numbers
+                    .into_iter()
+                    .map(|v| v.map(|v| v as i64))
+                    .collect::<Vec<_>>();
+                Series::new(name, ints)
+            } else {
+                Series::new(name, numbers)
+            };
+        }
+        if cells
+            .iter()
+            .all(|cell| matches!(cell, CellValue::Empty | CellValue::Date(_)))
+        {
+            let days = cells
+                .iter()
+                .map(|cell| match cell {
+                    CellValue::Date(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            // dates without a time of the day in every cell are only dates
+            let (series, dtype) = if days.iter().flatten().all(|v| v.fract() == 0.0) {
+                let days = days
+                    .into_iter()
+                    .map(|v| v.map(|v| v as i32))
+                    .collect::<Vec<_>>();
+                (Series::new(name, days), DataType::Date)
+            } else {
+                let millis = days.into_iter().map(|v| v.map(millis)).collect::<Vec<_>>();
+                let dtype = DataType::Datetime(TimeUnit::Milliseconds, None);
+                (Series::new(name, millis), dtype)
+            };
+            return series.cast(&dtype).expect("integers cast to dates");
+        }
+        if cells
+            .iter()
+            .all(|cell| matches!(cell, CellValue::Empty | CellValue::Bool(_)))
+        {
+            let bools = cells
+                .iter()
+                .map(|cell| match cell {
+                    CellValue::Bool(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            return Series::new(name, bools);
+        }
+    }
+    let strings = cells
+        .into_iter()
+        .map(CellValue::into_string)
+        .collect::<Vec<_>>();
+    Series::new(name, strings)
+}
+
+fn trim_grid(grid: Vec<Vec<CellValue>>) -> Vec<Vec<CellValue>> {
+    let first_col = grid
+        .iter()
+        .filter_map(|row| row.iter().position(|cell| !cell.is_empty()))
+        .min()
+        .unwrap_or_default();
+    let mut rows = grid
+        .into_iter()
+        .map(|mut row| {
+            while row.last().is_some_and(CellValue::is_empty) {
+                row.pop();
+            }
+            row.into_iter().skip(first_col).collect::<Vec<_>>()
+        })
+        .skip_while(Vec::is_empty)
+        .collect::<Vec<_>>();
+    while rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+    rows
+}
+
+/// Cells of the grid within `rows` and `columns`, without the empty rows and cells at
+/// their end. The first row and column of the range are kept even if empty, the first
+/// row being the header.
+fn cut_grid(
+    grid: Vec<Vec<CellValue>>,
+    rows: Range<usize>,
+    columns: Range<usize>,
+) -> Vec<Vec<CellValue>> {
+    let mut cut = grid
+        .into_iter()
+        .skip(rows.start)
+        .take(rows.len())
+        .map(|row| {
+            let mut row = row
+                .into_iter()
+                .skip(columns.start)
+                .take(columns.len())
+                .collect::<Vec<_>>();
+            while row.last().is_some_and(CellValue::is_empty) {
+                row.pop();
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    while cut.last().is_some_and(Vec::is_empty) {
+        cut.pop();
+    }
+    cut
+}
+
+/// Zero-based rows and columns of an A1-style range such as `A1:D100`, whose corners
+/// may come in any order.
+pub fn parse_range(range: &str) -> Option<(Range<usize>, Range<usize>)> {
+    let (start, end) = range.split_once(':')?;
+    let (start_row, start_col) = parse_cell_ref(start.trim())?;
+    let (end_row, end_col) = parse_cell_ref(end.trim())?;
+    Some((
+        start_row.min(end_row)..start_row.max(end_row) + 1,
+        start_col.min(end_col)..start_col.max(end_col) + 1,
+    ))
+}
+
+/// Zero-based (row, column) of an A1-style cell reference such as `B12`.
+pub fn parse_cell_ref(reference: &str) -> Option<(usize, usize)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+    let col = letters.chars().try_fold(0_usize, |acc, c| {
+        c.is_ascii_alphabetic()
+            .then(|| acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1))
+    })?;
+    let row = digits.parse::<usize>().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+/// Cells of the worksheet, the numbers of the styles of `date_styles` being dates
+/// counted from `epoch`.
+fn parse_worksheet(
+    xml: &str,
+    shared_strings: &[String],
+    date_styles: &[bool],
+    epoch: f64,
+) -> Vec<Vec<CellValue>> {
+    let mut grid: Vec<Vec<CellValue>> = Vec::new();
+    let mut row = 0;
+    let mut col = 0;
+    let mut cell_type = String::new();
+    let mut is_date = false;
+    let mut value: Option<String> = None;
+    let mut in_value = false;
+
+    for token in XmlTokens::new(xml) {
+        match token {
+            XmlToken::Open {
+                name: "row", attrs, ..
+            } => {
+                if let Some(idx) = attribute(attrs, "r").and_then(|r| r.parse::<usize>().ok()) {
+                    row = idx.saturating_sub(1);
+                }
+                col = 0;
+            }
+            XmlToken::Close("row") => {
+                row += 1;
+            }
+            XmlToken::Open {
+                name: "c",
+                attrs,
+                empty,
+            } => {
+                if let Some((r, c)) = attribute(attrs, "r").and_then(|r| parse_cell_ref(&r)) {
+                    row = r;
+                    col = c;
+                }
+                cell_type = attribute(attrs, "t").unwrap_or_default();
+                is_date = attribute(attrs, "s")
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .and_then(|s| date_styles.get(s).copied())
+                    .unwrap_or_default();
+                value = None;
+                if empty {
+                    col += 1;
+                }
+            }
+            XmlToken::Close("c") => {
+                let cell = match (cell_type.as_str(), value.take()) {
+                    (_, None) => CellValue::Empty,
+                    ("s", Some(v)) => v
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|idx| shared_strings.get(idx))
+                        .map(|s| CellValue::String(s.clone()))
+                        .unwrap_or(CellValue::Empty),
+                    ("b", Some(v)) => CellValue::Bool(v.trim() == "1"),
+                    ("str" | "inlineStr" | "e", Some(v)) => CellValue::String(v),
+                    (_, Some(v)) => match v.trim().parse::<f64>() {
+                        Ok(number) if is_date => CellValue::Date(number + epoch),
+                        Ok(number) => CellValue::Number(number),
+                        Err(_) => CellValue::String(v),
+                    },
+                };
+                if !cell.is_empty() {
+                    if grid.len() <= row {
+                        grid.resize_with(row + 1, Vec::new);
+                    }
+                    if grid[row].len() <= col {
+                        grid[row].resize(col + 1, CellValue::Empty);
+                    }
+                    grid[row][col] = cell;
+                }
+                col += 1;
+            }
+            XmlToken::Open {
+                name: "v" | "t",
+                empty: false,
+                ..
+            } => in_value = true,
+            XmlToken::Close("v" | "t") => in_value = false,
+            XmlToken::Text(text) if in_value => {
+                value
+                    .get_or_insert_with(String::new)
+                    .push_str(&unescape(text));
+            }
+            _ => (),
+        }
+    }
+    grid
+}
+
+fn parse_shared_strings(xml: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    let mut in_phonetic = false;
+    for token in XmlTokens::new(xml) {
+        match token {
+            XmlToken::Open { name: "si", .. } => current.clear(),
+            XmlToken::Close("si") => strings.push(std::mem::take(&mut current)),
+            XmlToken::Open { name: "rPh", .. } => in_phonetic = true,
+            XmlToken::Close("rPh") => in_phonetic = false,
+            XmlToken::Open {
+                name: "t",
+                empty: false,
+                ..
+            } => in_text = true,
+            XmlToken::Close("t") => in_text = false,
+            XmlToken::Text(text) if in_text && !in_phonetic => current.push_str(&unescape(text)),
+            _ => (),
+        }
+    }
+    strings
+}
+
+/// Whether each cell style of `cellXfs`, which the `s` attribute of the cells indexes,
+/// formats numbers as dates or times, with a built-in or a custom number format.
+fn parse_date_styles(xml: &str) -> Vec<bool> {
+    let mut custom_formats = HashMap::new();
+    let mut styles = Vec::new();
+    let mut in_cell_xfs = false;
+    for token in XmlTokens::new(xml) {
+        match token {
+            XmlToken::Open {
+                name: "numFmt",
+                attrs,
+                ..
+            } => {
+                if let (Some(id), Some(code)) = (
+                    attribute(attrs, "numFmtId").and_then(|id| id.parse::<u32>().ok()),
+                    attribute(attrs, "formatCode"),
+                ) {
+                    custom_formats.insert(id, is_date_format(&code));
+                }
+            }
+            XmlToken::Open {
+                name: "cellXfs",
+                empty: false,
+                ..
+            } => in_cell_xfs = true,
+            XmlToken::Close("cellXfs") => in_cell_xfs = false,
+            XmlToken::Open {
+                name: "xf", attrs, ..
+            } if in_cell_xfs => {
+                let id = attribute(attrs, "numFmtId").and_then(|id| id.parse::<u32>().ok());
+                styles.push(id.is_some_and(|id| {
+                    matches!(id, 14..=22 | 27..=36 | 45..=47 | 50..=58)
+                        || custom_formats.get(&id).copied().unwrap_or_default()
+                }));
+            }
+            _ => (),
+        }
+    }
+    styles
+}
+
+/// Whether a number format code such as `yyyy-mm-dd` or `h:mm` writes a date or a time,
+/// once its quoted texts, escaped characters and bracketed colors are left out.
+fn is_date_format(code: &str) -> bool {
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                chars.by_ref().find(|c| *c == '"');
+            }
+            '\\' | '_' | '*' => {
+                chars.next();
+            }
+            '[' => {
+                // elapsed times such as [h]:mm are still times
+                let section = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+                if section.chars().all(|c| "hHmMsS".contains(c)) {
+                    return !section.is_empty();
+                }
+            }
+            'd' | 'D' | 'm' | 'M' | 'y' | 'Y' | 'h' | 'H' | 's' | 'S' => return true,
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Whether the dates of the workbook count from 1904 rather than 1900.
+fn date_1904(xml: &str) -> bool {
+    XmlTokens::new(xml).any(|token| match token {
+        XmlToken::Open {
+            name: "workbookPr",
+            attrs,
+            ..
+        } => attribute(attrs, "date1904").is_some_and(|v| v == "1" || v == "true"),
+        _ => false,
+    })
+}
+
+fn parse_sheets(xml: &str) -> Vec<(String, String)> {
+    XmlTokens::new(xml)
+        .filter_map(|token| match token {
+            XmlToken::Open {
+                name: "sheet",
+                attrs,
+                ..
+            } => Some((attribute(attrs, "name")?, attribute(attrs, "id")?)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_relations(xml: &str) -> HashMap<String, String> {
+    XmlTokens::new(xml)
+        .filter_map(|token| match token {
+            XmlToken::Open {
+                name: "Relationship",
+                attrs,
+                ..
+            } => Some((attribute(attrs, "Id")?, attribute(attrs, "Target")?)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Just enough of a zip archive reader to pull the parts out of a workbook.
+struct Archive {
+    data: Vec<u8>,
+    entries: HashMap<String, ArchiveEntry>,
+}
+
+struct ArchiveEntry {
+    method: u16,
+    compressed_size: usize,
+    header_offset: usize,
+}
+
+impl Archive {
+    fn new(data: Vec<u8>) -> AppResult<Self> {
+        const END_OF_DIRECTORY: u32 = 0x06054b50;
+        const DIRECTORY_ENTRY: u32 = 0x02014b50;
+
+        let eocd = (0..data.len().saturating_sub(21))
+            .rev()
+            .find(|&pos| read_u32(&data, pos) == Some(END_OF_DIRECTORY))
+            .ok_or("not a valid xlsx file")?;
+        let count = read_u16(&data, eocd + 10).ok_or("corrupted xlsx file")? as usize;
+        let mut pos = read_u32(&data, eocd + 16).ok_or("corrupted xlsx file")? as usize;
+
+        let mut entries = HashMap::with_capacity(count);
+        for _ in 0..count {
+            if read_u32(&data, pos) != Some(DIRECTORY_ENTRY) {
+                return Err("corrupted xlsx file".into());
+            }
+            let field = |offset: usize| read_u16(&data, pos + offset).map(usize::from);
+            let method = read_u16(&data, pos + 10).ok_or("corrupted xlsx file")?;
+            let compressed_size = read_u32(&data, pos + 20).ok_or("corrupted xlsx file")?;
+            let name_len = field(28).ok_or("corrupted xlsx file")?;
+            let extra_len = field(30).ok_or("corrupted xlsx file")?;
+            let comment_len = field(32).ok_or("corrupted xlsx file")?;
+            let header_offset = read_u32(&data, pos + 42).ok_or("corrupted xlsx file")?;
+            let name = data
+                .get(pos + 46..pos + 46 + name_len)
+                .ok_or("corrupted xlsx file")?;
+            entries.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                ArchiveEntry {
+                    method,
+                    compressed_size: compressed_size as usize,
+                    header_offset: header_offset as usize,
+                },
+            );
+            pos += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(Self { data, entries })
+    }
+
+    fn read_string(&self, name: &str) -> AppResult<String> {
+        let entry = self
+            .entries
+            .get(name)
+            .ok_or_else(|| format!("'{}' is missing from the xlsx file", name))?;
+        let pos = entry.header_offset;
+        let name_len = read_u16(&self.data, pos + 26).ok_or("corrupted xlsx file")? as usize;
+        let extra_len = read_u16(&self.data, pos + 28).ok_or("corrupted xlsx file")? as usize;
+        let start = pos + 30 + name_len + extra_len;
+        let compressed = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .ok_or("corrupted xlsx file")?;
+        let mut content = String::new();
+        match entry.method {
+            0 => Cursor::new(compressed).read_to_string(&mut content)?,
+            8 => DeflateDecoder::new(compressed).read_to_string(&mut content)?,
+            method => return Err(format!("unsupported xlsx compression method {}", method).into()),
+        };
+        Ok(content)
+    }
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+#[derive(Debug, PartialEq)]
+enum XmlToken<'a> {
+    Open {
+        name: &'a str,
+        attrs: &'a str,
+        empty: bool,
+    },
+    Close(&'a str),
+    Text(&'a str),
+}
+
+/// Tokenizer for the well-formed, machine-generated XML found in workbooks. Namespace
+/// prefixes are stripped from the element names.
+struct XmlTokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> XmlTokens<'a> {
+    fn new(xml: &'a str) -> Self {
+        Self { rest: xml }
+    }
+}
+
+impl<'a> Iterator for XmlTokens<'a> {
+    type Item = XmlToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            if !self.rest.starts_with('<') {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                let (text, rest) = self.rest.split_at(end);
+                self.rest = rest;
+                return Some(XmlToken::Text(text));
+            }
+            if let Some(rest) = self.rest.strip_prefix("<!--") {
+                self.rest = rest.find("-->").map(|end| &rest[end + 3..]).unwrap_or("");
+                continue;
+            }
+            let end = self.rest.find('>')?;
+            let tag = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+            if tag.starts_with('?') || tag.starts_with('!') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(XmlToken::Close(local_name(name.trim())));
+            }
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+            return Some(XmlToken::Open {
+                name: local_name(name),
+                attrs,
+                empty,
+            });
+        }
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':')
+        .map(|(_, local)| local)
+        .unwrap_or(name)
+}
+
+/// Value of the attribute with the given local name, e.g. `id` matches `r:id`.
+fn attribute(attrs: &str, key: &str) -> Option<String> {
+    let mut rest = attrs;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after.chars().next()?;
+        let after = &after[quote.len_utf8()..];
+        let end = after.find(quote)?;
+        if local_name(name) == key {
+            return Some(unescape(&after[..end]));
+        }
+        rest = &after[end + 1..];
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_owned();
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell_ref() {
+        assert_eq!(parse_cell_ref("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_ref("B12"), Some((11, 1)));
+        assert_eq!(parse_cell_ref("AA3"), Some((2, 26)));
+        assert_eq!(parse_cell_ref("a1"), Some((0, 0)));
+        assert_eq!(parse_cell_ref("A0"), None);
+        assert_eq!(parse_cell_ref("12"), None);
+        assert_eq!(parse_cell_ref("A"), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("A1:D100"), Some((0..100, 0..4)));
+        assert_eq!(parse_range("c5:B2"), Some((1..5, 1..3)));
+        assert_eq!(parse_range("A1"), None);
+        assert_eq!(parse_range("A1:D"), None);
+    }
+
+    #[test]
+    fn test_cut_grid() {
+        let text = |v: &str| CellValue::String(v.to_owned());
+        let number = |v: f64| CellValue::Number(v);
+        let grid = vec![
+            vec![text("title")],
+            vec![],
+            vec![CellValue::Empty, text("id"), CellValue::Empty],
+            vec![CellValue::Empty, number(1.0), number(2.0), number(3.0)],
+            vec![CellValue::Empty],
+        ];
+        assert_eq!(
+            cut_grid(grid.clone(), 2..10, 1..3),
+            vec![vec![text("id")], vec![number(1.0), number(2.0)]]
+        );
+        assert!(cut_grid(grid, 5..10, 0..3).is_empty());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("a &amp; b"), "a & b");
+        assert_eq!(unescape("&lt;x&gt; &#65;&#x42;"), "<x> AB");
+        assert_eq!(unescape("fish & chips"), "fish & chips");
+    }
+
+    #[test]
+    fn test_parse_worksheet() {
+        let shared = vec!["name".to_owned(), "age".to_owned(), "bob".to_owned()];
+        let xml = r#"<?xml version="1.0"?>
+            <worksheet><sheetData>
+            <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+            <row r="2"><c r="A2" t="s"><v>2</v></c><c r="B2"><v>42</v></c><c r="C2" t="b"><v>1</v></c></row>
+            <row r="4"><c r="B4" t="inlineStr"><is><t>x &amp; y</t></is></c></row>
+            </sheetData></worksheet>"#;
+        let grid = parse_worksheet(xml, &shared, &[], EPOCH_1900);
+        assert_eq!(
+            grid,
+            vec![
+                vec![
+                    CellValue::String("name".to_owned()),
+                    CellValue::String("age".to_owned())
+                ],
+                vec![
+                    CellValue::String("bob".to_owned()),
+                    CellValue::Number(42.0),
+                    CellValue::Bool(true)
+                ],
+                vec![],
+                vec![CellValue::Empty, CellValue::String("x & y".to_owned())],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_date_format() {
+        let dates = [
+            "yyyy-mm-dd",
+            "d/m/yy h:mm",
+            "[h]:mm:ss",
+            "[$-409]mmm d",
+            "h AM/PM",
+        ];
+        for code in dates {
+            assert!(is_date_format(code), "{}", code);
+        }
+        let numbers = [
+            "General",
+            "0.00",
+            "#,##0 \"days\"",
+            "[Red]0.00",
+            "0.00\\h",
+            "@",
+        ];
+        for code in numbers {
+            assert!(!is_date_format(code), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_parse_date_styles() {
+        let xml = r#"<styleSheet>
+            <numFmts count="2">
+            <numFmt numFmtId="164" formatCode="yyyy\-mm\-dd"/>
+            <numFmt numFmtId="165" formatCode="0.000"/>
+            </numFmts>
+            <cellStyleXfs count="1"><xf numFmtId="14"/></cellStyleXfs>
+            <cellXfs count="5">
+            <xf numFmtId="0"/><xf numFmtId="14" applyNumberFormat="1"/><xf numFmtId="164"/>
+            <xf numFmtId="165"/><xf numFmtId="21"></xf>
+            </cellXfs>
+            </styleSheet>"#;
+        assert_eq!(parse_date_styles(xml), [false, true, true, false, true]);
+        assert!(date_1904(r#"<workbookPr date1904="1"/>"#));
+        assert!(!date_1904(r#"<workbookPr/>"#));
+    }
+
+    #[test]
+    fn test_dates() {
+        let xml = r#"<worksheet><sheetData>
+            <row r="1"><c r="A1" s="1"><v>45292</v></c><c r="B1" s="1"><v>45292.5</v></c><c r="C1"><v>45292</v></c></row>
+            </sheetData></worksheet>"#;
+        let grid = parse_worksheet(xml, &[], &[false, true], EPOCH_1900);
+        assert_eq!(
+            grid,
+            vec![vec![
+                CellValue::Date(19723.0),
+                CellValue::Date(19723.5),
+                CellValue::Number(45292.0)
+            ]]
+        );
+        assert_eq!(
+            CellValue::Date(19723.0).into_string().as_deref(),
+            Some("2024-01-01")
+        );
+        assert_eq!(
+            CellValue::Date(19723.5).into_string().as_deref(),
+            Some("2024-01-01 12:00:00")
+        );
+
+        let grid = vec![
+            vec![
+                CellValue::String("day".to_owned()),
+                CellValue::String("at".to_owned()),
+            ],
+            vec![CellValue::Date(19723.0), CellValue::Date(19723.5)],
+            vec![CellValue::Date(19724.0), CellValue::Date(19724.0)],
+        ];
+        let df = grid_into_data_frame(grid, true, true).unwrap();
+        assert_eq!(df.column("day").unwrap().dtype(), &DataType::Date);
+        assert_eq!(
+            df.column("at").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Milliseconds, None)
+        );
+        assert_eq!(
+            df.column("day").unwrap().get(1).unwrap().to_string(),
+            "2024-01-02"
+        );
+    }
+
+    #[test]
+    fn test_grid_into_data_frame() {
+        let grid = vec![
+            vec![
+                CellValue::String("id".to_owned()),
+                CellValue::String("price".to_owned()),
+            ],
+            vec![CellValue::Number(1.0), CellValue::Number(2.5)],
+            vec![CellValue::Number(2.0)],
+        ];
+        let df = grid_into_data_frame(grid, true, true).unwrap();
+        assert_eq!(df.get_column_names(), vec!["id", "price"]);
+        assert_eq!(df.height(), 2);
+        assert_eq!(
+            df.column("id").unwrap().dtype(),
+            &polars::datatypes::DataType::Int64
+        );
+        assert_eq!(df.column("price").unwrap().null_count(), 1);
+    }
+}
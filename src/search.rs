@@ -0,0 +1,235 @@
+use polars::prelude::{AnyValue, DataFrame, Series};
+use regex::RegexBuilder;
+
+use crate::utils::{any_value_into_string, RoundRobinExt};
+
+/// How a search query matches the cells, toggled in the search prompt and kept for the
+/// next searches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    /// The query is a regular expression rather than a text.
+    pub regex: bool,
+    /// Only the cells of the selected column are searched.
+    pub column: bool,
+}
+
+/// Matches of a search query in the table, ordered by row then column.
+#[derive(Debug)]
+pub struct Search {
+    query: String,
+    matches: Vec<(usize, usize)>,
+    current: usize,
+}
+
+impl Search {
+    /// Scans the cells of `data_frame`, the rows of the table from `start`, for `query`
+    /// as a substring, or as a regular expression matching part of the cells with
+    /// [`SearchOptions::regex`]. Only the cells of `column` are scanned if given. A `\c`
+    /// anywhere in the query makes the search case-insensitive too. Fails if the
+    /// regular expression is invalid.
+    pub fn new(
+        query: &str,
+        options: SearchOptions,
+        data_frame: &DataFrame,
+        start: usize,
+        column: Option<usize>,
+    ) -> Result<Self, String> {
+        let ignore_case = options.ignore_case || query.contains("\\c");
+        let pattern = query.replace("\\c", "");
+        let pattern = if options.regex {
+            pattern
+        } else {
+            regex::escape(&pattern)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|error| {
+                // the message of a syntax error spans several lines, the last one says why
+                let message = error.to_string();
+                let reason = message.lines().last().unwrap_or_default();
+                format!("invalid pattern: {}", reason.trim_start_matches("error: "))
+            })?;
+        let is_match = |value: &AnyValue| regex.is_match(&any_value_into_string(value.clone()));
+        let matches = match column {
+            Some(col) => data_frame
+                .select_at_idx(col)
+                .into_iter()
+                .flat_map(Series::iter)
+                .enumerate()
+                .filter(|(_, value)| is_match(value))
+                .map(|(idx, _)| (start + idx, col))
+                .collect(),
+            None => {
+                let width = data_frame.width();
+                data_frame
+                    .iter()
+                    .map(Series::iter)
+                    .round_robin()
+                    .enumerate()
+                    .filter(|(_, value)| is_match(value))
+                    .map(|(idx, _)| (start + idx / width, idx % width))
+                    .collect()
+            }
+        };
+        Ok(Self {
+            query: query.to_owned(),
+            matches,
+            current: 0,
+        })
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// Index of the current match.
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<(usize, usize)> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// Moves to the first match at or after `row`, wrapping around to the top.
+    pub fn seek(&mut self, row: usize) -> Option<(usize, usize)> {
+        self.current = self
+            .matches
+            .partition_point(|(match_row, _)| *match_row < row);
+        if self.current == self.matches.len() {
+            self.current = 0;
+        }
+        self.current()
+    }
+
+    pub fn next_match(&mut self) -> Option<(usize, usize)> {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+        self.current()
+    }
+
+    pub fn prev_match(&mut self) -> Option<(usize, usize)> {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+        self.current()
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.matches.binary_search(&(row, col)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn values() -> DataFrame {
+        df! {
+            "name" => ["Apple", "banana", "cherry", "pineapple"],
+            "note" => ["red", "yellow", "red apple", ""],
+        }
+        .unwrap()
+    }
+
+    fn search_text(query: &str, data_frame: &DataFrame, start: usize) -> Search {
+        Search::new(query, SearchOptions::default(), data_frame, start, None).unwrap()
+    }
+
+    #[test]
+    fn test_search_matches() {
+        let search = search_text("apple", &values(), 0);
+        assert_eq!(search.len(), 2);
+        assert!(search.contains(2, 1));
+        assert!(search.contains(3, 0));
+        assert!(!search.contains(0, 0));
+    }
+
+    #[test]
+    fn test_search_ignore_case() {
+        let search = search_text("apple\\c", &values(), 0);
+        assert_eq!(search.len(), 3);
+        assert!(search.contains(0, 0));
+        assert_eq!(search.query(), "apple\\c");
+    }
+
+    #[test]
+    fn test_search_options() {
+        let ignore_case = SearchOptions {
+            ignore_case: true,
+            ..Default::default()
+        };
+        let search = Search::new("APPLE", ignore_case, &values(), 0, None).unwrap();
+        assert_eq!(search.len(), 3);
+        // the text is not a pattern
+        assert!(search_text("a.*e", &values(), 0).is_empty());
+
+        let regex = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let search = Search::new("^(red|yellow)$", regex, &values(), 0, None).unwrap();
+        assert_eq!(search.len(), 2);
+        assert!(search.contains(0, 1));
+        assert!(search.contains(1, 1));
+        let search = Search::new("^a", regex, &values(), 0, None).unwrap();
+        assert!(search.is_empty());
+        let search = Search::new("^a\\c", regex, &values(), 0, None).unwrap();
+        assert!(search.contains(0, 0));
+        assert_eq!(
+            Search::new("(red", regex, &values(), 0, None).unwrap_err(),
+            "invalid pattern: unclosed group"
+        );
+    }
+
+    #[test]
+    fn test_search_column() {
+        let search = Search::new("red", SearchOptions::default(), &values(), 0, Some(1)).unwrap();
+        assert_eq!(search.len(), 2);
+        assert!(search.contains(0, 1));
+        assert!(search.contains(2, 1));
+        let search = Search::new("apple\\c", SearchOptions::default(), &values(), 10, Some(0));
+        let mut search = search.unwrap();
+        assert_eq!(search.len(), 2);
+        assert!(search.contains(10, 0));
+        assert!(search.contains(13, 0));
+        assert_eq!(search.seek(11), Some((13, 0)));
+    }
+
+    #[test]
+    fn test_search_navigation_wraps() {
+        let mut search = search_text("e", &values(), 0);
+        assert_eq!(search.seek(3), Some((3, 0)));
+        assert_eq!(search.next_match(), Some((0, 0)));
+        assert_eq!(search.prev_match(), Some((3, 0)));
+        assert_eq!(search.prev_match(), Some((2, 1)));
+        assert_eq!(search.seek(4), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_search_window() {
+        let search = search_text("apple", &values(), 100);
+        assert!(search.contains(102, 1));
+        assert!(search.contains(103, 0));
+    }
+
+    #[test]
+    fn test_search_no_matches() {
+        let mut search = search_text("kiwi", &values(), 0);
+        assert!(search.is_empty());
+        assert_eq!(search.seek(0), None);
+        assert_eq!(search.next_match(), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::app::Tabs;
+
+/// Files loaded and tables opened with `:as`, saved with `:session save` and restored
+/// with `--session`. They are kept one per line, `file <name> <path>` for the files,
+/// with the name their table is registered under, and `table <name> = <query>` for the
+/// tables, whose queries run again in order.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    pub files: Vec<(String, PathBuf)>,
+    pub tables: Vec<(String, String)>,
+}
+
+impl Session {
+    /// Files and tables of the tabs, with the names of the tabs that cannot be restored:
+    /// those read from stdin or a URL, and the tables that are not the result of a query.
+    pub fn from_tabs(tabs: &Tabs) -> (Self, Vec<String>) {
+        let mut session = Self::default();
+        let mut skipped = Vec::new();
+        for tabular in tabs.iter() {
+            match (&tabular.origin, &tabular.derivation) {
+                (Some(origin), _) => match origin.source.path() {
+                    Some(path) => {
+                        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+                        if session.name(&path).is_none() {
+                            session.files.push((tabular.table_name.clone(), path));
+                        }
+                    }
+                    None => skipped.push(tabular.name.clone()),
+                },
+                (None, Some(query)) => session
+                    .tables
+                    .push((tabular.table_name.clone(), query.clone())),
+                (None, None) => skipped.push(tabular.table_name.clone()),
+            }
+        }
+        (session, skipped)
+    }
+
+    /// Name of the table of the file at the absolute `path`, the tables of databases
+    /// keeping their own names.
+    pub fn name(&self, path: &Path) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|(_, file)| file == path)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Reads the session from `path`. The files are relative to the directory of the
+    /// session, and must still exist.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut session = Self::default();
+        for (idx, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, file)) = line
+                .strip_prefix("file ")
+                .and_then(|file| file.split_once(' '))
+            {
+                let file = dir.join(file.trim());
+                match fs::canonicalize(&file) {
+                    Ok(file) => session.files.push((name.to_owned(), file)),
+                    Err(_) => {
+                        return Err(invalid(format!(
+                            "the file {} of the session is missing",
+                            file.display()
+                        )))
+                    }
+                }
+            } else if let Some((name, query)) = line
+                .strip_prefix("table ")
+                .and_then(|table| table.split_once(" = "))
+            {
+                session
+                    .tables
+                    .push((name.trim().to_owned(), query.trim().to_owned()));
+            } else {
+                return Err(invalid(format!(
+                    "line {}: expected 'file <name> <path>' or 'table <name> = <query>'",
+                    idx + 1
+                )));
+            }
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = fs::File::create(path)?;
+        for (name, path) in &self.files {
+            writeln!(file, "file {} {}", name, path.display())?;
+        }
+        for (name, query) in &self.tables {
+            // queries are stored one per line
+            let query = query.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+            writeln!(file, "table {} = {}", name, query)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tabiew-session-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let data = fs::canonicalize(&dir).unwrap().join("orders.csv");
+        fs::write(&data, "id,amount\n1,10\n").unwrap();
+        let path = dir.join("session.tw");
+
+        let session = Session {
+            files: vec![("df1".to_owned(), data.clone())],
+            tables: vec![
+                (
+                    "big".to_owned(),
+                    "SELECT *\nFROM orders\nWHERE amount > 5".to_owned(),
+                ),
+                ("top".to_owned(), "SELECT * FROM big LIMIT 1".to_owned()),
+            ],
+        };
+        session.save(&path).unwrap();
+        let loaded = Session::from_file(&path).unwrap();
+        assert_eq!(loaded.files, [("df1".to_owned(), data.clone())]);
+        assert_eq!(loaded.name(&data), Some("df1"));
+        assert_eq!(
+            loaded.tables,
+            [
+                (
+                    "big".to_owned(),
+                    "SELECT * FROM orders WHERE amount > 5".to_owned()
+                ),
+                ("top".to_owned(), "SELECT * FROM big LIMIT 1".to_owned()),
+            ]
+        );
+
+        // relative to the session, and checked
+        fs::write(&path, "# orders\nfile df orders.csv\n").unwrap();
+        assert_eq!(
+            Session::from_file(&path).unwrap().files,
+            [("df".to_owned(), data)]
+        );
+        fs::write(&path, "file df customers.csv\n").unwrap();
+        let error = Session::from_file(&path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "the file {} of the session is missing",
+                dir.join("customers.csv").display()
+            )
+        );
+        fs::write(&path, "file df orders.csv\ntables big\n").unwrap();
+        let error = Session::from_file(&path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: expected 'file <name> <path>' or 'table <name> = <query>'"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{app::Sort, utils::config_dir};
+
+/// Maximum number of files whose place is kept in the state file.
+pub const STATES_CAPACITY: usize = 100;
+
+/// Place in a table: the selected cell, the scroll, the sort, the directions the columns
+/// were last sorted in and the hidden columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Place {
+    pub select: usize,
+    pub offset: usize,
+    pub select_column: usize,
+    pub column_offset: usize,
+    pub sort: Option<Sort>,
+    pub directions: Vec<Sort>,
+    pub hidden: Vec<String>,
+}
+
+/// Modification time, in nanoseconds since the epoch, and size of a file, which tell
+/// whether it changed.
+type Stamp = (u64, u64);
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_nanos() as u64, metadata.len()))
+}
+
+/// Places in the tables of the files opened lately, restored when a file is opened
+/// again. They are kept in `state` in the config directory, one line per file with its
+/// path, stamp and place, the latest last. The directions are a count followed by as
+/// many fields, so that the hidden columns can end the line.
+#[derive(Debug, Default)]
+pub struct States {
+    entries: Vec<(PathBuf, Stamp, Place)>,
+    path: Option<PathBuf>,
+}
+
+impl States {
+    /// Loads the places from `state` in the config directory. A missing or unreadable
+    /// file gives no places, which are still saved.
+    pub fn load() -> Self {
+        match config_dir() {
+            Some(dir) => Self::from_file(dir.join("state")),
+            None => Self::default(),
+        }
+    }
+
+    pub fn from_file(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .map(|content| content.lines().filter_map(parse_entry).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    /// Place saved for the file at the absolute `file`, unless the file changed since.
+    pub fn get(&self, file: &Path) -> Option<&Place> {
+        let stamp = stamp(file)?;
+        self.entries
+            .iter()
+            .find(|(path, saved, _)| path == file && *saved == stamp)
+            .map(|(_, _, place)| place)
+    }
+
+    /// Remembers `place` for the file at the absolute `file`, as it is now.
+    pub fn insert(&mut self, file: &Path, place: Place) {
+        let Some(stamp) = stamp(file) else {
+            return;
+        };
+        self.entries.retain(|(path, _, _)| path != file);
+        self.entries.push((file.to_owned(), stamp, place));
+        let excess = self.entries.len().saturating_sub(STATES_CAPACITY);
+        self.entries.drain(..excess);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::File::create(path)?;
+        for (path, (modified, size), place) in &self.entries {
+            let sort = place.sort.as_ref().map(sort_field).unwrap_or_default();
+            let fields = [
+                path.to_string_lossy().into_owned(),
+                modified.to_string(),
+                size.to_string(),
+                place.select.to_string(),
+                place.offset.to_string(),
+                place.select_column.to_string(),
+                place.column_offset.to_string(),
+                sort,
+                place.directions.len().to_string(),
+            ];
+            let directions = place.directions.iter().map(sort_field);
+            let fields = fields
+                .into_iter()
+                .chain(directions)
+                .chain(place.hidden.iter().cloned())
+                .map(|field| escape(&field));
+            writeln!(file, "{}", fields.collect::<Vec<_>>().join("\t"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a line of the state file, `None` if it is malformed.
+fn parse_entry(line: &str) -> Option<(PathBuf, Stamp, Place)> {
+    let mut fields = line.split('\t').map(unescape);
+    let path = PathBuf::from(fields.next()?);
+    let mut number = || fields.next()?.parse::<u64>().ok();
+    let stamp = (number()?, number()?);
+    let (select, offset) = (number()? as usize, number()? as usize);
+    let (select_column, column_offset) = (number()? as usize, number()? as usize);
+    let sort = parse_sort(&fields.next()?);
+    let count = fields.next()?.parse::<usize>().ok()?;
+    let directions = fields
+        .by_ref()
+        .take(count)
+        .map(|field| parse_sort(&field))
+        .collect::<Option<Vec<_>>>()?;
+    let place = Place {
+        select,
+        offset,
+        select_column,
+        column_offset,
+        sort,
+        directions,
+        hidden: fields.collect(),
+    };
+    Some((path, stamp, place))
+}
+
+/// Column name prefixed with `+` when sorted in ascending order, or `-` in descending.
+fn sort_field(sort: &Sort) -> String {
+    match sort.descending {
+        true => format!("-{}", sort.column),
+        false => format!("+{}", sort.column),
+    }
+}
+
+fn parse_sort(field: &str) -> Option<Sort> {
+    let (column, descending) = match field.split_at_checked(1)? {
+        ("+", column) => (column, false),
+        ("-", column) => (column, true),
+        _ => return None,
+    };
+    Some(Sort {
+        column: column.to_owned(),
+        descending,
+    })
+}
+
+/// Escapes the separators of the state file in a field.
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(field: &str) -> String {
+    let mut text = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => text.push('\t'),
+            Some('n') => text.push('\n'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_states() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("tabiew-state-{}", std::process::id()));
+        let file = dir.join(format!("tabiew-state-{}.csv", std::process::id()));
+        fs::write(&file, "a,b\n1,2\n").unwrap();
+        let place = Place {
+            select: 12,
+            offset: 3,
+            select_column: 1,
+            column_offset: 0,
+            sort: Some(Sort {
+                column: "price\tusd".to_owned(),
+                descending: true,
+            }),
+            directions: vec![Sort {
+                column: "id".to_owned(),
+                descending: false,
+            }],
+            hidden: vec!["a\\b".to_owned(), "c".to_owned()],
+        };
+        let mut states = States::from_file(path.clone());
+        states.insert(&file, place.clone());
+        states.save().unwrap();
+        assert_eq!(States::from_file(path.clone()).get(&file), Some(&place));
+
+        // a changed file starts over
+        fs::write(&file, "a,b\n1,2\n3,4\n").unwrap();
+        assert_eq!(States::from_file(path.clone()).get(&file), None);
+        fs::remove_file(path).unwrap();
+        fs::remove_file(file).unwrap();
+    }
+}
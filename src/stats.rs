@@ -0,0 +1,379 @@
+use polars::{
+    datatypes::{AnyValue, DataType},
+    frame::DataFrame,
+    lazy::{dsl::col, frame::IntoLazy},
+    prelude::{len, Expr, SortMultipleOptions},
+};
+
+use crate::{app::AppResult, utils::any_value_into_string};
+
+const TOP_COUNT: &str = "__tabiew_top_count";
+
+/// Values listed by default in the value counts, see [`ValueCounts::new`].
+pub const VALUE_COUNTS_TOP: usize = 20;
+
+/// Buckets of the histograms by default, see [`Histogram::new`].
+pub const HISTOGRAM_BUCKETS: usize = 20;
+
+/// Summary statistics of a column, as label and value pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub column: String,
+    pub stats: Vec<(&'static str, String)>,
+}
+
+impl ColumnStats {
+    /// Computes the statistics of `column`: count, null count, min, max, mean, median,
+    /// and std for numeric columns; count, null count, unique, top value, min, and max
+    /// for the others. Statistics that do not apply show as n/a.
+    pub fn new(data_frame: &DataFrame, column: &str) -> AppResult<Self> {
+        let numeric = data_frame.column(column)?.dtype().is_numeric();
+        let exprs: Vec<(&'static str, Expr)> = if numeric {
+            vec![
+                ("count", col(column).count()),
+                ("nulls", col(column).null_count()),
+                ("min", col(column).min()),
+                ("max", col(column).max()),
+                ("mean", col(column).mean()),
+                ("median", col(column).median()),
+                ("std", col(column).std(1)),
+            ]
+        } else {
+            vec![
+                ("count", col(column).count()),
+                ("nulls", col(column).null_count()),
+                ("unique", col(column).drop_nulls().n_unique()),
+                ("min", col(column).min()),
+                ("max", col(column).max()),
+            ]
+        };
+        let (labels, exprs): (Vec<_>, Vec<_>) = exprs
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (label, expr))| (label, expr.alias(&idx.to_string())))
+            .unzip();
+        let values = data_frame.clone().lazy().select(exprs).collect()?;
+        let mut stats = labels
+            .into_iter()
+            .zip(values.get_columns())
+            .map(|(label, series)| Ok((label, stat_value(series.get(0)?))))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        if !numeric {
+            let top = data_frame
+                .clone()
+                .lazy()
+                .filter(col(column).is_not_null())
+                .group_by([col(column)])
+                .agg([len().alias(TOP_COUNT)])
+                // ties go to the smallest value so the top value is stable
+                .sort(
+                    [TOP_COUNT, column],
+                    SortMultipleOptions::default().with_order_descending_multi([true, false]),
+                )
+                .limit(1)
+                .collect()?;
+            let top = match (top.column(column)?.get(0), top.column(TOP_COUNT)?.get(0)) {
+                (Ok(value), Ok(count)) => format!(
+                    "{} ({})",
+                    any_value_into_string(value),
+                    any_value_into_string(count)
+                ),
+                _ => "n/a".to_owned(),
+            };
+            stats.insert(3, ("top", top));
+        }
+
+        Ok(Self {
+            column: column.to_owned(),
+            stats,
+        })
+    }
+}
+
+/// Number of rows holding each value of a column, the most frequent first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueCounts {
+    pub column: String,
+    /// Values, null included, and their counts.
+    pub counts: Vec<(String, usize)>,
+    /// Number of values left out of the list.
+    pub more: usize,
+    /// Number of rows counted.
+    pub total: usize,
+}
+
+impl ValueCounts {
+    /// Counts the values of `column`, listing the `top` most frequent ones. Ties go to
+    /// the smallest value, null last.
+    pub fn new(data_frame: &DataFrame, column: &str, top: usize) -> AppResult<Self> {
+        let counts = data_frame
+            .clone()
+            .lazy()
+            .group_by([col(column)])
+            .agg([len().alias(TOP_COUNT)])
+            .sort(
+                [TOP_COUNT, column],
+                SortMultipleOptions::default()
+                    .with_order_descending_multi([true, false])
+                    .with_nulls_last(true),
+            )
+            .collect()?;
+        let values = counts.column(column)?;
+        let lens = counts.column(TOP_COUNT)?;
+        let listed = (0..counts.height().min(top))
+            .map(|idx| {
+                let value = match values.get(idx)? {
+                    AnyValue::Null => "null".to_owned(),
+                    value => any_value_into_string(value),
+                };
+                let len = lens.get(idx)?.extract::<usize>().unwrap_or_default();
+                Ok((value, len))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(Self {
+            column: column.to_owned(),
+            more: counts.height() - listed.len(),
+            counts: listed,
+            total: data_frame.height(),
+        })
+    }
+}
+
+/// Distribution of the values of a numeric column, counted in buckets of the same
+/// width splitting the range from the minimum to the maximum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub column: String,
+    /// Number of values in each bucket, from the smallest values. A single bucket holds
+    /// them all when they are the same, there is none without values.
+    pub buckets: Vec<u64>,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Nulls and NaN, which are not counted in the buckets.
+    pub nulls: usize,
+}
+
+impl Histogram {
+    /// Counts the finite values of `column` in `buckets` buckets.
+    pub fn new(data_frame: &DataFrame, column: &str, buckets: usize) -> AppResult<Self> {
+        let series = data_frame.column(column)?;
+        if !series.dtype().is_numeric() {
+            return Err(format!("{} is not a numeric column", column).into());
+        }
+        let values = series
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|value| value.is_finite())
+            .collect::<Vec<_>>();
+        let nulls = series.len() - values.len();
+        let Some(min) = values.iter().copied().reduce(f64::min) else {
+            return Ok(Self {
+                column: column.to_owned(),
+                buckets: Vec::new(),
+                min: 0.0,
+                max: 0.0,
+                mean: 0.0,
+                nulls,
+            });
+        };
+        let max = values.iter().copied().fold(min, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let len = if min == max { 1 } else { buckets.max(1) };
+        let mut counts = vec![0; len];
+        let width = (max - min) / len as f64;
+        for value in values {
+            let bucket = if width > 0.0 {
+                (((value - min) / width) as usize).min(len - 1)
+            } else {
+                0
+            };
+            counts[bucket] += 1;
+        }
+        Ok(Self {
+            column: column.to_owned(),
+            buckets: counts,
+            min,
+            max,
+            mean,
+            nulls,
+        })
+    }
+}
+
+fn stat_value(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => "n/a".to_owned(),
+        value => any_value_into_string(value),
+    }
+}
+
+/// Overview of the columns of a table, listing the name, type and number of nulls of
+/// each, with one of them selected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaOverview {
+    /// Names, types, and null counts, unknown while `--lazy` loads part of the rows.
+    pub columns: Vec<(String, String, Option<usize>)>,
+    /// Number of hidden columns, which are not listed.
+    pub hidden: usize,
+    pub selected: usize,
+}
+
+impl SchemaOverview {
+    /// Lists the `columns` of `data_frame`, counting their nulls if `nulls` is set.
+    pub fn new(data_frame: &DataFrame, columns: &[String], nulls: bool) -> AppResult<Self> {
+        let columns = columns
+            .iter()
+            .map(|name| {
+                let series = data_frame.column(name)?;
+                Ok((
+                    name.clone(),
+                    series.dtype().to_string(),
+                    nulls.then(|| series.null_count()),
+                ))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(Self {
+            hidden: data_frame.width().saturating_sub(columns.len()),
+            columns,
+            selected: 0,
+        })
+    }
+
+    pub fn up(&mut self, rows: usize) {
+        self.selected = self.selected.saturating_sub(rows);
+    }
+
+    pub fn down(&mut self, rows: usize) {
+        self.selected = (self.selected + rows).min(self.columns.len().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn stat<'a>(stats: &'a ColumnStats, label: &str) -> &'a str {
+        &stats.stats.iter().find(|(l, _)| *l == label).unwrap().1
+    }
+
+    #[test]
+    fn test_numeric_stats() {
+        let df = df! { "price" => [Some(1.0), Some(2.0), None, Some(6.0)] }.unwrap();
+        let stats = ColumnStats::new(&df, "price").unwrap();
+        assert_eq!(stat(&stats, "count"), "3");
+        assert_eq!(stat(&stats, "nulls"), "1");
+        assert_eq!(stat(&stats, "min"), "1");
+        assert_eq!(stat(&stats, "max"), "6");
+        assert_eq!(stat(&stats, "mean"), "3");
+        assert_eq!(stat(&stats, "median"), "2");
+        assert_eq!(stat(&stats, "std"), "2.6457513110645907");
+    }
+
+    #[test]
+    fn test_string_stats() {
+        let df = df! { "city" => [Some("Paris"), Some("Lyon"), None, Some("Paris")] }.unwrap();
+        let stats = ColumnStats::new(&df, "city").unwrap();
+        let labels = stats.stats.iter().map(|(l, _)| *l).collect::<Vec<_>>();
+        assert_eq!(labels, ["count", "nulls", "unique", "top", "min", "max"]);
+        assert_eq!(stat(&stats, "count"), "3");
+        assert_eq!(stat(&stats, "unique"), "2");
+        assert_eq!(stat(&stats, "top"), "Paris (2)");
+        assert_eq!(stat(&stats, "min"), "Lyon");
+    }
+
+    #[test]
+    fn test_value_counts() {
+        let df = df! {
+            "city" => [Some("Paris"), None, Some("Lyon"), Some("Paris"), Some("Nice"), None],
+        }
+        .unwrap();
+        let counts = ValueCounts::new(&df, "city", 3).unwrap();
+        assert_eq!(
+            counts.counts,
+            [
+                ("Paris".to_owned(), 2),
+                ("null".to_owned(), 2),
+                ("Lyon".to_owned(), 1)
+            ]
+        );
+        assert_eq!(counts.more, 1);
+        assert_eq!(counts.total, 6);
+
+        let df = df! { "price" => [1, 1, 1] }.unwrap();
+        let counts = ValueCounts::new(&df, "price", VALUE_COUNTS_TOP).unwrap();
+        assert_eq!(counts.counts, [("1".to_owned(), 3)]);
+        assert_eq!(counts.more, 0);
+    }
+
+    #[test]
+    fn test_schema() {
+        let df = df! {
+            "id" => [1, 2, 3],
+            "city" => [Some("Paris"), None, None],
+            "price" => [Some(1.5), Some(2.0), None],
+        }
+        .unwrap();
+        let columns = ["price".to_owned(), "id".to_owned()];
+        let mut schema = SchemaOverview::new(&df, &columns, true).unwrap();
+        assert_eq!(
+            schema.columns,
+            [
+                ("price".to_owned(), "f64".to_owned(), Some(1)),
+                ("id".to_owned(), "i32".to_owned(), Some(0))
+            ]
+        );
+        assert_eq!(schema.hidden, 1);
+        schema.down(5);
+        assert_eq!(schema.selected, 1);
+        schema.up(1);
+        assert_eq!(schema.selected, 0);
+
+        let schema = SchemaOverview::new(&df, &columns, false).unwrap();
+        assert_eq!(schema.columns[0].2, None);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let df = df! {
+            "price" => [Some(0.0), Some(1.0), None, Some(4.0), Some(10.0), Some(f64::NAN)],
+            "city" => ["a", "b", "c", "d", "e", "f"],
+        }
+        .unwrap();
+        let histogram = Histogram::new(&df, "price", 5).unwrap();
+        assert_eq!(histogram.buckets, [2, 0, 1, 0, 1]);
+        assert_eq!((histogram.min, histogram.max), (0.0, 10.0));
+        assert_eq!(histogram.mean, 3.75);
+        assert_eq!(histogram.nulls, 2);
+        assert!(Histogram::new(&df, "city", 5).is_err());
+
+        let df = df! { "price" => [3, 3, 3] }.unwrap();
+        let histogram = Histogram::new(&df, "price", HISTOGRAM_BUCKETS).unwrap();
+        assert_eq!(histogram.buckets, [3]);
+        assert_eq!(histogram.mean, 3.0);
+
+        let df = df! { "price" => [None::<i64>, None] }.unwrap();
+        let histogram = Histogram::new(&df, "price", HISTOGRAM_BUCKETS).unwrap();
+        assert!(histogram.buckets.is_empty());
+        assert_eq!(histogram.nulls, 2);
+    }
+
+    #[test]
+    fn test_all_null_stats() {
+        let df = df! { "empty" => [None::<i64>, None] }.unwrap();
+        let stats = ColumnStats::new(&df, "empty").unwrap();
+        assert_eq!(stat(&stats, "count"), "0");
+        assert_eq!(stat(&stats, "nulls"), "2");
+        assert_eq!(stat(&stats, "mean"), "n/a");
+        assert_eq!(stat(&stats, "std"), "n/a");
+
+        let df = df! { "empty" => [None::<&str>, None] }.unwrap();
+        let stats = ColumnStats::new(&df, "empty").unwrap();
+        assert_eq!(stat(&stats, "top"), "n/a");
+        assert_eq!(stat(&stats, "unique"), "0");
+    }
+}
@@ -1,26 +1,178 @@
+use std::{fs, path::Path, str::FromStr, sync::OnceLock};
+
+use clap::ValueEnum;
 use ratatui::style::{Color, Style, Stylize};
 
+use crate::{app::AppResult, args::AppTheme};
+
 pub trait Styler {
-    fn table_header() -> Style;
-    fn table_header_cell(col: usize) -> Style;
-    fn table_row(row: usize) -> Style;
-    fn table_highlight() -> Style;
-    fn table_cell(row: usize, col: usize) -> Style;
-    fn status_bar_red() -> Style;
-    fn status_bar_green() -> Style;
-    fn status_bar_blue() -> Style;
-    fn item_block() -> Style;
+    fn table_header(&self) -> Style;
+    fn table_header_cell(&self, col: usize) -> Style;
+    fn table_header_dtype(&self, col: usize) -> Style;
+    fn table_row(&self, row: usize, stripes: bool) -> Style;
+    fn table_highlight(&self) -> Style;
+    fn table_range(&self) -> Style;
+    fn table_cell(&self, row: usize, col: usize) -> Style;
+    fn table_match(&self) -> Style;
+    fn table_null(&self) -> Style;
+    /// Symbol and style of a boolean shown with `--symbols`.
+    fn table_boolean(&self, value: bool) -> (&'static str, Style);
+    /// Style of the values of a categorical column shown with `--symbols`, by the
+    /// index of their color.
+    fn table_category(&self, index: usize) -> Style;
+    fn table_border(&self) -> Style;
+    fn table_separator(&self) -> Style;
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style;
+    fn json_string(&self) -> Style;
+    fn json_literal(&self) -> Style;
+    fn status_bar_red(&self) -> Style;
+    fn status_bar_green(&self) -> Style;
+    fn status_bar_blue(&self) -> Style;
+    fn item_block(&self) -> Style;
 }
 
 pub struct Monokai;
 pub struct Terminal;
+pub struct SolarizedDark;
+pub struct SolarizedLight;
+pub struct Nord;
+
+/// Theme without colors, with `--no-color` or `NO_COLOR`, emphasizing by bold,
+/// underlined and reversed text only.
+pub struct Monochrome;
+
+/// Theme styled by the colors of a theme file, set once with [`ConfigTheme::set`].
+pub struct ConfigTheme;
+
+/// Colors of a theme file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeColors {
+    pub fg: Color,
+    pub bg: Color,
+    pub accent: Color,
+    pub header: Color,
+    pub selection: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        Self {
+            fg: Color::Reset,
+            bg: Color::Reset,
+            accent: Color::Cyan,
+            header: Color::Cyan,
+            selection: Color::Yellow,
+        }
+    }
+}
+
+impl ThemeColors {
+    pub fn from_file(path: &Path) -> AppResult<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// Parses `key = "color"` lines of a TOML file, where the keys are `fg`, `bg`,
+    /// `accent`, `header`, and `selection`, and the colors are names like
+    /// `"light blue"`, `"#rrggbb"` hex codes, or 256-color indices. Keys left out
+    /// keep the colors of the terminal theme.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut colors = Self::default();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |msg: String| format!("line {}: {}", idx + 1, msg);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected <key> = \"<color>\"".to_owned()))?;
+            let value = parse_string(value.trim())
+                .ok_or_else(|| error(format!("expected a quoted color, found {}", value.trim())))?;
+            let color =
+                Color::from_str(value).map_err(|_| error(format!("invalid color '{}'", value)))?;
+            match key.trim() {
+                "fg" => colors.fg = color,
+                "bg" => colors.bg = color,
+                "accent" => colors.accent = color,
+                "header" => colors.header = color,
+                "selection" => colors.selection = color,
+                key => return Err(error(format!("unknown key '{}'", key))),
+            }
+        }
+        Ok(colors)
+    }
+}
+
+/// Content of a TOML basic or literal string, optionally followed by a comment.
+fn parse_string(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let (content, rest) = value[1..].split_once(quote)?;
+    let rest = rest.trim_start();
+    (rest.is_empty() || rest.starts_with('#')).then_some(content)
+}
+
+static CONFIG_THEME: OnceLock<ThemeColors> = OnceLock::new();
+
+impl ConfigTheme {
+    /// Sets the colors of the theme, which can only be done once.
+    pub fn set(colors: ThemeColors) {
+        let _ = CONFIG_THEME.set(colors);
+    }
+
+    /// Whether the colors of a theme file were set.
+    pub fn is_set() -> bool {
+        CONFIG_THEME.get().is_some()
+    }
+
+    fn colors() -> &'static ThemeColors {
+        CONFIG_THEME.get_or_init(ThemeColors::default)
+    }
+}
+
+/// Styler drawing the interface in a theme, Monokai for `auto` which is only picked
+/// from the terminal background on launch.
+pub fn styler(theme: &AppTheme) -> &'static dyn Styler {
+    match theme {
+        AppTheme::Monokai | AppTheme::Auto => &Monokai,
+        AppTheme::Terminal => &Terminal,
+        AppTheme::SolarizedDark => &SolarizedDark,
+        AppTheme::SolarizedLight => &SolarizedLight,
+        AppTheme::Nord => &Nord,
+        AppTheme::Monochrome => &Monochrome,
+        AppTheme::Config => &ConfigTheme,
+    }
+}
+
+/// Themes in the order `:theme` cycles through them, ending with the colors of the
+/// theme file when one was loaded.
+pub fn themes() -> Vec<AppTheme> {
+    let mut themes = AppTheme::value_variants()
+        .iter()
+        .filter(|theme| !matches!(theme, AppTheme::Auto | AppTheme::Config))
+        .cloned()
+        .collect::<Vec<_>>();
+    if ConfigTheme::is_set() {
+        themes.push(AppTheme::Config);
+    }
+    themes
+}
+
+/// Name of a theme as given to `--theme` and `:theme`, `file` for the colors of the
+/// theme file.
+pub fn theme_name(theme: &AppTheme) -> String {
+    theme
+        .to_possible_value()
+        .map(|value| value.get_name().to_owned())
+        .unwrap_or_else(|| "file".to_owned())
+}
 
 impl Styler for Monokai {
-    fn table_header() -> Style {
+    fn table_header(&self) -> Style {
         Style::default().bg(Color::from_u32(0x001c191d))
     }
 
-    fn table_header_cell(col: usize) -> Style {
+    fn table_header_cell(&self, col: usize) -> Style {
         Style::default()
             .fg(match col % 6 {
                 0 => Color::from_u32(0x00ff6188),
@@ -34,41 +186,94 @@ impl Styler for Monokai {
             .bold()
     }
 
-    fn table_row(row: usize) -> Style {
-        if row % 2 == 0 {
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00939293)).italic()
+    }
+
+    fn table_row(&self, row: usize, stripes: bool) -> Style {
+        if !stripes || row.is_multiple_of(2) {
             Style::new().bg(Color::from_u32(0x00232024))
         } else {
             Style::new().bg(Color::from_u32(0x001c191d))
         }
     }
 
-    fn table_highlight() -> Style {
+    fn table_highlight(&self) -> Style {
         Style::new().bg(Color::from_u32(0x00c89f2d))
     }
 
-    fn table_cell(_row: usize, _col: usize) -> Style {
+    fn table_range(&self) -> Style {
+        Style::new().bg(Color::from_u32(0x00524228))
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
         Style::default().fg(Color::White)
     }
 
-    fn status_bar_red() -> Style {
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x0078dce8))
+            .fg(Color::from_u32(0x001c191d))
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00727072))
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Color::from_u32(0x00a9dc76)))
+        } else {
+            ("✗", Style::default().fg(Color::from_u32(0x00ff6188)))
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(self.table_header_cell(index).fg.unwrap_or_default())
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x001c191d))
+            .fg(Color::from_u32(0x00c89f2d))
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().fg(Color::from_u32(0x00727072))
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).fg(Color::from_u32(0x00727072))
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00ffd866))
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00ab9df2))
+    }
+
+    fn status_bar_red(&self) -> Style {
         Style::default()
             .bg(Color::from_u32(0x00d02d00))
             .fg(Color::White)
     }
 
-    fn status_bar_green() -> Style {
+    fn status_bar_green(&self) -> Style {
         Style::default()
             .bg(Color::from_u32(0x00008f1f))
             .fg(Color::White)
     }
 
-    fn status_bar_blue() -> Style {
+    fn status_bar_blue(&self) -> Style {
         Style::default()
             .bg(Color::from_u32(0x00007dd0))
             .fg(Color::White)
     }
 
-    fn item_block() -> Style {
+    fn item_block(&self) -> Style {
         Style::new()
             .bg(Color::from_u32(0x001c191d))
             .fg(Color::from_u32(0x00c89f2d))
@@ -76,39 +281,694 @@ impl Styler for Monokai {
 }
 
 impl Styler for Terminal {
-    fn table_header() -> Style {
+    fn table_header(&self) -> Style {
         Style::default().bg(Color::Cyan).fg(Color::Black)
     }
 
-    fn table_header_cell(_col: usize) -> Style {
+    fn table_header_cell(&self, _col: usize) -> Style {
         Style::default()
     }
 
-    fn table_row(_row: usize) -> Style {
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().dim().italic()
+    }
+
+    fn table_row(&self, _row: usize, _stripes: bool) -> Style {
         Default::default()
     }
 
-    fn table_highlight() -> Style {
+    fn table_highlight(&self) -> Style {
         Style::default().bg(Color::Yellow).fg(Color::Black)
     }
 
-    fn table_cell(_row: usize, _col: usize) -> Style {
+    fn table_range(&self) -> Style {
+        Style::default().bg(Color::DarkGray).fg(Color::White)
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
         Style::default()
     }
 
-    fn status_bar_red() -> Style {
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Color::Magenta)
+            .fg(Color::Black)
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().dim()
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Color::Green))
+        } else {
+            ("✗", Style::default().fg(Color::Red))
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(match index % 6 {
+            0 => Color::Red,
+            1 => Color::Green,
+            2 => Color::Yellow,
+            3 => Color::Blue,
+            4 => Color::Magenta,
+            _ => Color::Cyan,
+        })
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default().fg(Color::Cyan)
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().dim()
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).dim()
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Color::Green)
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Color::Magenta)
+    }
+
+    fn status_bar_red(&self) -> Style {
         Style::default().bg(Color::Red).fg(Color::White)
     }
 
-    fn status_bar_green() -> Style {
+    fn status_bar_green(&self) -> Style {
         Style::default().bg(Color::Green).fg(Color::White)
     }
 
-    fn status_bar_blue() -> Style {
+    fn status_bar_blue(&self) -> Style {
         Style::default().bg(Color::Blue).fg(Color::White)
     }
 
-    fn item_block() -> Style {
+    fn item_block(&self) -> Style {
+        Style::default()
+    }
+}
+
+impl Styler for SolarizedDark {
+    fn table_header(&self) -> Style {
+        Style::default().bg(Color::from_u32(0x00073642))
+    }
+
+    fn table_header_cell(&self, col: usize) -> Style {
         Style::default()
+            .fg(match col % 6 {
+                0 => Color::from_u32(0x00268bd2),
+                1 => Color::from_u32(0x002aa198),
+                2 => Color::from_u32(0x00859900),
+                3 => Color::from_u32(0x00b58900),
+                4 => Color::from_u32(0x00cb4b16),
+                5 => Color::from_u32(0x00d33682),
+                _ => panic!("How!"),
+            })
+            .bold()
+    }
+
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00586e75)).italic()
+    }
+
+    fn table_row(&self, row: usize, stripes: bool) -> Style {
+        if !stripes || row.is_multiple_of(2) {
+            Style::new().bg(Color::from_u32(0x00002b36))
+        } else {
+            Style::new().bg(Color::from_u32(0x00073642))
+        }
+    }
+
+    fn table_highlight(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00b58900))
+            .fg(Color::from_u32(0x00002b36))
+    }
+
+    fn table_range(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00586e75))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00839496))
+    }
+
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x002aa198))
+            .fg(Color::from_u32(0x00002b36))
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00586e75))
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Color::from_u32(0x00859900)))
+        } else {
+            ("✗", Style::default().fg(Color::from_u32(0x00dc322f)))
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(self.table_header_cell(index).fg.unwrap_or_default())
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00002b36))
+            .fg(Color::from_u32(0x00b58900))
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().fg(Color::from_u32(0x00586e75))
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).fg(Color::from_u32(0x00586e75))
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x002aa198))
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00d33682))
+    }
+
+    fn status_bar_red(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00dc322f))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn status_bar_green(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00859900))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn status_bar_blue(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00268bd2))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn item_block(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00002b36))
+            .fg(Color::from_u32(0x00b58900))
+    }
+}
+
+impl Styler for SolarizedLight {
+    fn table_header(&self) -> Style {
+        Style::default().bg(Color::from_u32(0x00eee8d5))
+    }
+
+    fn table_header_cell(&self, col: usize) -> Style {
+        Style::default()
+            .fg(match col % 6 {
+                0 => Color::from_u32(0x00268bd2),
+                1 => Color::from_u32(0x002aa198),
+                2 => Color::from_u32(0x00859900),
+                3 => Color::from_u32(0x00b58900),
+                4 => Color::from_u32(0x00cb4b16),
+                5 => Color::from_u32(0x00d33682),
+                _ => panic!("How!"),
+            })
+            .bold()
+    }
+
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x0093a1a1)).italic()
+    }
+
+    fn table_row(&self, row: usize, stripes: bool) -> Style {
+        if !stripes || row.is_multiple_of(2) {
+            Style::new().bg(Color::from_u32(0x00fdf6e3))
+        } else {
+            Style::new().bg(Color::from_u32(0x00eee8d5))
+        }
+    }
+
+    fn table_highlight(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00b58900))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn table_range(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x0093a1a1))
+            .fg(Color::from_u32(0x00002b36))
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00586e75))
+    }
+
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x002aa198))
+            .fg(Color::from_u32(0x00fdf6e3))
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x0093a1a1))
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Color::from_u32(0x00859900)))
+        } else {
+            ("✗", Style::default().fg(Color::from_u32(0x00dc322f)))
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(self.table_header_cell(index).fg.unwrap_or_default())
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00fdf6e3))
+            .fg(Color::from_u32(0x00b58900))
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().fg(Color::from_u32(0x0093a1a1))
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).fg(Color::from_u32(0x0093a1a1))
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x002aa198))
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00d33682))
+    }
+
+    fn status_bar_red(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00dc322f))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn status_bar_green(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00859900))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn status_bar_blue(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00268bd2))
+            .fg(Color::from_u32(0x00fdf6e3))
+    }
+
+    fn item_block(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00fdf6e3))
+            .fg(Color::from_u32(0x00586e75))
+    }
+}
+
+impl Styler for Nord {
+    fn table_header(&self) -> Style {
+        Style::default().bg(Color::from_u32(0x003b4252))
+    }
+
+    fn table_header_cell(&self, col: usize) -> Style {
+        Style::default()
+            .fg(match col % 6 {
+                0 => Color::from_u32(0x0088c0d0),
+                1 => Color::from_u32(0x0081a1c1),
+                2 => Color::from_u32(0x008fbcbb),
+                3 => Color::from_u32(0x00a3be8c),
+                4 => Color::from_u32(0x00ebcb8b),
+                5 => Color::from_u32(0x00b48ead),
+                _ => panic!("How!"),
+            })
+            .bold()
+    }
+
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00616e88)).italic()
+    }
+
+    fn table_row(&self, row: usize, stripes: bool) -> Style {
+        if !stripes || row.is_multiple_of(2) {
+            Style::new().bg(Color::from_u32(0x002e3440))
+        } else {
+            Style::new().bg(Color::from_u32(0x003b4252))
+        }
+    }
+
+    fn table_highlight(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x005e81ac))
+            .fg(Color::from_u32(0x00eceff4))
+    }
+
+    fn table_range(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x00434c5e))
+            .fg(Color::from_u32(0x00eceff4))
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
+        Style::default().fg(Color::from_u32(0x00d8dee9))
+    }
+
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00ebcb8b))
+            .fg(Color::from_u32(0x002e3440))
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00616e88))
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Color::from_u32(0x00a3be8c)))
+        } else {
+            ("✗", Style::default().fg(Color::from_u32(0x00bf616a)))
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(self.table_header_cell(index).fg.unwrap_or_default())
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x002e3440))
+            .fg(Color::from_u32(0x0088c0d0))
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().fg(Color::from_u32(0x00616e88))
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).fg(Color::from_u32(0x00616e88))
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00a3be8c))
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Color::from_u32(0x00b48ead))
+    }
+
+    fn status_bar_red(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00bf616a))
+            .fg(Color::from_u32(0x002e3440))
+    }
+
+    fn status_bar_green(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x00a3be8c))
+            .fg(Color::from_u32(0x002e3440))
+    }
+
+    fn status_bar_blue(&self) -> Style {
+        Style::default()
+            .bg(Color::from_u32(0x0088c0d0))
+            .fg(Color::from_u32(0x002e3440))
+    }
+
+    fn item_block(&self) -> Style {
+        Style::new()
+            .bg(Color::from_u32(0x002e3440))
+            .fg(Color::from_u32(0x0088c0d0))
+    }
+}
+
+impl Styler for Monochrome {
+    fn table_header(&self) -> Style {
+        Style::default().bold()
+    }
+
+    fn table_header_cell(&self, _col: usize) -> Style {
+        Style::default().bold()
+    }
+
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().italic()
+    }
+
+    fn table_row(&self, _row: usize, _stripes: bool) -> Style {
+        Style::default()
+    }
+
+    fn table_highlight(&self) -> Style {
+        Style::default().reversed()
+    }
+
+    fn table_range(&self) -> Style {
+        Style::default().bold()
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
+        Style::default()
+    }
+
+    fn table_match(&self) -> Style {
+        Style::default().bold().underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().dim()
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().bold())
+        } else {
+            ("✗", Style::default().dim())
+        }
+    }
+
+    fn table_category(&self, _index: usize) -> Style {
+        Style::default()
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+    }
+
+    fn table_separator(&self) -> Style {
+        Style::default()
+    }
+
+    fn table_row_number(&self, _row: usize, _stripes: bool) -> Style {
+        Style::default().dim()
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default()
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().bold()
+    }
+
+    fn status_bar_red(&self) -> Style {
+        Style::default().bold().reversed()
+    }
+
+    fn status_bar_green(&self) -> Style {
+        Style::default().reversed()
+    }
+
+    fn status_bar_blue(&self) -> Style {
+        Style::default().reversed()
+    }
+
+    fn item_block(&self) -> Style {
+        Style::default()
+    }
+}
+
+impl Styler for ConfigTheme {
+    fn table_header(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().header)
+            .fg(Self::colors().bg)
+    }
+
+    fn table_header_cell(&self, _col: usize) -> Style {
+        Style::default().bold()
+    }
+
+    fn table_header_dtype(&self, _col: usize) -> Style {
+        Style::default().italic()
+    }
+
+    fn table_row(&self, _row: usize, _stripes: bool) -> Style {
+        Style::default().bg(Self::colors().bg).fg(Self::colors().fg)
+    }
+
+    fn table_highlight(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().selection)
+            .fg(Self::colors().bg)
+    }
+
+    fn table_range(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().header)
+            .fg(Self::colors().bg)
+    }
+
+    fn table_cell(&self, _row: usize, _col: usize) -> Style {
+        Style::default().fg(Self::colors().fg)
+    }
+
+    fn table_match(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().accent)
+            .fg(Self::colors().bg)
+            .underlined()
+    }
+
+    fn table_null(&self) -> Style {
+        Style::default().fg(Self::colors().fg).dim()
+    }
+
+    fn table_boolean(&self, value: bool) -> (&'static str, Style) {
+        if value {
+            ("✓", Style::default().fg(Self::colors().accent))
+        } else {
+            ("✗", Style::default().fg(Self::colors().fg).dim())
+        }
+    }
+
+    fn table_category(&self, index: usize) -> Style {
+        Style::default().fg(match index % 3 {
+            0 => Self::colors().accent,
+            1 => Self::colors().header,
+            _ => Self::colors().selection,
+        })
+    }
+
+    fn table_border(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().bg)
+            .fg(Self::colors().accent)
+    }
+
+    fn table_separator(&self) -> Style {
+        self.table_header().fg(Self::colors().fg).dim()
+    }
+
+    fn table_row_number(&self, row: usize, stripes: bool) -> Style {
+        self.table_row(row, stripes).fg(Self::colors().fg).dim()
+    }
+
+    fn json_string(&self) -> Style {
+        Style::default().fg(Self::colors().accent)
+    }
+
+    fn json_literal(&self) -> Style {
+        Style::default().fg(Self::colors().header)
+    }
+
+    fn status_bar_red(&self) -> Style {
+        Style::default().bg(Color::Red).fg(Color::White)
+    }
+
+    fn status_bar_green(&self) -> Style {
+        Style::default().bg(Color::Green).fg(Color::White)
+    }
+
+    fn status_bar_blue(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().accent)
+            .fg(Self::colors().bg)
+    }
+
+    fn item_block(&self) -> Style {
+        Style::default()
+            .bg(Self::colors().bg)
+            .fg(Self::colors().accent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme_colors() {
+        let colors = ThemeColors::parse(
+            "# solarized-ish\n\
+             fg = \"#839496\"\n\
+             bg = '#002b36' # base03\n\
+             accent = \"light blue\"\n\
+             selection = \"136\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            colors,
+            ThemeColors {
+                fg: Color::Rgb(0x83, 0x94, 0x96),
+                bg: Color::Rgb(0x00, 0x2b, 0x36),
+                accent: Color::LightBlue,
+                header: Color::Cyan,
+                selection: Color::Indexed(136),
+            }
+        );
+    }
+
+    #[test]
+    fn test_themes() {
+        let names = themes().iter().map(theme_name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "monokai",
+                "terminal",
+                "solarized-dark",
+                "solarized-light",
+                "nord",
+                "monochrome"
+            ]
+        );
+        assert_eq!(theme_name(&AppTheme::Config), "file");
+    }
+
+    #[test]
+    fn test_parse_theme_errors() {
+        assert!(ThemeColors::parse("fg = \"#12345\"")
+            .unwrap_err()
+            .contains("invalid color"));
+        assert!(ThemeColors::parse("fg = red").is_err());
+        assert!(ThemeColors::parse("\nborder = \"red\"")
+            .unwrap_err()
+            .starts_with("line 2: unknown key"));
     }
 }
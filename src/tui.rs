@@ -1,12 +1,43 @@
-use crate::app::{AppResult, StatusBar, Tabular};
+use crate::app::{App, AppResult};
 use crate::event::EventHandler;
-use crate::theme::Styler;
-use crate::ui;
+use crossterm::cursor;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::Backend;
 use ratatui::Terminal;
 use std::io;
 use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the terminal is in raw mode on the alternate screen, for the panic hook.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Installs a panic hook that restores the terminal before the panic message is printed.
+///
+/// Without it, a panic while the interface is shown leaves the terminal in raw mode
+/// on the alternate screen, and the message is lost with it.
+pub fn install_panic_hook() {
+    let panic_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic| {
+        if ACTIVE.swap(false, Ordering::SeqCst) {
+            // Failing to restore the terminal must not hide the message
+            let _ = restore();
+        }
+        panic_hook(panic);
+    }));
+}
+
+/// Reverts the terminal properties set by [`Tui::init`].
+fn restore() -> AppResult<()> {
+    terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        io::stderr(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    )?;
+    Ok(())
+}
 
 /// Representation of a terminal user interface.
 ///
@@ -28,18 +59,15 @@ impl<B: Backend> Tui<B> {
 
     /// Initializes the terminal interface.
     ///
-    /// It enables the raw mode and sets terminal properties.
-    pub fn init(&mut self) -> AppResult<()> {
+    /// It enables the raw mode and sets terminal properties, capturing the mouse
+    /// events if `mouse_capture` is set.
+    pub fn init(&mut self, mouse_capture: bool) -> AppResult<()> {
         terminal::enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
         crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
-
-        // Define a custom panic hook to reset the terminal properties.
-        // This way, you won't have your terminal messed up if an unexpected error happens.
-        let panic_hook = panic::take_hook();
-        panic::set_hook(Box::new(move |panic| {
-            Self::reset().expect("failed to reset the terminal");
-            panic_hook(panic);
-        }));
+        if mouse_capture {
+            crossterm::execute!(io::stderr(), EnableMouseCapture)?;
+        }
 
         self.terminal.hide_cursor()?;
         self.terminal.clear()?;
@@ -50,18 +78,8 @@ impl<B: Backend> Tui<B> {
     ///
     /// [`Draw`]: ratatui::Terminal::draw
     /// [`rendering`]: crate::ui::render
-    pub fn draw<Theme: Styler>(&mut self, tabular: &mut Tabular, status_bar: &mut StatusBar) -> AppResult<()> {
-        self.terminal.draw(|frame| ui::render::<Theme>(tabular, status_bar, frame))?;
-        Ok(())
-    }
-
-    /// Resets the terminal interface.
-    ///
-    /// This function is also used for the panic hook to revert
-    /// the terminal properties if unexpected errors occur.
-    fn reset() -> AppResult<()> {
-        terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen)?;
+    pub fn draw(&mut self, app: &mut App) -> AppResult<()> {
+        self.terminal.draw(|frame| app.render(frame))?;
         Ok(())
     }
 
@@ -69,7 +87,8 @@ impl<B: Backend> Tui<B> {
     ///
     /// It disables the raw mode and reverts back the terminal properties.
     pub fn exit(&mut self) -> AppResult<()> {
-        Self::reset()?;
+        ACTIVE.store(false, Ordering::SeqCst);
+        restore()?;
         self.terminal.show_cursor()?;
         Ok(())
     }
@@ -1,109 +1,934 @@
 
+use std::ops::Range;
+
 use itertools::{izip, Itertools};
+use polars::datatypes::AnyValue;
 use ratatui::{prelude::*, widgets::*};
 
 use crate::{
-    app::{StatusBar, Tabular},
+    app::{CellAreas, ColumnSymbols, Mode, StatusBar, Tabs, Tabular},
     command_pallete::CommandPallete,
-    theme::Styler,
-    utils::{line_count, TableValues},
+    pretty::Nested,
+    theme::{styler, Styler},
+    utils::{category_index, ellipsize, fit_columns, human_count, line_count, truncate, wrap_text},
 };
 
+/// Widest the column names get in the record view, longer ones are cut.
+const RECORD_LABEL_MAX_WIDTH: usize = 32;
+
+/// Widest the values get in the value counts, longer ones are cut.
+const VALUE_COUNTS_WIDTH: usize = 40;
+
+/// Widest the column names get in the schema overview, longer ones are cut.
+const SCHEMA_NAME_WIDTH: usize = 40;
+
+/// Widest the query of the view gets in the status bar, `Q` shows it in full.
+const VIEW_WIDTH: usize = 32;
+
 /// Renders the user interface widgets.
-pub fn render<Theme: Styler>(
-    tabular: &mut Tabular,
-    status_bar: &mut StatusBar,
-    frame: &mut Frame,
-) {
+pub fn render(tabs: &mut Tabs, status_bar: &mut StatusBar, frame: &mut Frame) {
+    let theme = styler(&status_bar.theme);
+    let tab_info = if tabs.len() > 1 {
+        format!(
+            "{} [{}/{}] ",
+            tabs.selected().name,
+            tabs.index() + 1,
+            tabs.len()
+        )
+    } else {
+        format!("{} ", tabs.selected().name)
+    };
+    let tabular = tabs.selected_mut();
+    // rows scrolled into view since the last frame
+    if let Err(error) = tabular.load_page() {
+        status_bar.error(error);
+    }
     let layout = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).split(frame.size());
 
     // Draw table / item
     if let Some(scroll) = &mut tabular.detailed_view {
         // Set visible rows = 0
         tabular.rendered_rows = 0;
+        tabular.cell_areas = Default::default();
         let space = layout[0].inner(Margin::new(1, 1));
         let title = format!(" {} ", tabular.select + 1);
 
         let values = tabular.table_values.get_row(tabular.select);
 
         let (paragraph, line_count) =
-            paragraph_from_headers_values::<Theme>(&title, &tabular.headers, &values, space.width);
+            paragraph_from_headers_values(&title, &tabular.headers, &values, space.width, theme);
 
         scroll.adjust(line_count, space.height as usize);
         frame.render_widget(paragraph.scroll(((*scroll).into(), 0)), layout[0]);
+    } else if let Some(offset) = &mut tabular.record_view {
+        tabular.rendered_rows = 0;
+        tabular.cell_areas = Default::default();
+        let height = tabular.table_values.height();
+        let block = Block::new()
+            .title(format!(
+                " row {}/{} ",
+                (tabular.select + 1).min(height),
+                height
+            ))
+            .borders(Borders::ALL)
+            .style(theme.item_block());
+        let space = block.inner(layout[0]);
+
+        // Keep the selected column in view
+        let visible = space.height as usize;
+        *offset = (*offset).clamp(
+            tabular
+                .select_column
+                .saturating_sub(visible.saturating_sub(1)),
+            tabular.select_column,
+        );
+        let label_width = tabular
+            .headers
+            .iter()
+            .map(|header| header.chars().count())
+            .max()
+            .unwrap_or_default()
+            .min(RECORD_LABEL_MAX_WIDTH);
+        let lines = tabular
+            .headers
+            .iter()
+            .enumerate()
+            .skip(*offset)
+            .take(visible)
+            .map(|(col, header)| {
+                let style = theme.table_header_cell(col);
+                let label = Span::styled(
+                    format!("{:<width$.width$} ", header, width = label_width),
+                    if col == tabular.select_column {
+                        style.reversed()
+                    } else {
+                        style
+                    },
+                );
+                let value = if tabular.highlight_nulls
+                    && tabular.table_values.is_null(tabular.select, col)
+                {
+                    Span::styled("∅", theme.table_null())
+                } else {
+                    let value = tabular
+                        .table_values
+                        .get(tabular.select, col)
+                        .unwrap_or_default();
+                    Span::styled(
+                        value.replace('\n', " "),
+                        theme.table_cell(tabular.select, col),
+                    )
+                };
+                Line::from(vec![label, value])
+            })
+            .collect_vec();
+        frame.render_widget(Paragraph::new(lines).block(block), layout[0]);
     } else {
-        // Set visible rows = table height - 1 (if header)
-        tabular.rendered_rows = layout[0].height.saturating_sub(1);
+        // Set visible rows = table height - header height (2 if the data types are shown)
+        // - the separator line under the header
+        let header_height = if tabular.show_types { 2 } else { 1 };
+        tabular.rendered_rows = layout[0].height.saturating_sub(header_height + 1);
         tabular.adjust_offset();
+        // rows uncovered by a taller table
+        if let Err(error) = tabular.load_page() {
+            status_bar.error(error);
+        }
+        if tabular.wraps() {
+            // with the heights of the rows loaded
+            tabular.adjust_offset();
+        }
+        let widths = tabular.render_widths();
+        let row_heights = match tabular.wraps() {
+            true => tabular.row_heights(&widths),
+            false => Vec::new(),
+        };
+
+        // Row numbers on the left, as wide as the largest visible one
+        let height = tabular.table_values.height();
+        let rows = match row_heights.is_empty() {
+            true => tabular.offset..(tabular.offset + tabular.rendered_rows as usize).min(height),
+            false => tabular.offset..tabular.offset + row_heights.len(),
+        };
+        let first = tabular.first_line_number;
+        let number_width = match tabular.line_numbers {
+            true => (rows.end + first).saturating_sub(1).to_string().len() + 1,
+            false => 0,
+        };
+        // a column marking the bookmarked rows while there are bookmarks
+        let marks = !tabular.bookmarks.is_empty();
+        let gutter_width = number_width as u16 + marks as u16;
+        let [gutter_area, table_area] =
+            Layout::horizontal([Constraint::Length(gutter_width), Constraint::Fill(1)])
+                .areas(layout[0]);
+        if gutter_width > 0 {
+            frame.render_widget(
+                Paragraph::new(
+                    std::iter::repeat_n(
+                        Line::styled("", theme.table_header()),
+                        header_height as usize + 1,
+                    )
+                    .chain(rows.flat_map(|row| {
+                        let lines = row_heights.get(row - tabular.offset).copied().unwrap_or(1);
+                        let mark = match tabular.is_bookmarked(row) {
+                            true => Span::styled("•", theme.table_match()),
+                            false => Span::raw(if marks { " " } else { "" }),
+                        };
+                        let number = match tabular.line_numbers {
+                            true => format!("{:>width$} ", row + first, width = number_width - 1),
+                            false => String::new(),
+                        };
+                        // the number on the first line of a wrapped row
+                        std::iter::once(Line::from(vec![
+                            mark,
+                            Span::styled(number, theme.table_row_number(row, tabular.stripes)),
+                        ]))
+                        .chain(std::iter::repeat_n(Line::raw(""), lines as usize - 1))
+                    }))
+                    .collect_vec(),
+                ),
+                gutter_area,
+            );
+        }
 
         let mut local_st = TableState::new()
             .with_offset(0)
             .with_selected(tabular.select.saturating_sub(tabular.offset));
 
+        // Frozen columns are drawn on their own on the left, separated by a border
+        let frozen_columns = fit_columns(
+            &widths,
+            0..tabular.frozen.min(widths.len()),
+            table_area.width as usize,
+        );
+        let mut cell_columns = column_areas(table_area.x, &frozen_columns);
+        let scroll_area = if frozen_columns.is_empty() {
+            table_area
+        } else {
+            let frozen_width = frozen_columns.iter().map(|(_, w)| w + 1).sum();
+            let areas = Layout::horizontal([Constraint::Length(frozen_width), Constraint::Fill(1)])
+                .split(table_area);
+            frame.render_stateful_widget(
+                tabulate(tabular, &frozen_columns, &row_heights, theme),
+                areas[0],
+                &mut local_st.clone(),
+            );
+            let border = Block::new()
+                .borders(Borders::LEFT)
+                .border_style(theme.table_border());
+            let inner = border.inner(areas[1]);
+            frame.render_widget(border, areas[1]);
+            inner
+        };
+
+        tabular.adjust_column_offset(scroll_area.width as usize);
+        let columns = fit_columns(
+            &widths,
+            tabular.column_offset..widths.len(),
+            scroll_area.width as usize,
+        );
+        tabular.rendered_columns = match (columns.first(), columns.last()) {
+            (Some((first, _)), Some((last, _))) => *first..last + 1,
+            _ => 0..0,
+        };
+        cell_columns.extend(column_areas(scroll_area.x, &columns));
+        let rows_y = table_area.y + header_height + 1;
+        tabular.cell_areas = CellAreas {
+            columns: cell_columns,
+            header: table_area.y..table_area.y + header_height,
+            rows: rows_y..rows_y + tabular.rendered_rows,
+            row_heights: row_heights.clone(),
+        };
         frame.render_stateful_widget(
-            tabulate::<Theme>(
-                &tabular.table_values,
-                &tabular.widths,
-                &tabular.headers,
-                tabular.offset,
-                tabular.rendered_rows as usize,
-            ),
-            layout[0],
+            tabulate(tabular, &columns, &row_heights, theme),
+            scroll_area,
             &mut local_st,
         );
+        if tabular.is_empty() && tabular.rendered_rows > 0 {
+            frame.render_widget(
+                Paragraph::new("no rows")
+                    .centered()
+                    .style(theme.table_null()),
+                Rect {
+                    y: rows_y + (tabular.rendered_rows - 1) / 2,
+                    height: 1,
+                    ..table_area
+                },
+            );
+        }
+        frame.render_widget(
+            Block::new()
+                .borders(Borders::TOP)
+                .border_style(theme.table_separator()),
+            Rect {
+                y: layout[0].y + header_height,
+                height: 1,
+                ..layout[0]
+            },
+        );
+    }
+
+    // Popups over the table or the record view
+    if let Some(mut scroll) = tabular.cell_popup {
+        let area = centered_rect(
+            layout[0],
+            Constraint::Percentage(80),
+            Constraint::Percentage(60),
+        );
+        let block = Block::new()
+            .title(format!(" {} ", tabular.headers[tabular.select_column]))
+            .borders(Borders::ALL)
+            .style(theme.item_block());
+        let space = block.inner(area);
+        let text = tabular
+            .table_values
+            .get(tabular.select, tabular.select_column)
+            .unwrap_or_default();
+        // nested values and JSON texts are pretty-printed
+        let lines = match tabular
+            .selected_value()
+            .and_then(|value| Nested::from_any_value(&value))
+            .or_else(|| Nested::from_json(text))
+        {
+            Some(nested) => nested.lines(space.width as usize, theme),
+            None => wrap_text(text, space.width as usize)
+                .into_iter()
+                .map(Line::raw)
+                .collect_vec(),
+        };
+        scroll.adjust(lines.len(), space.height as usize);
+        tabular.cell_popup = Some(scroll);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((scroll.into(), 0)),
+            area,
+        );
+    }
 
+    if let Some(mut scroll) = tabular.view_popup {
+        let area = centered_rect(
+            layout[0],
+            Constraint::Percentage(80),
+            Constraint::Percentage(60),
+        );
+        let block = Block::new()
+            .title(" View ")
+            .borders(Borders::ALL)
+            .style(theme.item_block());
+        let space = block.inner(area);
+        let mut lines = wrap_text(tabular.provenance(), space.width as usize);
+        if let Some(sort) = &tabular.sort {
+            lines.push(String::new());
+            let arrow = if sort.descending { '↓' } else { '↑' };
+            lines.push(format!("sorted by {} {}", sort.column, arrow));
+        }
+        if !tabular.filters.is_empty() {
+            lines.push(String::new());
+            let filters = tabular.filters.iter();
+            lines.extend(filters.map(|filter| format!("filter: {}", filter)));
+        }
+        scroll.adjust(lines.len(), space.height as usize);
+        tabular.view_popup = Some(scroll);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines.into_iter().map(Line::raw).collect_vec())
+                .block(block)
+                .scroll((scroll.into(), 0)),
+            area,
+        );
+    }
 
+    if let Some(stats) = &tabular.stats {
+        let label_width = stats.stats.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+        let lines = stats
+            .stats
+            .iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$} ", label, width = label_width),
+                        theme.table_header_cell(0),
+                    ),
+                    Span::raw(value.as_str()),
+                ])
+            })
+            .collect_vec();
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or(0)
+            .max(stats.column.len() + 2);
+        let area = centered_rect(
+            layout[0],
+            Constraint::Length(width as u16 + 4),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::new()
+                    .title(format!(" {} ", stats.column))
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(theme.item_block()),
+            ),
+            area,
+        );
     }
 
-    match &mut status_bar.state {
-        crate::app::StatusBarState::Normal => frame.render_widget(
-            Line::default()
-                .spans([
-                    Span::raw(format!(
-                        "Row: {:<width$} ",
-                        tabular.select + 1,
-                        width = tabular.table_values.height().to_string().len()
-                    )),
+    if let Some(counts) = &tabular.value_counts {
+        let value_width = counts
+            .counts
+            .iter()
+            .map(|(value, _)| value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(VALUE_COUNTS_WIDTH);
+        let count_width = counts
+            .counts
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = counts
+            .counts
+            .iter()
+            .map(|(value, count)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$.width$} ", value, width = value_width),
+                        theme.table_header_cell(0),
+                    ),
                     Span::raw(format!(
-                        "Table Size: {} x {} ",
-                        tabular.table_values.height(),
-                        tabular.table_values.width()
+                        "{:>width$} {:>6.1}%",
+                        count,
+                        100.0 * *count as f64 / counts.total.max(1) as f64,
+                        width = count_width
                     )),
                 ])
-                .alignment(Alignment::Right)
-                .style(Theme::status_bar_blue()),
-            layout[1],
+            })
+            .collect_vec();
+        if counts.more > 0 {
+            lines.push(Line::styled(
+                format!("+{} more", counts.more),
+                theme.table_null(),
+            ));
+        } else if lines.is_empty() {
+            lines.push(Line::styled("no rows", theme.table_null()));
+        }
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or(0)
+            .max(counts.column.len() + 2);
+        let area = centered_rect(
+            layout[0],
+            Constraint::Length(width as u16 + 4),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::new()
+                    .title(format!(" {} ", counts.column))
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(theme.item_block()),
+            ),
+            area,
+        );
+    }
+
+    if let Some(histogram) = &tabular.histogram {
+        let area = centered_rect(
+            layout[0],
+            Constraint::Percentage(80),
+            Constraint::Percentage(60),
+        );
+        let block = Block::new()
+            .title(format!(" {} ", histogram.column))
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1))
+            .style(theme.item_block());
+        let [chart_area, axis_area, summary_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(area));
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        if histogram.buckets.is_empty() {
+            frame.render_widget(
+                Line::styled("no values", theme.table_null()).centered(),
+                chart_area,
+            );
+        } else {
+            let len = histogram.buckets.len() as u16;
+            let bar_width = (chart_area.width.saturating_sub(len - 1) / len).max(1);
+            let width = (len * bar_width + len - 1).min(axis_area.width);
+            let axis_area = Rect { width, ..axis_area };
+            let summary_area = Rect {
+                width,
+                ..summary_area
+            };
+            let bars = histogram
+                .buckets
+                .iter()
+                .map(|count| Bar::default().value(*count))
+                .collect_vec();
+            frame.render_widget(
+                BarChart::default()
+                    .data(BarGroup::default().bars(&bars))
+                    .bar_width(bar_width)
+                    .bar_gap(1)
+                    .bar_style(theme.table_header_cell(4)),
+                chart_area,
+            );
+            // the minimum and the maximum under the first and the last bar
+            let format = |value| tabular.number_format.format(AnyValue::Float64(value));
+            frame.render_widget(Line::raw(format(histogram.min)), axis_area);
+            frame.render_widget(
+                Line::raw(format(histogram.max)).alignment(Alignment::Right),
+                axis_area,
+            );
+            frame.render_widget(
+                Line::styled(
+                    format!(
+                        "mean {} · nulls {}",
+                        format(histogram.mean),
+                        histogram.nulls
+                    ),
+                    theme.table_header_cell(0),
+                )
+                .centered(),
+                summary_area,
+            );
+        }
+    }
+
+    if let Some(schema) = &tabular.schema {
+        let index_width = schema.columns.len().to_string().len();
+        let name_width = schema
+            .columns
+            .iter()
+            .map(|(name, _, _)| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(SCHEMA_NAME_WIDTH);
+        let dtype_width = schema
+            .columns
+            .iter()
+            .map(|(_, dtype, _)| dtype.chars().count())
+            .max()
+            .unwrap_or(0);
+        let items = schema
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, dtype, nulls))| {
+                let nulls = match nulls {
+                    Some(0) => String::new(),
+                    Some(nulls) => format!("{} nulls", nulls),
+                    None => "? nulls".to_owned(),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>width$} ", idx + 1, width = index_width),
+                        theme.table_header_dtype(idx),
+                    ),
+                    Span::styled(
+                        format!("{:<width$.width$} ", name, width = name_width),
+                        theme.table_header_cell(idx),
+                    ),
+                    Span::raw(format!("{:<width$} ", dtype, width = dtype_width)),
+                    Span::styled(nulls, theme.table_null()),
+                ])
+            })
+            .collect_vec();
+        let title = match schema.hidden {
+            0 => format!(" {} columns ", schema.columns.len()),
+            hidden => format!(" {} columns, {} hidden ", schema.columns.len(), hidden),
+        };
+        let width = items
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or(0)
+            .max(title.chars().count());
+        let area = centered_rect(
+            layout[0],
+            Constraint::Length(width as u16 + 4),
+            Constraint::Length((items.len() as u16 + 2).min(layout[0].height)),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(
+                    Block::new()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .padding(Padding::horizontal(1))
+                        .style(theme.item_block()),
+                )
+                .highlight_style(theme.table_highlight()),
+            area,
+            &mut ListState::default().with_selected(Some(schema.selected)),
+        );
+    }
+
+    let search_info = tabular
+        .search
+        .as_ref()
+        .filter(|search| !search.is_empty())
+        .map(|search| format!("Match: {}/{} ", search.index() + 1, search.len()))
+        .unwrap_or_default();
+    let columns_info = match &tabular.rendered_columns {
+        columns
+            if tabular.detailed_view.is_none()
+                && tabular.record_view.is_none()
+                && columns.len() < tabular.widths.len() =>
+        {
+            let more_left = columns.start > tabular.frozen.min(tabular.widths.len());
+            let more_right = columns.end < tabular.widths.len();
+            format!(
+                "{} cols {}–{} of {} {} ",
+                if more_left { '◀' } else { ' ' },
+                columns.start + 1,
+                columns.end,
+                tabular.widths.len(),
+                if more_right { '▶' } else { ' ' },
+            )
+        }
+        _ => String::new(),
+    };
+    let query_info = match (&tabular.pending, tabular.query_timing) {
+        (Some(pending), _) => format!(
+            "{} Querying… {}s (Esc to cancel) ",
+            pending.spinner(),
+            pending.elapsed().as_secs()
+        ),
+        (None, Some((elapsed, rows))) => format!(
+            "query: {:.2}s, {} rows ",
+            elapsed.as_secs_f64(),
+            human_count(rows)
         ),
+        (None, None) => String::new(),
+    };
+    let hidden_info = match tabular.hidden.len() {
+        0 => String::new(),
+        n => format!("Hidden: {} ", n),
+    };
+    let sort_info = tabular
+        .sort
+        .as_ref()
+        .map(|sort| {
+            format!(
+                "{} sorted by {} ",
+                if sort.descending { '↓' } else { '↑' },
+                sort.column
+            )
+        })
+        .unwrap_or_default();
+    let view_info = format!("view: {} ", ellipsize(tabular.provenance(), VIEW_WIDTH));
+    let filter_info = match tabular.filters.as_slice() {
+        [] => String::new(),
+        [filter] => format!("Filter: {} ", filter),
+        filters => format!("Filters: {} ", filters.len()),
+    };
+    let modified_info = if tabular.modified { "[modified] " } else { "" };
+    let follow_info = match &tabular.follow {
+        Some(follow) if follow.paused => "[paused] ",
+        Some(_) => "[following] ",
+        None => "",
+    };
+    let truncation_info = match &tabular.truncation {
+        Some(truncation) => format!("[{}] ", truncation),
+        None => String::new(),
+    };
+    let range_info = match tabular.selected_range() {
+        Some(range) if range.len() == 1 => "1 row selected ".to_owned(),
+        Some(range) => format!("{} rows selected ", range.len()),
+        None => String::new(),
+    };
+
+    if let Some(selected) = status_bar.history_popup {
+        let entries = status_bar.history().entries();
+        let area = centered_rect(
+            layout[0],
+            Constraint::Percentage(80),
+            Constraint::Length((entries.len() as u16 + 2).min(layout[0].height)),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(
+            List::new(entries.iter().rev().map(String::as_str))
+                .block(
+                    Block::new()
+                        .title(" History ")
+                        .borders(Borders::ALL)
+                        .style(theme.item_block()),
+                )
+                .highlight_style(theme.table_highlight()),
+            area,
+            &mut ListState::default().with_selected(Some(selected)),
+        );
+    }
+
+    if let Some(help) = &mut status_bar.help {
+        let key_width = help
+            .sections
+            .iter()
+            .flat_map(|(_, entries)| entries.iter().map(|(keys, _)| keys.chars().count()))
+            .max()
+            .unwrap_or(0);
+        let lines = help
+            .sections
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, (category, entries))| {
+                // a blank line between the categories
+                (idx > 0)
+                    .then(Line::default)
+                    .into_iter()
+                    .chain([Line::styled(category.name(), theme.table_header_cell(0))])
+                    .chain(entries.iter().map(|(keys, description)| {
+                        Line::from(vec![
+                            Span::styled(
+                                format!("  {:<width$}  ", keys, width = key_width),
+                                theme.table_header_cell(1),
+                            ),
+                            Span::raw(*description),
+                        ])
+                    }))
+            })
+            .collect_vec();
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2;
+        let area = centered_rect(
+            layout[0],
+            Constraint::Length(width.min(layout[0].width)),
+            Constraint::Length((lines.len() as u16 + 2).min(layout[0].height)),
+        );
+        let block = Block::new()
+            .title(" Help ")
+            .borders(Borders::ALL)
+            .style(theme.item_block());
+        help.scroll
+            .adjust(lines.len(), block.inner(area).height as usize);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((help.scroll.into(), 0)),
+            area,
+        );
+    }
+
+    if let Some(palette) = &status_bar.palette {
+        let usage_width = palette
+            .matches()
+            .map(|entry| entry.usage.chars().count())
+            .max()
+            .unwrap_or(0);
+        let items = palette
+            .matches()
+            .map(|entry| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", entry.usage, width = usage_width),
+                        theme.table_header_cell(1),
+                    ),
+                    Span::raw(entry.description),
+                ])
+            })
+            .collect_vec();
+        let area = centered_rect(
+            layout[0],
+            Constraint::Percentage(80),
+            Constraint::Length((items.len() as u16 + 3).min(layout[0].height)),
+        );
+        let block = Block::new()
+            .title(" Commands ")
+            .borders(Borders::ALL)
+            .style(theme.item_block());
+        let [query_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(block.inner(area));
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        frame.render_widget(
+            Line::from(vec![
+                Span::raw("> "),
+                Span::raw(palette.query()),
+                Span::styled(" ", invert_style(theme.item_block())),
+            ]),
+            query_area,
+        );
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(theme.table_highlight()),
+            list_area,
+            &mut ListState::default().with_selected(Some(palette.selected())),
+        );
+    }
+
+    // e.g. "row 12/120 of 2000 · col price (4/12)" when a query filtered the rows
+    let height = tabular.table_values.height();
+    let mut position_info = format!(
+        "row {:>width$}/{}",
+        (tabular.select + 1).min(height),
+        height,
+        width = height.to_string().len()
+    );
+    if height != tabular.source_height() {
+        position_info.push_str(&format!(" of {}", tabular.source_height()));
+    }
+    if let Some(column) = tabular.headers.get(tabular.select_column) {
+        position_info.push_str(&format!(
+            " · col {} ({}/{})",
+            column,
+            tabular.select_column + 1,
+            tabular.headers.len()
+        ));
+    }
+    position_info.push(' ');
+
+    // e.g. " -- NORMAL -- ", on the left of the status bar or the right of the prompt
+    let mode_info = format!(" -- {} -- ", status_bar.mode().name());
+    let mode_width = mode_info.len() as u16;
+
+    // e.g. " [i][.*] in name", the search options on the right of the search prompt
+    let search_flags = match status_bar.mode() {
+        Mode::Search => {
+            let options = status_bar.search_options;
+            let flags = [(options.ignore_case, "[i]"), (options.regex, "[.*]")]
+                .into_iter()
+                .filter_map(|(set, flag)| set.then_some(flag))
+                .collect::<String>();
+            let scope = match tabular.headers.get(tabular.select_column) {
+                Some(column) if options.column => format!("in {}", column),
+                _ => "in all columns".to_owned(),
+            };
+            match flags.is_empty() {
+                true => scope,
+                false => format!("{} {}", flags, scope),
+            }
+        }
+        _ => String::new(),
+    };
+    let search_flags = match search_flags.is_empty() {
+        true => search_flags,
+        false => format!(" {}", search_flags),
+    };
+    let flags_width = search_flags.chars().count() as u16;
+
+    match &mut status_bar.state {
+        crate::app::StatusBarState::Normal => {
+            let [mode_area, info_area] =
+                Layout::horizontal([Constraint::Length(mode_width), Constraint::Fill(1)])
+                    .areas(layout[1]);
+            frame.render_widget(
+                Line::raw(mode_info).style(theme.status_bar_blue()),
+                mode_area,
+            );
+            frame.render_widget(
+                Line::default()
+                    .spans([
+                        Span::raw(query_info),
+                        Span::raw(tab_info),
+                        Span::raw(modified_info),
+                        Span::raw(follow_info),
+                        Span::raw(truncation_info),
+                        Span::raw(range_info),
+                        Span::raw(search_info),
+                        Span::raw(view_info),
+                        Span::raw(sort_info),
+                        Span::raw(filter_info),
+                        Span::raw(hidden_info),
+                        Span::raw(columns_info),
+                        Span::raw(position_info),
+                    ])
+                    .alignment(Alignment::Right)
+                    .style(theme.status_bar_blue()),
+                info_area,
+            )
+        }
 
         crate::app::StatusBarState::Error(msg) => frame.render_widget(
             Line::raw(msg.as_str())
                 .alignment(Alignment::Center)
-                .style(Theme::status_bar_red()),
+                .style(theme.status_bar_red()),
+            layout[1],
+        ),
+
+        crate::app::StatusBarState::Info(msg) => frame.render_widget(
+            Line::raw(msg.as_str())
+                .alignment(Alignment::Center)
+                .style(theme.status_bar_green()),
             layout[1],
         ),
 
         crate::app::StatusBarState::Command(text) => {
+            if !status_bar.completions.is_empty() {
+                let candidates = status_bar.completions.join("  ");
+                let width = (candidates.chars().count() as u16 + 4).min(layout[0].width);
+                let height = (line_count(&candidates, width.saturating_sub(4) as usize) as u16 + 2)
+                    .min(layout[0].height);
+                let area = Rect {
+                    x: layout[0].x,
+                    y: layout[0].bottom().saturating_sub(height),
+                    width,
+                    height,
+                };
+                frame.render_widget(Clear, area);
+                frame.render_widget(
+                    Paragraph::new(candidates)
+                        .wrap(Wrap { trim: true })
+                        .block(
+                            Block::new()
+                                .borders(Borders::ALL)
+                                .padding(Padding::horizontal(1))
+                                .style(theme.item_block()),
+                        ),
+                    area,
+                );
+            }
+            let [prompt_area, flags_area, mode_area] = Layout::horizontal([
+                Constraint::Fill(1),
+                Constraint::Length(flags_width),
+                Constraint::Length(mode_width),
+            ])
+            .areas(layout[1]);
             frame.render_stateful_widget(
                 CommandPallete::new(
-                    Theme::status_bar_green(),
-                    invert_style(Theme::status_bar_green()),
+                    theme.status_bar_green(),
+                    invert_style(theme.status_bar_green()),
                 ),
-                layout[1],
+                prompt_area,
                 text,
             );
+            frame.render_widget(
+                Line::raw(search_flags).style(theme.status_bar_green()),
+                flags_area,
+            );
+            frame.render_widget(
+                Line::raw(mode_info).style(theme.status_bar_green()),
+                mode_area,
+            );
         }
     }
 }
 
-fn paragraph_from_headers_values<'a, Theme: Styler>(
+fn paragraph_from_headers_values<'a>(
     title: &'a str,
     headers: &'a [String],
     values: &'a [&str],
     width: u16,
+    theme: &dyn Styler,
 ) -> (Paragraph<'a>, usize) {
     let lines = izip!(headers, values.iter())
         .enumerate()
-        .flat_map(|(idx, (header, value))| lines_from_header_value::<Theme>(idx, header, value))
+        .flat_map(|(idx, (header, value))| lines_from_header_value(idx, header, value, theme))
         .collect_vec();
     let lc = lines
         .iter()
@@ -111,64 +936,184 @@ fn paragraph_from_headers_values<'a, Theme: Styler>(
         .sum();
     let prgr = Paragraph::new(lines)
         .block(Block::new().title(title).borders(Borders::ALL))
-        .style(Theme::item_block())
+        .style(theme.item_block())
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: true });
     (prgr, lc)
 }
 
-fn lines_from_header_value<'a, Theme: Styler>(
+fn lines_from_header_value<'a>(
     idx: usize,
     header: &'a str,
     value: &'a str,
+    theme: &dyn Styler,
 ) -> Vec<Line<'a>> {
     let header_line = std::iter::once(Line::from(Span::styled(
         header,
-        Theme::table_header_cell(idx),
+        theme.table_header_cell(idx),
     )));
     let value_lines = value
         .lines()
-        .map(|line| Line::from(Span::styled(line, Theme::table_cell(idx, 0))));
+        .map(|line| Line::from(Span::styled(line, theme.table_cell(idx, 0))));
     header_line
         .chain(value_lines)
         .chain(std::iter::once(Line::default()))
         .collect_vec()
 }
 
-pub fn tabulate<'a, Theme: Styler>(
-    value_pool: &'a TableValues,
-    widths: &'a [usize],
-    headers: &'a [String],
-    offset: usize,
-    length: usize,
+/// Table of the rendered rows of the given columns, paired with their render widths.
+/// The rows are as tall as `row_heights`, or one line each if it is empty, the values
+/// of the wrapped columns wrapping to their width.
+pub fn tabulate<'a>(
+    tabular: &'a Tabular,
+    columns: &[(usize, u16)],
+    row_heights: &[u16],
+    theme: &dyn Styler,
 ) -> Table<'a> {
+    let offset = tabular.offset;
+    let length = match row_heights.is_empty() {
+        true => tabular.rendered_rows as usize,
+        false => row_heights.len(),
+    };
+    let range = tabular.selected_range().unwrap_or_default();
     Table::new(
         (offset..offset + length)
             .map(|row_idx| {
-                Row::new(value_pool.get_row(row_idx).into_iter().map(Cell::new))
-                    .style(Theme::table_row(row_idx))
+                let height = row_heights.get(row_idx - offset).copied().unwrap_or(1);
+                Row::new(columns.iter().map(|(col_idx, width)| {
+                    let text = tabular.table_values.get(row_idx, *col_idx).unwrap_or("");
+                    let null = tabular.table_values.is_null(row_idx, *col_idx);
+                    let value = match height > 1 && tabular.is_wrapped(*col_idx) {
+                        true => Text::from(
+                            wrap_text(text, *width as usize)
+                                .into_iter()
+                                .map(Line::raw)
+                                .collect_vec(),
+                        ),
+                        false => Text::from(truncate(text, *width as usize)),
+                    };
+                    let alignment = alignment(tabular.numeric[*col_idx]);
+                    match &tabular.search {
+                        Some(search) if search.contains(row_idx, *col_idx) => {
+                            Cell::new(value.alignment(alignment)).style(theme.table_match())
+                        }
+                        _ if tabular.highlight_nulls && null => {
+                            Cell::new(Text::from("∅").alignment(alignment))
+                                .style(theme.table_null())
+                        }
+                        _ => match tabular.column_symbols[*col_idx] {
+                            // nulls and the rows past the end are empty
+                            ColumnSymbols::Boolean if !text.is_empty() => {
+                                let (symbol, style) = theme.table_boolean(text == "true");
+                                Cell::new(Text::from(symbol).alignment(alignment)).style(style)
+                            }
+                            ColumnSymbols::Category if !null => {
+                                Cell::new(value.alignment(alignment))
+                                    .style(theme.table_category(category_index(text)))
+                            }
+                            _ => Cell::new(value.alignment(alignment)),
+                        },
+                    }
+                }))
+                .height(height)
+                .style({
+                    let style = match tabular.row_color(row_idx) {
+                        Some(color) => theme.table_row(row_idx, tabular.stripes).bg(color),
+                        None => theme.table_row(row_idx, tabular.stripes),
+                    };
+                    match range.contains(&row_idx) {
+                        true => style.patch(theme.table_range()),
+                        false => style,
+                    }
+                })
             })
             .collect_vec(),
-        widths
+        columns
             .iter()
-            .copied()
-            .map(|w| Constraint::Length(w as u16))
+            .map(|(_, w)| Constraint::Length(*w))
             .collect::<Vec<_>>(),
     )
-    .header(header_row::<Theme>(headers))
-    .highlight_style(Theme::table_highlight())
+    .header(header_row(
+        &tabular.headers,
+        tabular.show_types.then_some(tabular.dtypes.as_slice()),
+        &tabular.numeric,
+        columns,
+        tabular.select_column,
+        theme,
+    ))
+    .highlight_style(theme.table_highlight())
 }
 
-fn header_row<Theme: Styler>(df: &[String]) -> Row {
+fn header_row<'a>(
+    df: &'a [String],
+    dtypes: Option<&'a [String]>,
+    numeric: &[bool],
+    columns: &[(usize, u16)],
+    select_column: usize,
+    theme: &dyn Styler,
+) -> Row<'a> {
     Row::new(
-        df.iter()
-            .enumerate()
-            .map(|(col_idx, name)| {
-                Cell::new(name.as_str()).style(Theme::table_header_cell(col_idx))
+        columns
+            .iter()
+            .map(|(col_idx, width)| {
+                let style = theme.table_header_cell(*col_idx);
+                let name = Line::styled(
+                    truncate(&df[*col_idx], *width as usize),
+                    if *col_idx == select_column {
+                        style.reversed()
+                    } else {
+                        style
+                    },
+                );
+                let text = match dtypes {
+                    Some(dtypes) => Text::from(vec![
+                        name,
+                        Line::styled(
+                            dtypes[*col_idx].as_str(),
+                            theme.table_header_dtype(*col_idx),
+                        ),
+                    ]),
+                    None => Text::from(name),
+                };
+                Cell::new(text.alignment(alignment(numeric[*col_idx])))
             })
             .collect::<Vec<_>>(),
     )
-    .style(Theme::table_header())
+    .height(if dtypes.is_some() { 2 } else { 1 })
+    .bottom_margin(1)
+    .style(theme.table_header())
+}
+
+/// Numbers are right-aligned so that their digits line up.
+fn alignment(numeric: bool) -> Alignment {
+    if numeric {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    }
+}
+
+/// X-ranges of the columns of a table drawn from `x`, separated by one space.
+fn column_areas(x: u16, columns: &[(usize, u16)]) -> Vec<(usize, Range<u16>)> {
+    let mut start = x;
+    columns
+        .iter()
+        .map(|(col, width)| {
+            let area = (*col, start..start + width);
+            start += width + 1;
+            area
+        })
+        .collect()
+}
+
+/// Rectangle centered in `area`, sized by the given constraints.
+fn centered_rect(area: Rect, width: Constraint, height: Constraint) -> Rect {
+    let vertical = Layout::vertical([height])
+        .flex(layout::Flex::Center)
+        .split(area);
+    Layout::horizontal([width])
+        .flex(layout::Flex::Center)
+        .split(vertical[0])[0]
 }
 
 fn invert_style(mut style: Style) -> Style {
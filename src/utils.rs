@@ -1,14 +1,30 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    env,
+    hash::{Hash, Hasher},
+    ops::Range,
+    path::PathBuf,
+};
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use polars::{
-    datatypes::{AnyValue, DataType},
+    datatypes::{AnyValue, BooleanChunked, DataType, TimeUnit},
+    error::PolarsResult,
     frame::DataFrame,
+    prelude::{IntoSeries, NamedFrom},
     series::Series,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+/// Text of the cells, for a window of `rows` rows starting at `start` when the table is
+/// read lazily, or for the whole table.
 #[derive(Debug)]
 pub struct TableValues {
     pool: ValuePool,
+    start: usize,
+    rows: usize,
     height: usize,
     width: usize,
 }
@@ -20,13 +36,27 @@ impl TableValues {
         let pool = data_frame.iter().map(Series::iter).round_robin().collect();
         Self {
             pool,
+            start: 0,
+            rows: height,
             width,
             height,
         }
     }
 
+    fn index(&self, row: usize, col: usize) -> Option<usize> {
+        row.checked_sub(self.start)
+            .filter(|row| *row < self.rows && col < self.width)
+            .map(|row| row * self.width + col)
+    }
+
+    /// Text of a cell, `None` for rows out of the window.
     pub fn get(&self, row: usize, col: usize) -> Option<&str> {
-        self.pool.get(row * self.width + col)
+        self.pool.get(self.index(row, col)?)
+    }
+
+    pub fn is_null(&self, row: usize, col: usize) -> bool {
+        self.index(row, col)
+            .is_some_and(|index| self.pool.is_null(index))
     }
 
     pub fn get_row(&self, row: usize) -> Vec<&str> {
@@ -39,19 +69,40 @@ impl TableValues {
         self.width
     }
 
+    /// Rows held, the others are out of the window.
+    pub fn window(&self) -> Range<usize> {
+        self.start..self.start + self.rows
+    }
+
+    /// Number of rows of the table, including the ones out of the window.
     pub fn height(&self) -> usize {
         self.height
     }
 
     pub fn replace_dataframe(&mut self, data_frame: DataFrame) {
-        self.height = data_frame.height();
+        let height = data_frame.height();
+        self.replace_window(data_frame, 0, height, NumberFormat::default());
+    }
+
+    /// Holds the rows of `data_frame` as the rows from `start` of a table of `height` rows,
+    /// with the numbers written in `format`.
+    pub fn replace_window(
+        &mut self,
+        data_frame: DataFrame,
+        start: usize,
+        height: usize,
+        format: NumberFormat,
+    ) {
+        self.start = start;
+        self.rows = data_frame.height();
+        self.height = height;
         self.width = data_frame.width();
         self.pool.clear();
         data_frame
             .iter()
             .map(Series::iter)
             .round_robin()
-            .for_each(|value| self.pool.push(value));
+            .for_each(|value| self.pool.push(value, format));
     }
 }
 
@@ -59,6 +110,7 @@ impl TableValues {
 struct ValuePool {
     pool: String,
     indices: Vec<usize>,
+    nulls: Vec<bool>,
 }
 
 impl ValuePool {
@@ -66,14 +118,24 @@ impl ValuePool {
         let pool = String::with_capacity(pool_capacity);
         let mut indices = Vec::with_capacity(index_capacity);
         indices.push(0);
-        Self { pool, indices }
+        let nulls = Vec::with_capacity(index_capacity);
+        Self {
+            pool,
+            indices,
+            nulls,
+        }
     }
 
-    pub fn push(&mut self, value: AnyValue) {
-        self.pool.push_str(&any_value_into_string(value));
+    pub fn push(&mut self, value: AnyValue, format: NumberFormat) {
+        self.nulls.push(value.is_null());
+        self.pool.push_str(&format.format(value));
         self.indices.push(self.pool.len());
     }
 
+    pub fn is_null(&self, index: usize) -> bool {
+        self.nulls.get(index).copied().unwrap_or_default()
+    }
+
     pub fn get(&self, index: usize) -> Option<&str> {
         let start = *self.indices.get(index)?;
         let end = *self.indices.get(index + 1)?;
@@ -83,6 +145,7 @@ impl ValuePool {
     pub fn clear(&mut self) {
         self.pool.clear();
         self.indices.clear();
+        self.nulls.clear();
         self.indices.push(0);
     }
 }
@@ -91,7 +154,7 @@ impl<'a> FromIterator<AnyValue<'a>> for ValuePool {
     fn from_iter<T: IntoIterator<Item = AnyValue<'a>>>(iter: T) -> Self {
         let mut pool = ValuePool::new(16, 16);
         for value in iter {
-            pool.push(value);
+            pool.push(value, NumberFormat::default());
         }
         pool
     }
@@ -229,22 +292,366 @@ pub fn line_count(text: &str, width: usize) -> usize {
     line_count
 }
 
-pub fn data_frame_widths(df: &polars::frame::DataFrame) -> Vec<usize> {
+/// Render widths of the `columns` fitting in `space`, one cell apart. The last column
+/// is truncated to the remaining space, and the first one is always kept.
+pub fn fit_columns(widths: &[usize], columns: Range<usize>, space: usize) -> Vec<(usize, u16)> {
+    let mut remaining = space;
+    let mut fitted = Vec::new();
+    for col in columns {
+        let width = widths[col].min(remaining);
+        if width == 0 && !fitted.is_empty() {
+            break;
+        }
+        fitted.push((col, width as u16));
+        remaining = remaining.saturating_sub(width + 1);
+    }
+    fitted
+}
+
+/// Wraps the text at word boundaries into lines at most `width` columns wide. Words
+/// longer than a line are broken between graphemes.
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_word_bounds() {
+            let word_width = word.width();
+            if line_width + word_width > width && line_width > 0 {
+                lines.push(std::mem::take(&mut line).trim_end().to_owned());
+                line_width = 0;
+                if word.trim().is_empty() {
+                    continue;
+                }
+            }
+            if word_width > width {
+                for grapheme in word.graphemes(true) {
+                    let grapheme_width = grapheme.width();
+                    if line_width + grapheme_width > width && line_width > 0 {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0;
+                    }
+                    line.push_str(grapheme);
+                    line_width += grapheme_width;
+                }
+            } else {
+                line.push_str(word);
+                line_width += word_width;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// The text on a single line of at most `width` characters, its whitespace collapsed and
+/// its middle replaced with `…` when it is longer, keeping its start and its end.
+pub fn ellipsize(text: &str, width: usize) -> String {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let chars = words.join(" ").chars().collect::<Vec<_>>();
+    if chars.len() <= width {
+        return chars.into_iter().collect();
+    }
+    let start = width.saturating_sub(1).div_ceil(2);
+    let end = width.saturating_sub(1) / 2;
+    chars[..start]
+        .iter()
+        .chain(['…'].iter().take(width))
+        .chain(&chars[chars.len() - end..])
+        .collect()
+}
+
+/// The text cut to at most `width` columns, ending with `…` when it is wider.
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for grapheme in text.graphemes(true) {
+        truncated_width += grapheme.width();
+        if truncated_width >= width {
+            break;
+        }
+        truncated.push_str(grapheme);
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    Cow::Owned(truncated)
+}
+
+/// Directory of the Tabiew configuration files: `$XDG_CONFIG_HOME/tabiew`,
+/// `~/.config/tabiew`, or `%APPDATA%\tabiew` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(base.join("tabiew"))
+}
+
+/// Size in bytes in a human readable form, e.g. `1.5 GB`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Count in a short form, e.g. `1.2M`.
+pub fn human_count(count: usize) -> String {
+    const UNITS: [&str; 4] = ["", "K", "M", "B"];
+    let mut size = count as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        count.to_string()
+    } else {
+        format!("{:.1}{}", size, UNITS[unit])
+    }
+}
+
+/// Frames of the spinners shown while loading and querying.
+pub const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+pub fn data_frame_widths(df: &polars::frame::DataFrame, format: NumberFormat) -> Vec<usize> {
     df.get_column_names()
         .into_iter()
         .zip(df.get_columns())
-        .map(|(col, series)| col.len().max(series_width(series)))
+        .map(|(col, series)| col.len().max(series_width(series, format)))
         .collect::<Vec<_>>()
 }
 
-pub fn series_width(series: &Series) -> usize {
+pub fn series_width(series: &Series, format: NumberFormat) -> usize {
     series
         .iter()
-        .map(|any_value| any_value_into_string(any_value).len())
+        .map(|any_value| format.format(any_value).len())
         .max()
         .unwrap_or_default()
 }
 
+/// Display options of the numbers in the table, which leave the values used by SQL and
+/// the exports untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Separates the thousands of the integer part with commas.
+    pub thousands: bool,
+    /// Digits after the decimal point of floats.
+    pub float_precision: Option<usize>,
+}
+
+/// Floats at least this large are written in scientific notation.
+const SCIENTIFIC_LARGE: f64 = 1e15;
+
+/// Floats this small are written in scientific notation when no precision is set.
+const SCIENTIFIC_SMALL: f64 = 1e-4;
+
+impl NumberFormat {
+    /// Text of `value`, formatted with the options if it is a number.
+    pub fn format(&self, value: AnyValue) -> String {
+        let text = match value {
+            AnyValue::Float32(value) => self.format_float(value),
+            AnyValue::Float64(value) => self.format_float(value),
+            value if value.dtype().is_integer() => any_value_into_string(value),
+            value => return any_value_into_string(value),
+        };
+        if self.thousands {
+            group_thousands(&text)
+        } else {
+            text
+        }
+    }
+
+    /// Writes `value` with the precision, falling back to the scientific notation for
+    /// values too large to read or too small for the precision, e.g. `1.50e-7` rather
+    /// than `0.00`.
+    fn format_float<T>(&self, value: T) -> String
+    where
+        T: Copy + Into<f64> + std::fmt::Display + std::fmt::LowerExp,
+    {
+        let abs = value.into().abs();
+        let small = match self.float_precision {
+            Some(precision) => 0.5 * 10_f64.powi(-(precision as i32)),
+            None => SCIENTIFIC_SMALL,
+        };
+        let scientific = abs.is_finite() && abs != 0.0 && !(small..SCIENTIFIC_LARGE).contains(&abs);
+        match (self.float_precision, scientific) {
+            (Some(precision), true) => format!("{:.*e}", precision, value),
+            (Some(precision), false) => format!("{:.*}", precision, value),
+            (None, true) => format!("{:e}", value),
+            (None, false) => value.to_string(),
+        }
+    }
+}
+
+/// Inserts commas between the thousands of the integer part of `number`, e.g. `-1,234.5`.
+/// Numbers in scientific notation, infinities and NaN are left as they are.
+fn group_thousands(number: &str) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", number),
+    };
+    if !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return number.to_owned();
+    }
+    let (integer, fraction) = digits.split_at(digits.find('.').unwrap_or(digits.len()));
+    let mut grouped = String::with_capacity(number.len() + integer.len() / 3);
+    grouped.push_str(sign);
+    for (idx, digit) in integer.chars().enumerate() {
+        if idx > 0 && (integer.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(fraction);
+    grouped
+}
+
+/// GitHub-flavored Markdown table of `data_frame`, the numbers formatted with `format`
+/// and right-aligned, the nulls empty.
+pub fn markdown_table(data_frame: &DataFrame, format: NumberFormat) -> String {
+    let mut table = String::new();
+    let mut push_row = |cells: Vec<String>| {
+        table.push('|');
+        for cell in cells {
+            table.push(' ');
+            table.push_str(&markdown_cell(&cell));
+            table.push_str(" |");
+        }
+        table.push('\n');
+    };
+    let columns = data_frame.get_columns();
+    push_row(
+        columns
+            .iter()
+            .map(|column| column.name().to_owned())
+            .collect(),
+    );
+    push_row(
+        columns
+            .iter()
+            .map(|column| {
+                if column.dtype().is_numeric() {
+                    "---:".to_owned()
+                } else {
+                    "---".to_owned()
+                }
+            })
+            .collect(),
+    );
+    formatted_rows(data_frame, format).for_each(push_row);
+    table
+}
+
+/// Box-drawn table of `data_frame` as the terminal shows it, the numbers formatted with
+/// `format` and right-aligned, the line breaks of the values written as spaces.
+pub fn box_table(data_frame: &DataFrame, format: NumberFormat) -> String {
+    let columns = data_frame.get_columns();
+    let right = columns
+        .iter()
+        .map(|column| column.dtype().is_numeric())
+        .collect::<Vec<_>>();
+    let header = columns
+        .iter()
+        .map(|column| column.name().to_owned())
+        .collect::<Vec<_>>();
+    let rows = std::iter::once(header)
+        .chain(formatted_rows(data_frame, format))
+        .map(|row| {
+            row.into_iter()
+                .map(|cell| cell.replace("\r\n", " ").replace('\n', " "))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut widths = vec![0; columns.len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+    let rule = |left: &str, middle: &str, end: &str| {
+        let lines = widths
+            .iter()
+            .map(|width| "─".repeat(width + 2))
+            .collect::<Vec<_>>();
+        format!("{}{}{}\n", left, lines.join(middle), end)
+    };
+    let mut table = rule("┌", "┬", "┐");
+    for (index, row) in rows.iter().enumerate() {
+        table.push('│');
+        for ((cell, width), right) in row.iter().zip(&widths).zip(&right) {
+            let padding = " ".repeat(width - cell.width());
+            // the names are aligned as their values
+            if *right {
+                table.push_str(&format!(" {}{} │", padding, cell));
+            } else {
+                table.push_str(&format!(" {}{} │", cell, padding));
+            }
+        }
+        table.push('\n');
+        if index == 0 {
+            table.push_str(&rule("├", "┼", "┤"));
+        }
+    }
+    table.push_str(&rule("└", "┴", "┘"));
+    table
+}
+
+/// Index of the color of a categorical value, the same for the same text in any table.
+pub fn category_index(text: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish() as usize
+}
+
+/// Rows of `data_frame`, a line each with the values separated by tabs, the numbers
+/// formatted with `format` and the nulls empty.
+pub fn tab_separated(data_frame: &DataFrame, format: NumberFormat) -> String {
+    formatted_rows(data_frame, format)
+        .map(|row| row.join("\t"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Values of the rows of `data_frame`, the numbers formatted with `format`.
+fn formatted_rows(
+    data_frame: &DataFrame,
+    format: NumberFormat,
+) -> impl Iterator<Item = Vec<String>> + '_ {
+    let columns = data_frame
+        .get_columns()
+        .iter()
+        .map(|column| column.rechunk())
+        .collect::<Vec<_>>();
+    (0..data_frame.height()).map(move |row| {
+        columns
+            .iter()
+            .map(|column| format.format(column.get(row).expect("a row of the column")))
+            .collect()
+    })
+}
+
+/// Escapes the pipes of `text`, which end a cell, and its line breaks, which end a row.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
 pub fn any_value_into_string(value: polars::datatypes::AnyValue) -> String {
     match value {
         AnyValue::Null => "".to_owned(),
@@ -261,8 +668,8 @@ pub fn any_value_into_string(value: polars::datatypes::AnyValue) -> String {
         AnyValue::Float32(v) => format!("{}", v),
         AnyValue::Float64(v) => format!("{}", v),
         AnyValue::Decimal(v1, v2) => format!("{}.{}", v1, v2),
-        AnyValue::Date(v) => format!("{}", v),
-        AnyValue::Datetime(v1, v2, v3) => format!("{} {} {:?}", v1, v2, v3),
+        AnyValue::Date(_) => value.to_string(),
+        AnyValue::Datetime(_, _, _) => value.to_string(),
         AnyValue::Duration(v1, v2) => format!("{} {}", v1, v2),
         AnyValue::Time(v) => format!("{}", v),
         AnyValue::List(_) => value.to_string(),
@@ -274,6 +681,51 @@ pub fn any_value_into_string(value: polars::datatypes::AnyValue) -> String {
     }
 }
 
+/// Unnests the struct columns of `data_frame` into a column per field, named after the
+/// path of the field as `address.city`, for at most `depth` levels of nesting. With
+/// `explode`, the list columns are also exploded into a row per item, one after the
+/// other, and structs in the lists are unnested in turn.
+pub fn flatten(mut data_frame: DataFrame, depth: usize, explode: bool) -> PolarsResult<DataFrame> {
+    for _ in 0..depth {
+        let mut nested = false;
+        if explode {
+            let lists = data_frame
+                .get_columns()
+                .iter()
+                .filter(|column| matches!(column.dtype(), DataType::List(_)))
+                .map(|column| column.name().to_owned())
+                .collect::<Vec<_>>();
+            for name in lists {
+                data_frame = data_frame.explode([name])?;
+                nested = true;
+            }
+        }
+        let columns = data_frame
+            .get_columns()
+            .iter()
+            .flat_map(|column| match column.struct_() {
+                Ok(fields) => {
+                    nested = true;
+                    fields
+                        .fields()
+                        .iter()
+                        .map(|field| {
+                            let name = format!("{}.{}", column.name(), field.name());
+                            field.clone().with_name(&name)
+                        })
+                        .collect()
+                }
+                Err(_) => vec![column.clone()],
+            })
+            .collect::<Vec<_>>();
+        if !nested {
+            break;
+        }
+        data_frame = DataFrame::new(columns)?;
+    }
+    Ok(data_frame)
+}
+
 pub fn infer_schema_safe(data_frame: &mut DataFrame) {
     let dtypes = [
         DataType::Int64,
@@ -286,6 +738,7 @@ pub fn infer_schema_safe(data_frame: &mut DataFrame) {
         .get_column_names()
         .into_iter()
         .map(|col_name| (col_name, data_frame.column(col_name).unwrap()))
+        .filter(|(_, series)| series.dtype() == &DataType::String)
         .filter_map(|(col_name, series)| {
             dtypes
                 .iter()
@@ -300,11 +753,264 @@ pub fn infer_schema_safe(data_frame: &mut DataFrame) {
         });
 }
 
+/// Casts string columns to `Datetime` or `Date` when every non-null value parses with
+/// the given chrono format. Datetime values carrying an offset are converted to UTC.
+pub fn infer_temporal_safe(
+    data_frame: &mut DataFrame,
+    date_format: Option<&str>,
+    datetime_format: Option<&str>,
+) {
+    data_frame
+        .get_columns()
+        .iter()
+        .filter_map(|series| {
+            let values = series.str().ok()?;
+            if values.null_count() == values.len() {
+                return None;
+            }
+            datetime_format
+                .and_then(|fmt| parse_datetimes(values.into_iter(), fmt))
+                .map(|(micros, utc)| {
+                    Series::new(series.name(), micros).cast(&DataType::Datetime(
+                        TimeUnit::Microseconds,
+                        utc.then(|| "UTC".to_owned()),
+                    ))
+                })
+                .or_else(|| {
+                    date_format
+                        .and_then(|fmt| parse_dates(values.into_iter(), fmt))
+                        .map(|days| Series::new(series.name(), days).cast(&DataType::Date))
+                })
+                .and_then(Result::ok)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .for_each(|series| {
+            let name = series.name().to_owned();
+            data_frame.replace(&name, series).unwrap();
+        });
+}
+
+fn parse_dates<'a>(
+    values: impl Iterator<Item = Option<&'a str>>,
+    fmt: &str,
+) -> Option<Vec<Option<i32>>> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    values
+        .map(|value| match value {
+            Some(value) => NaiveDate::parse_from_str(value.trim(), fmt)
+                .ok()
+                .map(|date| Some((date - epoch).num_days() as i32)),
+            None => Some(None),
+        })
+        .collect()
+}
+
+/// Parses datetimes into microseconds since the epoch, telling whether the values
+/// carried an offset. Mixing values with and without offset fails.
+fn parse_datetimes<'a>(
+    values: impl Iterator<Item = Option<&'a str>>,
+    fmt: &str,
+) -> Option<(Vec<Option<i64>>, bool)> {
+    let mut utc = None;
+    let micros = values
+        .map(|value| {
+            let Some(value) = value.map(str::trim) else {
+                return Some(None);
+            };
+            let (micros, with_offset) = match DateTime::parse_from_str(value, fmt) {
+                Ok(datetime) => (datetime.timestamp_micros(), true),
+                Err(_) => (
+                    NaiveDateTime::parse_from_str(value, fmt)
+                        .ok()?
+                        .and_utc()
+                        .timestamp_micros(),
+                    false,
+                ),
+            };
+            (*utc.get_or_insert(with_offset) == with_offset).then_some(Some(micros))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((micros, utc.unwrap_or_default()))
+}
+
+/// Converts the text typed for a cell to a value of type `dtype`. The empty text is
+/// null, and texts that do not convert are an error.
+pub fn parse_value(text: &str, dtype: &DataType) -> Result<Series, String> {
+    if text.is_empty() {
+        return Ok(Series::full_null("", 1, dtype));
+    }
+    let text = if dtype == &DataType::String {
+        text
+    } else {
+        text.trim()
+    };
+    let value = match dtype {
+        // strings do not cast to booleans
+        DataType::Boolean => match text.to_lowercase().as_str() {
+            "true" => Some(Series::new("", [true])),
+            "false" => Some(Series::new("", [false])),
+            _ => None,
+        },
+        _ => Series::new("", [text])
+            .strict_cast(dtype)
+            .ok()
+            .filter(|series| series.null_count() == 0),
+    };
+    value.ok_or_else(|| format!("'{}' is not a valid {}", text, dtype))
+}
+
+/// Data type named as in the header of the table, as `i64` or `str`, or by a longer
+/// name, as `int` or `string`.
+pub fn parse_dtype(name: &str) -> Option<DataType> {
+    Some(match name.to_lowercase().as_str() {
+        "i8" => DataType::Int8,
+        "i16" => DataType::Int16,
+        "i32" => DataType::Int32,
+        "i64" | "int" | "integer" => DataType::Int64,
+        "u8" => DataType::UInt8,
+        "u16" => DataType::UInt16,
+        "u32" => DataType::UInt32,
+        "u64" => DataType::UInt64,
+        "f32" => DataType::Float32,
+        "f64" | "float" | "double" => DataType::Float64,
+        "str" | "string" | "utf8" | "text" => DataType::String,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+        "time" => DataType::Time,
+        _ => return None,
+    })
+}
+
+/// Casts `series` to `dtype`, the values that do not convert becoming null. Texts
+/// convert to booleans from `true` and `false` in any case, which Polars does not do.
+pub fn cast_series(series: &Series, dtype: &DataType) -> PolarsResult<Series> {
+    match (series.dtype(), dtype) {
+        (DataType::String, DataType::Boolean) => Ok(series
+            .str()?
+            .into_iter()
+            .map(|text| match text?.trim().to_lowercase().as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            })
+            .collect::<BooleanChunked>()
+            .into_series()
+            .with_name(series.name())),
+        _ => series.cast(dtype),
+    }
+}
+
+/// Replaces the value at `row` of `column` with the single `value`, of the same type.
+pub fn set_cell(
+    data_frame: &mut DataFrame,
+    column: &str,
+    row: usize,
+    value: &Series,
+) -> PolarsResult<()> {
+    let series = data_frame.column(column)?;
+    let keep = (0..series.len())
+        .map(|idx| idx != row)
+        .collect::<BooleanChunked>();
+    let edited = series.zip_with(&keep, &value.new_from_index(0, series.len()))?;
+    data_frame.replace(column, edited.with_name(column))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use polars::prelude::*;
 
+    #[test]
+    fn test_tab_separated() {
+        let df = df! {
+            "name" => [Some("a"), None],
+            "price" => [1.5, 2.0],
+        }
+        .unwrap();
+        assert_eq!(tab_separated(&df, NumberFormat::default()), "a\t1.5\n\t2");
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let df = df! {
+            "name" => [Some("a|b"), None],
+            "price" => [Some(1234.5), Some(2.0)],
+            "note" => ["one\ntwo", "three"],
+        }
+        .unwrap();
+        let format = NumberFormat {
+            thousands: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            markdown_table(&df, format),
+            "| name | price | note |\n\
+             | --- | ---: | --- |\n\
+             | a\\|b | 1,234.5 | one<br>two |\n\
+             |  | 2 | three |\n"
+        );
+    }
+
+    #[test]
+    fn test_box_table() {
+        let df = df! {
+            "name" => [Some("ab"), None],
+            "price" => [Some(1234.5), Some(2.0)],
+            "note" => ["one\ntwo", "é"],
+        }
+        .unwrap();
+        assert_eq!(
+            box_table(&df, NumberFormat::default()),
+            "┌──────┬────────┬─────────┐\n\
+             │ name │  price │ note    │\n\
+             ├──────┼────────┼─────────┤\n\
+             │ ab   │ 1234.5 │ one two │\n\
+             │      │      2 │ é       │\n\
+             └──────┴────────┴─────────┘\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let value = |text, dtype| {
+            parse_value(text, &dtype).map(|series| series.get(0).unwrap().to_string())
+        };
+        assert_eq!(value(" 42", DataType::Int64).as_deref(), Ok("42"));
+        assert_eq!(value("1.5", DataType::Float64).as_deref(), Ok("1.5"));
+        assert_eq!(value("true", DataType::Boolean).as_deref(), Ok("true"));
+        assert_eq!(value(" a b", DataType::String).as_deref(), Ok("\" a b\""));
+        assert_eq!(
+            value("2024-02-29", DataType::Date).as_deref(),
+            Ok("2024-02-29")
+        );
+        assert_eq!(value("", DataType::Int64).as_deref(), Ok("null"));
+        assert_eq!(
+            value("1x", DataType::Int64),
+            Err("'1x' is not a valid i64".to_owned())
+        );
+        assert!(value("2024-02-30", DataType::Date).is_err());
+    }
+
+    #[test]
+    fn test_set_cell() {
+        let mut df = df!("a" => [Some(1), None, Some(3)], "b" => ["x", "y", "z"]).unwrap();
+        set_cell(&mut df, "a", 1, &Series::new("", [2])).unwrap();
+        set_cell(
+            &mut df,
+            "b",
+            0,
+            &Series::full_null("", 1, &DataType::String),
+        )
+        .unwrap();
+        assert_eq!(
+            df,
+            df!("a" => [Some(1), Some(2), Some(3)], "b" => [None, Some("y"), Some("z")]).unwrap()
+        );
+    }
+
     #[test]
     fn test_value_pool() {
         let df = df! {
@@ -317,6 +1023,65 @@ mod tests {
         println!("{:?}", vp.get(0, 0))
     }
 
+    #[test]
+    fn test_value_pool_nulls() {
+        let df = df! {
+            "strings" => [Some(""), None],
+            "ints" => [None, Some(2)],
+        }
+        .unwrap();
+
+        let vp = TableValues::from_dataframe(df);
+        assert_eq!(vp.get(0, 0), Some(""));
+        assert!(!vp.is_null(0, 0));
+        assert!(vp.is_null(0, 1));
+        assert!(vp.is_null(1, 0));
+        assert!(!vp.is_null(1, 1));
+    }
+
+    #[test]
+    fn test_table_values_window() {
+        let df = df! {
+            "id" => [10, 11],
+            "name" => [Some("k"), None],
+        }
+        .unwrap();
+
+        let mut values = TableValues::from_dataframe(df.clone());
+        values.replace_window(df, 10, 100, NumberFormat::default());
+        assert_eq!(values.height(), 100);
+        assert_eq!(values.get(9, 0), None);
+        assert_eq!(values.get(10, 0), Some("10"));
+        assert_eq!(values.get(11, 1), Some(""));
+        assert!(values.is_null(11, 1));
+        assert_eq!(values.get(12, 0), None);
+        assert_eq!(values.get_row(50), ["", ""]);
+    }
+
+    #[test]
+    fn test_number_format() {
+        let plain = NumberFormat::default();
+        assert_eq!(plain.format(AnyValue::Int64(-1234567)), "-1234567");
+        assert_eq!(plain.format(AnyValue::Float64(0.25)), "0.25");
+        assert_eq!(plain.format(AnyValue::Float64(2e20)), "2e20");
+        assert_eq!(plain.format(AnyValue::Float32(1.5e-7)), "1.5e-7");
+
+        let format = NumberFormat {
+            thousands: true,
+            float_precision: Some(2),
+        };
+        assert_eq!(format.format(AnyValue::Int64(-1234567)), "-1,234,567");
+        assert_eq!(format.format(AnyValue::UInt16(999)), "999");
+        assert_eq!(format.format(AnyValue::Float64(-1234.567)), "-1,234.57");
+        assert_eq!(format.format(AnyValue::Float64(0.0)), "0.00");
+        assert_eq!(format.format(AnyValue::Float64(0.001)), "1.00e-3");
+        assert_eq!(format.format(AnyValue::Float64(1.5e16)), "1.50e16");
+        assert_eq!(format.format(AnyValue::Float64(f64::NAN)), "NaN");
+        assert_eq!(format.format(AnyValue::Float64(f64::NEG_INFINITY)), "-inf");
+        assert_eq!(format.format(AnyValue::String("1234")), "1234");
+        assert_eq!(format.format(AnyValue::Null), "");
+    }
+
     #[test]
     fn test_round_robin() {
         let v1 = vec![1, 2, 3];
@@ -493,6 +1258,178 @@ mod tests {
         assert_eq!(line_count(text, 10), 1);
     }
 
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(999), "999 B");
+        assert_eq!(human_size(1_500), "1.5 KB");
+        assert_eq!(human_size(2_345_678_901), "2.3 GB");
+        assert_eq!(human_count(532), "532");
+        assert_eq!(human_count(1_234_567), "1.2M");
+    }
+
+    #[test]
+    fn test_ellipsize() {
+        assert_eq!(ellipsize("SELECT *\n  FROM df", 20), "SELECT * FROM df");
+        assert_eq!(
+            ellipsize("SELECT * FROM df WHERE price > 100", 20),
+            "SELECT * F…ice > 100"
+        );
+        assert_eq!(ellipsize("abc", 0), "");
+        assert_eq!(ellipsize("abc", 1), "…");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("{\"a\": [1, 2]}", 9), "{\"a\": [1…");
+        assert_eq!(truncate("日本語の文", 6), "日本…");
+        assert_eq!(truncate("abc", 1), "…");
+        assert_eq!(truncate("abc", 0), "");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("hello big world", 9), ["hello big", "world"]);
+        assert_eq!(
+            wrap_text("line one\nline two", 20),
+            ["line one", "line two"]
+        );
+        assert_eq!(wrap_text("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap_text("", 3), [""]);
+    }
+
+    #[test]
+    fn test_wrap_text_graphemes() {
+        // combining accents stay with their letter
+        assert_eq!(
+            wrap_text("e\u{301}e\u{301}e\u{301}e\u{301}", 3),
+            ["e\u{301}e\u{301}e\u{301}", "e\u{301}"]
+        );
+        // wide characters take two columns
+        assert_eq!(
+            wrap_text("日本語テキスト", 4),
+            ["日本", "語", "テキ", "スト"]
+        );
+        assert_eq!(wrap_text("héllo wörld", 5), ["héllo", "wörld"]);
+    }
+
+    #[test]
+    fn test_fit_columns() {
+        let widths = [3, 5, 4, 10];
+        assert_eq!(fit_columns(&widths, 0..4, 10), [(0, 3), (1, 5)]);
+        assert_eq!(fit_columns(&widths, 1..4, 12), [(1, 5), (2, 4), (3, 1)]);
+        assert_eq!(fit_columns(&widths, 3..4, 4), [(3, 4)]);
+        assert_eq!(fit_columns(&widths, 0..4, 100).len(), 4);
+    }
+
+    #[test]
+    fn test_cast_series() {
+        assert_eq!(parse_dtype("F64"), Some(DataType::Float64));
+        assert_eq!(parse_dtype("string"), Some(DataType::String));
+        assert_eq!(parse_dtype("decimal"), None);
+
+        let texts = Series::new("flag", [Some("True"), Some("no"), None, Some("false ")]);
+        let flags = cast_series(&texts, &DataType::Boolean).unwrap();
+        assert_eq!(flags.name(), "flag");
+        assert_eq!(
+            flags.bool().unwrap().into_iter().collect::<Vec<_>>(),
+            [Some(true), None, None, Some(false)]
+        );
+        let numbers = cast_series(&Series::new("n", ["1.5", "x"]), &DataType::Float64).unwrap();
+        assert_eq!(numbers.f64().unwrap().get(0), Some(1.5));
+        assert_eq!(numbers.null_count(), 1);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let geo = StructChunked::new("geo", &[Series::new("lat", [1.5, 2.5])])
+            .unwrap()
+            .into_series();
+        let address = StructChunked::new("address", &[Series::new("city", ["Paris", "Nice"]), geo])
+            .unwrap()
+            .into_series();
+        let tags = Series::new(
+            "tags",
+            [
+                Series::new("", ["a", "b"]),
+                Series::new("", Vec::<&str>::new()),
+            ],
+        );
+        let df = DataFrame::new(vec![Series::new("id", [1, 2]), address, tags]).unwrap();
+
+        let flat = flatten(df.clone(), 8, false).unwrap();
+        assert_eq!(
+            flat.get_column_names(),
+            ["id", "address.city", "address.geo.lat", "tags"]
+        );
+        assert_eq!(flat.height(), 2);
+
+        let shallow = flatten(df.clone(), 1, false).unwrap();
+        assert_eq!(
+            shallow.get_column_names(),
+            ["id", "address.city", "address.geo", "tags"]
+        );
+
+        let exploded = flatten(df, 8, true).unwrap();
+        assert_eq!(exploded.height(), 3);
+        assert_eq!(
+            exploded.column("tags").unwrap().str().unwrap().get(1),
+            Some("b")
+        );
+        assert_eq!(exploded.column("tags").unwrap().null_count(), 1);
+    }
+
+    #[test]
+    fn test_infer_temporal_safe_dates() {
+        let mut df = df! {
+            "day_first" => [Some("31/12/2024"), None, Some("01/02/2023")],
+            "partial" => [Some("31/12/2024"), Some("2024-12-31"), None],
+        }
+        .unwrap();
+        infer_temporal_safe(&mut df, Some("%d/%m/%Y"), None);
+
+        let dates = df.column("day_first").unwrap();
+        assert_eq!(dates.dtype(), &DataType::Date);
+        assert_eq!(dates.null_count(), 1);
+        assert_eq!(
+            dates.get(0).unwrap(),
+            AnyValue::Date(
+                (NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()
+                    - NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
+                .num_days() as i32
+            )
+        );
+        assert_eq!(df.column("partial").unwrap().dtype(), &DataType::String);
+    }
+
+    #[test]
+    fn test_infer_temporal_safe_datetimes_with_timezone() {
+        let mut df = df! {
+            "ts" => ["2024-12-31T23:00:00+02:00", "2024-01-01T00:00:00Z"],
+            "local" => ["2024-12-31T23:00:00", "2024-01-01T00:00:00"],
+        }
+        .unwrap();
+        infer_temporal_safe(&mut df, None, Some("%Y-%m-%dT%H:%M:%S%#z"));
+
+        let ts = df.column("ts").unwrap();
+        assert_eq!(
+            ts.dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, Some("UTC".to_owned()))
+        );
+        assert_eq!(
+            ts.cast(&DataType::Int64).unwrap().i64().unwrap().get(0),
+            Some(1735678800000000)
+        );
+        // the offset is mandatory in the format, so values without one do not parse
+        assert_eq!(df.column("local").unwrap().dtype(), &DataType::String);
+
+        infer_temporal_safe(&mut df, None, Some("%Y-%m-%dT%H:%M:%S"));
+        assert_eq!(
+            df.column("local").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+    }
+
     #[test]
     fn test_infer_schema_safe_basic() {
         let mut df = df! {